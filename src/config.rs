@@ -0,0 +1,151 @@
+use std::env;
+
+/// Runtime settings read from the environment once at startup.
+/// See `.env.example` for the variables and their defaults.
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// Sessions expire after this many hours without a request.
+    pub session_ttl_hours: i64,
+    /// Mark the session cookie `Secure` (HTTPS only). Enable in production.
+    pub secure_cookies: bool,
+    /// How often the background task purges expired session rows.
+    pub session_cleanup_interval_secs: u64,
+    /// Public origin used to build links in emails, without a trailing slash.
+    pub app_base_url: String,
+    /// Sender address for outgoing mail.
+    pub mail_from: String,
+    pub mail_transport: MailTransport,
+    /// Password reset links stop working after this many minutes.
+    pub password_reset_ttl_minutes: i64,
+    /// Email verification links stop working after this many hours.
+    pub email_verification_ttl_hours: i64,
+    pub unverified_policy: UnverifiedPolicy,
+    /// Failed logins in a row before an account is temporarily locked.
+    pub login_max_failures_per_account: i64,
+    /// Failed logins from one IP address within an hour before it is locked.
+    pub login_max_failures_per_ip: i64,
+    /// Length of the first lockout; each further failure doubles it.
+    pub login_lockout_base_secs: i64,
+    /// Upper bound for a single lockout.
+    pub login_lockout_max_secs: i64,
+    /// Take the client address from `X-Forwarded-For`. Only enable behind a
+    /// reverse proxy (Railway) that appends to the header; exposed directly,
+    /// clients could claim any address.
+    pub trust_proxy_headers: bool,
+    /// Reverse proxies in front of the app. The client address is the entry
+    /// this many hops from the right of `X-Forwarded-For`, the one our
+    /// outermost proxy appended; entries to its left are client-supplied.
+    pub trusted_proxy_hops: usize,
+}
+
+/// What accounts with an unverified email may do, selected with
+/// `UNVERIFIED_USER_POLICY`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnverifiedPolicy {
+    /// No restrictions (`allow`).
+    Allow,
+    /// Everything except adding videos (`no-uploads`, the default).
+    NoUploads,
+    /// Browse only: no videos, sessions or journal entries (`read-only`).
+    ReadOnly,
+}
+
+/// Actions that may be withheld from unverified accounts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Capability {
+    UploadVideos,
+    LogActivity,
+}
+
+impl UnverifiedPolicy {
+    pub fn permits(self, capability: Capability) -> bool {
+        match self {
+            UnverifiedPolicy::Allow => true,
+            UnverifiedPolicy::NoUploads => capability != Capability::UploadVideos,
+            UnverifiedPolicy::ReadOnly => false,
+        }
+    }
+}
+
+/// Where outgoing email goes, selected with `MAIL_TRANSPORT`.
+#[derive(Clone, Debug)]
+pub enum MailTransport {
+    /// Print messages to stdout (default; handy for local development).
+    Log,
+    /// Write each message as an `.eml` file into a directory.
+    File { dir: String },
+    /// Deliver through an SMTP relay using STARTTLS.
+    Smtp {
+        host: String,
+        port: u16,
+        username: String,
+        password: String,
+    },
+}
+
+impl Config {
+    pub fn from_env() -> Self {
+        Config {
+            session_ttl_hours: env_parse("SESSION_TTL_HOURS", 24 * 7),
+            secure_cookies: env_flag("SESSION_SECURE_COOKIES", false),
+            session_cleanup_interval_secs: env_parse("SESSION_CLEANUP_INTERVAL_SECS", 60 * 60),
+            app_base_url: env::var("APP_BASE_URL")
+                .unwrap_or_else(|_| "http://localhost:3000".to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            mail_from: env::var("MAIL_FROM")
+                .unwrap_or_else(|_| "CalmControl <no-reply@calmcontrol.app>".to_string()),
+            mail_transport: mail_transport_from_env(),
+            password_reset_ttl_minutes: env_parse("PASSWORD_RESET_TTL_MINUTES", 60),
+            email_verification_ttl_hours: env_parse("EMAIL_VERIFICATION_TTL_HOURS", 48),
+            unverified_policy: match env::var("UNVERIFIED_USER_POLICY")
+                .unwrap_or_default()
+                .trim()
+            {
+                "allow" => UnverifiedPolicy::Allow,
+                "read-only" => UnverifiedPolicy::ReadOnly,
+                _ => UnverifiedPolicy::NoUploads,
+            },
+            login_max_failures_per_account: env_parse("LOGIN_MAX_FAILURES_PER_ACCOUNT", 5),
+            login_max_failures_per_ip: env_parse("LOGIN_MAX_FAILURES_PER_IP", 20),
+            login_lockout_base_secs: env_parse("LOGIN_LOCKOUT_BASE_SECS", 60),
+            login_lockout_max_secs: env_parse("LOGIN_LOCKOUT_MAX_SECS", 60 * 60),
+            trust_proxy_headers: env_flag("TRUST_PROXY_HEADERS", false),
+            trusted_proxy_hops: env_parse("TRUSTED_PROXY_HOPS", 1).max(1),
+        }
+    }
+}
+
+fn mail_transport_from_env() -> MailTransport {
+    match env::var("MAIL_TRANSPORT").unwrap_or_default().trim() {
+        "smtp" => MailTransport::Smtp {
+            host: env::var("SMTP_HOST").unwrap_or_default(),
+            port: env_parse("SMTP_PORT", 587),
+            username: env::var("SMTP_USERNAME").unwrap_or_default(),
+            password: env::var("SMTP_PASSWORD").unwrap_or_default(),
+        },
+        "file" => MailTransport::File {
+            dir: env::var("MAIL_DIR").unwrap_or_else(|_| "mail".to_string()),
+        },
+        _ => MailTransport::Log,
+    }
+}
+
+// ── Helpers ────────────────────────────────────────────────────────────────────
+
+fn env_parse<T: std::str::FromStr>(key: &str, default: T) -> T {
+    env::var(key)
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(default)
+}
+
+fn env_flag(key: &str, default: bool) -> bool {
+    match env::var(key) {
+        Ok(v) => matches!(
+            v.trim().to_lowercase().as_str(),
+            "1" | "true" | "yes" | "on"
+        ),
+        Err(_) => default,
+    }
+}
@@ -0,0 +1,209 @@
+//! Envelope encryption for journal notes. Each user has a random data key that
+//! encrypts their notes (AES-256-GCM); the data key is stored wrapped by a
+//! master key that only ever lives in the environment. Rotating the master key
+//! means re-wrapping the data keys, never re-encrypting the notes.
+
+use aes_gcm::{
+    Aes256Gcm, Key, Nonce,
+    aead::{Aead, AeadCore, KeyInit, OsRng, Payload},
+};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use std::{env, fmt};
+
+/// Marks an encrypted note. Notes without it predate encryption.
+pub const ENCRYPTED_PREFIX: &str = "enc1:";
+const NONCE_LEN: usize = 12;
+/// Bytes of HMAC kept per search token; plenty to make collisions negligible.
+const SEARCH_TOKEN_LEN: usize = 16;
+
+/// A master key, identified by a fingerprint so a wrapped data key records
+/// which master key it needs.
+#[derive(Clone)]
+pub struct MasterKey {
+    id: String,
+    cipher: Aes256Gcm,
+}
+
+impl MasterKey {
+    /// Parse a key given as 64 hex characters (`openssl rand -hex 32`).
+    pub fn from_hex(hex: &str) -> Result<Self, String> {
+        let hex = hex.trim();
+        if hex.len() != 64 || !hex.is_ascii() {
+            return Err("Master keys must be 64 hex characters (32 bytes).".to_string());
+        }
+        let mut bytes = [0u8; 32];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+                .map_err(|_| "Master keys must be 64 hex characters (32 bytes).".to_string())?;
+        }
+
+        let digest = Sha256::digest(bytes);
+        let id = digest[..8].iter().map(|b| format!("{b:02x}")).collect();
+        Ok(MasterKey {
+            id,
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&bytes)),
+        })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+/// The current master key plus any previous ones still needed to unwrap data
+/// keys that haven't been rotated yet.
+#[derive(Clone)]
+pub struct Keyring {
+    current: MasterKey,
+    previous: Vec<MasterKey>,
+}
+
+impl fmt::Debug for Keyring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let previous: Vec<&str> = self.previous.iter().map(MasterKey::id).collect();
+        f.debug_struct("Keyring")
+            .field("current", &self.current.id)
+            .field("previous", &previous)
+            .finish()
+    }
+}
+
+impl Keyring {
+    /// `JOURNAL_MASTER_KEY`, plus the comma-separated
+    /// `JOURNAL_PREVIOUS_MASTER_KEYS` kept around while rotating.
+    pub fn from_env() -> Result<Self, String> {
+        let current = env::var("JOURNAL_MASTER_KEY").map_err(
+            |_| "JOURNAL_MASTER_KEY is not set. Generate one with: openssl rand -hex 32",
+        )?;
+        let current =
+            MasterKey::from_hex(&current).map_err(|e| format!("JOURNAL_MASTER_KEY: {e}"))?;
+
+        let previous = env::var("JOURNAL_PREVIOUS_MASTER_KEYS")
+            .unwrap_or_default()
+            .split(',')
+            .filter(|k| !k.trim().is_empty())
+            .map(|k| {
+                MasterKey::from_hex(k).map_err(|e| format!("JOURNAL_PREVIOUS_MASTER_KEYS: {e}"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Keyring { current, previous })
+    }
+
+    /// A keyring with a fixed master key, for tests.
+    #[cfg(test)]
+    pub fn for_tests() -> Self {
+        Keyring {
+            current: MasterKey::from_hex(&"42".repeat(32)).expect("valid test key"),
+            previous: Vec::new(),
+        }
+    }
+
+    pub fn current_id(&self) -> &str {
+        self.current.id()
+    }
+
+    /// Encrypt a data key under the current master key. Returns the wrapped
+    /// bytes and the id of the master key used.
+    pub fn wrap(&self, key: &DataKey) -> Result<(Vec<u8>, String), String> {
+        let wrapped = seal(&self.current.cipher, &key.bytes, self.current.id.as_bytes())?;
+        Ok((wrapped, self.current.id.clone()))
+    }
+
+    /// Decrypt a data key wrapped by the master key `master_key_id`.
+    pub fn unwrap(&self, wrapped: &[u8], master_key_id: &str) -> Result<DataKey, String> {
+        let master = std::iter::once(&self.current)
+            .chain(&self.previous)
+            .find(|k| k.id == master_key_id)
+            .ok_or_else(|| format!("Master key {master_key_id} is not configured."))?;
+
+        let bytes = open(&master.cipher, wrapped, master_key_id.as_bytes())?;
+        let bytes: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| "Wrapped data key has the wrong length.".to_string())?;
+        Ok(DataKey { bytes })
+    }
+}
+
+/// A user's key for their journal notes.
+pub struct DataKey {
+    bytes: [u8; 32],
+}
+
+impl DataKey {
+    pub fn generate() -> Self {
+        let mut bytes = [0u8; 32];
+        OsRng.fill_bytes(&mut bytes);
+        DataKey { bytes }
+    }
+
+    fn cipher(&self) -> Aes256Gcm {
+        Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&self.bytes))
+    }
+
+    /// Encrypt a note for storage. `context` (the entry id) is authenticated
+    /// too, so a ciphertext can't be moved onto another entry.
+    pub fn encrypt(&self, plaintext: &str, context: &str) -> Result<String, String> {
+        let sealed = seal(&self.cipher(), plaintext.as_bytes(), context.as_bytes())?;
+        Ok(format!("{ENCRYPTED_PREFIX}{}", BASE64.encode(sealed)))
+    }
+
+    /// Decrypt a stored note. Notes written before encryption come back as-is.
+    pub fn decrypt(&self, stored: &str, context: &str) -> Result<String, String> {
+        let Some(encoded) = stored.strip_prefix(ENCRYPTED_PREFIX) else {
+            return Ok(stored.to_string());
+        };
+        let sealed = BASE64.decode(encoded).map_err(|e| e.to_string())?;
+        let bytes = open(&self.cipher(), &sealed, context.as_bytes())?;
+        String::from_utf8(bytes).map_err(|e| e.to_string())
+    }
+
+    /// Blind index token for a search word: a keyed hash, so the index can be
+    /// queried for a word without storing the word itself. Tokens differ
+    /// between users because their keys do.
+    pub fn search_token(&self, word: &str) -> Vec<u8> {
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(&self.bytes)
+            .expect("HMAC accepts any key length");
+        mac.update(b"journal-search:");
+        mac.update(word.as_bytes());
+        mac.finalize().into_bytes()[..SEARCH_TOKEN_LEN].to_vec()
+    }
+}
+
+pub fn is_encrypted(stored: &str) -> bool {
+    stored.starts_with(ENCRYPTED_PREFIX)
+}
+
+/// Encrypt with a fresh random nonce, returned as `nonce || ciphertext`.
+fn seal(cipher: &Aes256Gcm, plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>, String> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext,
+                aad,
+            },
+        )
+        .map_err(|_| "Encryption failed.".to_string())?;
+    Ok([nonce.as_slice(), &ciphertext].concat())
+}
+
+fn open(cipher: &Aes256Gcm, sealed: &[u8], aad: &[u8]) -> Result<Vec<u8>, String> {
+    if sealed.len() < NONCE_LEN {
+        return Err("Ciphertext is too short.".to_string());
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    cipher
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad,
+            },
+        )
+        .map_err(|_| "Decryption failed: wrong key or corrupted data.".to_string())
+}
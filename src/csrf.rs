@@ -0,0 +1,202 @@
+//! Synchronizer-token CSRF protection. Each session gets a random token that
+//! every form posts back as `_csrf`; the middleware refuses state-changing
+//! requests whose token is missing or doesn't match. Public pages viewed
+//! without a session use a double-submit cookie instead, so browsing them
+//! doesn't create a session row.
+
+use axum::{
+    body::{Body, to_bytes},
+    extract::Request,
+    http::{HeaderMap, HeaderValue, Method, StatusCode, header},
+    middleware::Next,
+    response::{Html, IntoResponse, Response},
+};
+use serde::Deserialize;
+use tower_sessions::Session;
+
+use crate::{handlers::auth::SESSION_USER_KEY, templates, tokens};
+
+/// Name of the hidden form field carrying the token.
+pub const FORM_FIELD: &str = "_csrf";
+/// Alternative to the form field for scripted requests.
+const HEADER: &str = "x-csrf-token";
+const SESSION_KEY: &str = "csrf_token";
+/// Double-submit cookie for visitors without a session.
+const COOKIE_NAME: &str = "calm_csrf";
+/// Same limit axum applies to `Form` bodies.
+const MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+#[derive(Deserialize)]
+struct TokenField {
+    #[serde(rename = "_csrf")]
+    csrf: Option<String>,
+}
+
+/// The session's CSRF token, created on first use.
+pub async fn token(session: &Session) -> String {
+    if let Some(t) = session.get::<String>(SESSION_KEY).await.ok().flatten() {
+        return t;
+    }
+    let (raw, _) = tokens::generate();
+    let _ = session.insert(SESSION_KEY, raw.clone()).await;
+    raw
+}
+
+/// Replace the session's token with a fresh one. Called whenever a session
+/// signs in, so a token handed out before authentication stops working.
+pub async fn rotate(session: &Session) {
+    let (raw, _) = tokens::generate();
+    let _ = session.insert(SESSION_KEY, raw).await;
+}
+
+/// A token for a public page, plus the cookie to set if it's a new
+/// double-submit token.
+pub struct PublicToken {
+    pub value: String,
+    cookie: Option<HeaderValue>,
+}
+
+impl PublicToken {
+    /// `response` with the token's cookie attached, if it needs one.
+    pub fn respond(&self, response: impl IntoResponse) -> Response {
+        let mut response = response.into_response();
+        if let Some(cookie) = &self.cookie {
+            response
+                .headers_mut()
+                .append(header::SET_COOKIE, cookie.clone());
+        }
+        response
+    }
+}
+
+/// The token for a form on a page anyone can view. Signed-in users and
+/// sessions that already hold a token use the session's; anyone else uses (or
+/// is issued) the double-submit cookie.
+pub async fn public_token(session: &Session, headers: &HeaderMap, secure: bool) -> PublicToken {
+    let has_session_token = session
+        .get::<String>(SESSION_KEY)
+        .await
+        .ok()
+        .flatten()
+        .is_some();
+    let logged_in = session
+        .get::<String>(SESSION_USER_KEY)
+        .await
+        .ok()
+        .flatten()
+        .is_some();
+    if has_session_token || logged_in {
+        return PublicToken {
+            value: token(session).await,
+            cookie: None,
+        };
+    }
+    if let Some(value) = cookie_token(headers) {
+        return PublicToken {
+            value: value.to_string(),
+            cookie: None,
+        };
+    }
+
+    let (value, _) = tokens::generate();
+    let secure = if secure { "; Secure" } else { "" };
+    let cookie = HeaderValue::from_str(&format!(
+        "{COOKIE_NAME}={value}; Path=/; HttpOnly; SameSite=Lax{secure}"
+    ))
+    .ok();
+    PublicToken { value, cookie }
+}
+
+/// The double-submit token from the request's cookies, if well-formed.
+fn cookie_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .filter_map(|pair| pair.trim().strip_prefix(COOKIE_NAME)?.strip_prefix('='))
+        .find(|t| !t.is_empty() && t.bytes().all(|b| b.is_ascii_alphanumeric()))
+}
+
+/// Middleware checking the token on every non-safe request. The bearer-token
+/// JSON API under `/api/` is exempt since browsers can't attach its key.
+pub async fn verify(session: Session, request: Request, next: Next) -> Response {
+    let safe = matches!(
+        *request.method(),
+        Method::GET | Method::HEAD | Method::OPTIONS
+    );
+    if safe || request.uri().path().starts_with("/api/") {
+        return next.run(request).await;
+    }
+
+    let (parts, body) = request.into_parts();
+    let bytes = match to_bytes(body, MAX_BODY_BYTES).await {
+        Ok(b) => b,
+        Err(_) => return StatusCode::PAYLOAD_TOO_LARGE.into_response(),
+    };
+
+    let submitted = parts
+        .headers
+        .get(HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+        .or_else(|| {
+            serde_urlencoded::from_bytes::<TokenField>(&bytes)
+                .ok()
+                .and_then(|f| f.csrf)
+        });
+    let logged_in = session
+        .get::<String>(SESSION_USER_KEY)
+        .await
+        .ok()
+        .flatten()
+        .is_some();
+    // The cookie only stands in for visitors with neither a session token nor
+    // an account, so it can never authorise a signed-in request
+    let expected = match session.get::<String>(SESSION_KEY).await.ok().flatten() {
+        Some(token) => Some(token),
+        None if !logged_in => cookie_token(&parts.headers).map(str::to_string),
+        None => None,
+    };
+
+    match (submitted, expected) {
+        (Some(s), Some(e)) if constant_time_eq(s.as_bytes(), e.as_bytes()) => {
+            next.run(Request::from_parts(parts, Body::from(bytes)))
+                .await
+        }
+        _ => (
+            StatusCode::FORBIDDEN,
+            Html(templates::csrf_failed_page(logged_in)),
+        )
+            .into_response(),
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cookies(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::COOKIE, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn finds_the_double_submit_cookie_among_others() {
+        let headers = cookies("id=abc; calm_csrf=0f3a9c; theme=dark");
+        assert_eq!(cookie_token(&headers), Some("0f3a9c"));
+    }
+
+    #[test]
+    fn ignores_missing_empty_and_malformed_cookies() {
+        assert_eq!(cookie_token(&HeaderMap::new()), None);
+        assert_eq!(cookie_token(&cookies("calm_csrf=")), None);
+        assert_eq!(cookie_token(&cookies("calm_csrf_old=abc")), None);
+        assert_eq!(cookie_token(&cookies("calm_csrf=a\"b")), None);
+    }
+}
@@ -4,98 +4,45 @@ use sqlx::{
 };
 use std::str::FromStr;
 
-pub async fn create_pool(database_url: &str) -> Result<SqlitePool, sqlx::Error> {
-    let options = SqliteConnectOptions::from_str(database_url)?.create_if_missing(true);
+use crate::migrations;
 
-    let pool = SqlitePoolOptions::new()
+/// Open the database without touching its schema. Deleted and overwritten
+/// rows are zeroed (`secure_delete`), so journal plaintext replaced by
+/// encryption doesn't survive in free pages.
+pub async fn connect(database_url: &str) -> Result<SqlitePool, sqlx::Error> {
+    let options = SqliteConnectOptions::from_str(database_url)?
+        .create_if_missing(true)
+        .pragma("secure_delete", "ON");
+
+    SqlitePoolOptions::new()
         .max_connections(5)
         .connect_with(options)
-        .await?;
-
-    init_schema(&pool).await?;
-
-    Ok(pool)
+        .await
 }
 
-async fn init_schema(pool: &SqlitePool) -> Result<(), sqlx::Error> {
-    sqlx::query(
-        "CREATE TABLE IF NOT EXISTS users (
-            id            TEXT PRIMARY KEY,
-            name          TEXT NOT NULL,
-            email         TEXT UNIQUE NOT NULL,
-            password_hash TEXT NOT NULL,
-            created_at    TEXT NOT NULL DEFAULT (datetime('now'))
-        )",
-    )
-    .execute(pool)
-    .await?;
-
-    sqlx::query(
-        "CREATE TABLE IF NOT EXISTS mindful_sessions (
-            id           TEXT PRIMARY KEY,
-            user_id      TEXT NOT NULL,
-            session_type TEXT NOT NULL,
-            duration_min INTEGER NOT NULL DEFAULT 0,
-            completed_at TEXT NOT NULL DEFAULT (datetime('now')),
-            FOREIGN KEY (user_id) REFERENCES users(id)
-        )",
-    )
-    .execute(pool)
-    .await?;
-
-    sqlx::query(
-        "CREATE TABLE IF NOT EXISTS journal_entries (
-            id         TEXT PRIMARY KEY,
-            user_id    TEXT NOT NULL,
-            mood       INTEGER NOT NULL CHECK(mood BETWEEN 1 AND 5),
-            note       TEXT NOT NULL DEFAULT '',
-            created_at TEXT NOT NULL DEFAULT (datetime('now')),
-            FOREIGN KEY (user_id) REFERENCES users(id)
-        )",
-    )
-    .execute(pool)
-    .await?;
-
-    sqlx::query(
-        "CREATE TABLE IF NOT EXISTS videos (
-            id            TEXT PRIMARY KEY,
-            user_id       TEXT NOT NULL,
-            title         TEXT NOT NULL,
-            description   TEXT NOT NULL DEFAULT '',
-            video_url     TEXT NOT NULL,
-            thumbnail_url TEXT NOT NULL DEFAULT '',
-            category      TEXT NOT NULL DEFAULT 'general',
-            created_at    TEXT NOT NULL DEFAULT (datetime('now')),
-            FOREIGN KEY (user_id) REFERENCES users(id)
-        )",
-    )
-    .execute(pool)
-    .await?;
+/// Open the database and bring its schema up to date.
+pub async fn create_pool(database_url: &str) -> Result<SqlitePool, sqlx::Error> {
+    let pool = connect(database_url).await?;
 
-    sqlx::query(
-        "CREATE TABLE IF NOT EXISTS newsletter_subscribers (
-            id                TEXT PRIMARY KEY,
-            email             TEXT UNIQUE NOT NULL,
-            name              TEXT NOT NULL DEFAULT '',
-            unsubscribe_token TEXT UNIQUE NOT NULL,
-            subscribed_at     TEXT NOT NULL DEFAULT (datetime('now'))
-        )",
-    )
-    .execute(pool)
-    .await?;
+    migrations::run_pending(&pool).await?;
 
-    sqlx::query(
-        "CREATE TABLE IF NOT EXISTS newsletter_articles (
-            id           TEXT PRIMARY KEY,
-            title        TEXT NOT NULL,
-            summary      TEXT NOT NULL DEFAULT '',
-            content_html TEXT NOT NULL DEFAULT '',
-            source_urls  TEXT NOT NULL DEFAULT '',
-            published_at TEXT NOT NULL DEFAULT (datetime('now'))
-        )",
-    )
-    .execute(pool)
-    .await?;
+    Ok(pool)
+}
 
-    Ok(())
+/// A private in-memory database with the current schema, for tests. Each
+/// in-memory connection is its own database, so the pool keeps exactly one
+/// open for good.
+#[cfg(test)]
+pub async fn test_pool() -> SqlitePool {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect("sqlite::memory:")
+        .await
+        .expect("in-memory database opens");
+    migrations::run_pending(&pool)
+        .await
+        .expect("migrations apply to an empty database");
+    pool
 }
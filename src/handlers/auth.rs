@@ -1,14 +1,29 @@
 use axum::{
-    extract::{Form, State},
+    extract::{ConnectInfo, Form, Query, State},
+    http::{HeaderMap, StatusCode},
     response::{Html, IntoResponse, Redirect, Response},
 };
+use chrono::Utc;
+use chrono_tz::Tz;
 use serde::Deserialize;
-use std::sync::Arc;
+use std::{net::SocketAddr, sync::Arc};
 use tower_sessions::Session;
 
-use crate::{state::AppState, templates};
+use crate::{
+    config::Capability,
+    csrf,
+    handlers::two_factor,
+    mailer::Email,
+    models::{
+        login_attempt::AttemptOutcome,
+        user::{User, parse_timezone},
+    },
+    state::AppState,
+    templates, tokens,
+};
 
-pub const SESSION_USER_KEY: &str = "user_email";
+/// Id of the signed-in user. Never the email address, which can change hands.
+pub const SESSION_USER_KEY: &str = "user_id";
 
 #[derive(Deserialize)]
 pub struct LoginForm {
@@ -22,31 +37,233 @@ pub struct RegisterForm {
     pub email: String,
     pub password: String,
     pub confirm_password: String,
+    /// Filled in from the browser; blank if scripts are off.
+    #[serde(default)]
+    pub timezone: String,
+}
+
+#[derive(Deserialize)]
+pub struct LoginQuery {
+    pub reset: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ForgotPasswordForm {
+    pub email: String,
+}
+
+#[derive(Deserialize)]
+pub struct ResetPasswordQuery {
+    pub token: String,
+}
+
+#[derive(Deserialize)]
+pub struct VerifyEmailQuery {
+    pub token: String,
+}
+
+#[derive(Deserialize)]
+pub struct ResetPasswordForm {
+    pub token: String,
+    pub password: String,
+    pub confirm_password: String,
+}
+
+// ── Helpers ────────────────────────────────────────────────────────────────────
+
+/// The logged-in user, or a redirect to the login page.
+pub async fn require_user(session: &Session, state: &Arc<AppState>) -> Result<User, Response> {
+    let user_id = session
+        .get::<String>(SESSION_USER_KEY)
+        .await
+        .ok()
+        .flatten()
+        .ok_or_else(|| Redirect::to("/login").into_response())?;
+
+    state
+        .user_store
+        .find_by_id(&user_id)
+        .await
+        .ok_or_else(|| Redirect::to("/login").into_response())
+}
+
+/// Mark the session as belonging to `user_id`. The session id and CSRF token
+/// both change on sign-in, so neither can be planted or harvested beforehand.
+pub async fn sign_in(session: &Session, user_id: String) {
+    let _ = session.cycle_id().await;
+    let _ = session.insert(SESSION_USER_KEY, user_id).await;
+    csrf::rotate(session).await;
+}
+
+// ── Brute-force protection ─────────────────────────────────────────────────────
+
+/// Address of the client making the request. Behind trusted proxies this is
+/// the `X-Forwarded-For` entry our outermost proxy appended, never one the
+/// client wrote itself.
+pub fn client_ip(state: &AppState, headers: &HeaderMap, addr: SocketAddr) -> String {
+    if state.config.trust_proxy_headers {
+        let forwarded = headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .collect::<Vec<_>>()
+            .join(",");
+        if let Some(ip) = forwarded_client(&forwarded, state.config.trusted_proxy_hops) {
+            return ip.to_string();
+        }
+    }
+    addr.ip().to_string()
+}
+
+/// The `hops`-th entry from the right of an `X-Forwarded-For` value, if the
+/// header has that many and it is an IP address.
+fn forwarded_client(header: &str, hops: usize) -> Option<&str> {
+    header
+        .split(',')
+        .map(str::trim)
+        .rev()
+        .nth(hops.checked_sub(1)?)
+        .filter(|ip| ip.parse::<std::net::IpAddr>().is_ok())
+}
+
+/// If the account or the IP address is locked out, record the refused attempt
+/// and return the message to show instead of checking credentials.
+pub async fn lockout_message(state: &AppState, email: &str, ip: &str) -> Option<String> {
+    let cfg = &state.config;
+    let account = state
+        .user_store
+        .account_failures(email)
+        .await
+        .lockout_remaining(
+            cfg.login_max_failures_per_account,
+            cfg.login_lockout_base_secs,
+            cfg.login_lockout_max_secs,
+        );
+    let by_ip = state.user_store.ip_failures(ip).await.lockout_remaining(
+        cfg.login_max_failures_per_ip,
+        cfg.login_lockout_base_secs,
+        cfg.login_lockout_max_secs,
+    );
+
+    let remaining = account.max(by_ip)?;
+    let _ = state
+        .user_store
+        .record_login_attempt(email, ip, AttemptOutcome::Locked)
+        .await;
+
+    let minutes = (remaining + 59) / 60;
+    Some(format!(
+        "Too many failed sign-in attempts. Please try again in {minutes} minute{}.",
+        if minutes == 1 { "" } else { "s" }
+    ))
 }
 
-pub async fn show_login_page(session: Session) -> Response {
+/// Log a wrong password or code, and email the account owner the moment the
+/// account becomes locked.
+pub async fn record_login_failure(
+    state: &AppState,
+    email: &str,
+    ip: &str,
+    outcome: AttemptOutcome,
+) {
+    if let Err(e) = state
+        .user_store
+        .record_login_attempt(email, ip, outcome)
+        .await
+    {
+        eprintln!("Failed to record login attempt: {e}");
+        return;
+    }
+
+    let failures = state.user_store.account_failures(email).await.failures;
+    if failures != state.config.login_max_failures_per_account {
+        return;
+    }
+    let Some(user) = state.user_store.find_by_email(email).await else {
+        return;
+    };
+
+    let message = Email {
+        to: user.email.clone(),
+        subject: "Sign-in to your CalmControl account was paused".to_string(),
+        body: format!(
+            "Hi {},\n\n\
+             We saw {failures} failed attempts to sign in to your CalmControl account, \
+             most recently from {ip} at {} UTC, so we've paused sign-in for a little \
+             while.\n\n\
+             If this was you, just wait a few minutes and try again. If it wasn't, \
+             we recommend resetting your password, which also lifts the pause:\n\n\
+             {}/forgot-password\n",
+            user.name,
+            Utc::now().format("%Y-%m-%d %H:%M"),
+            state.config.app_base_url,
+        ),
+    };
+    let mailer = state.mailer.clone();
+    tokio::spawn(async move {
+        if let Err(e) = mailer.send(message).await {
+            eprintln!("Failed to send lockout email: {e}");
+        }
+    });
+}
+
+// ── Handlers ───────────────────────────────────────────────────────────────────
+
+pub async fn show_login_page(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(params): Query<LoginQuery>,
+) -> Response {
     if let Ok(Some(_)) = session.get::<String>(SESSION_USER_KEY).await {
         return Redirect::to("/dashboard").into_response();
     }
-    Html(templates::login_page(None)).into_response()
+    let csrf = csrf::public_token(&session, &headers, state.config.secure_cookies).await;
+    let notice = params
+        .reset
+        .map(|_| "Your password has been reset. Please sign in with your new password.");
+    let page = templates::login_page(&csrf.value, None, notice);
+    csrf.respond(Html(page))
 }
 
 pub async fn process_login(
     session: Session,
     State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     Form(form): Form<LoginForm>,
 ) -> Response {
     let email = form.email.trim().to_lowercase();
+    let ip = client_ip(&state, &headers, addr);
+
+    if let Some(msg) = lockout_message(&state, &email, &ip).await {
+        let csrf = csrf::public_token(&session, &headers, state.config.secure_cookies).await;
+        let page = templates::login_page(&csrf.value, Some(&msg), None);
+        return csrf.respond(Html(page));
+    }
 
     match state.user_store.find_by_email(&email).await {
         Some(user) if user.verify_password(&form.password) => {
-            let _ = session.insert(SESSION_USER_KEY, user.email.clone()).await;
+            if user.has_two_factor() {
+                return two_factor::begin_challenge(&session, &user).await;
+            }
+            let _ = state
+                .user_store
+                .record_login_attempt(&email, &ip, AttemptOutcome::Success)
+                .await;
+            sign_in(&session, user.id).await;
             Redirect::to("/dashboard").into_response()
         }
-        _ => Html(templates::login_page(Some(
-            "Invalid email or password. Please try again.",
-        )))
-        .into_response(),
+        _ => {
+            record_login_failure(&state, &email, &ip, AttemptOutcome::BadPassword).await;
+            let csrf = csrf::public_token(&session, &headers, state.config.secure_cookies).await;
+            let page = templates::login_page(
+                &csrf.value,
+                Some("Invalid email or password. Please try again."),
+                None,
+            );
+            csrf.respond(Html(page))
+        }
     }
 }
 
@@ -55,45 +272,475 @@ pub async fn logout(session: Session) -> Response {
     Redirect::to("/login").into_response()
 }
 
-pub async fn show_register_page(session: Session) -> Response {
+pub async fn show_register_page(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Response {
     if let Ok(Some(_)) = session.get::<String>(SESSION_USER_KEY).await {
         return Redirect::to("/dashboard").into_response();
     }
-    Html(templates::register_page(None)).into_response()
+    let csrf = csrf::public_token(&session, &headers, state.config.secure_cookies).await;
+    let page = templates::register_page(&csrf.value, None);
+    csrf.respond(Html(page))
+}
+
+async fn register_error(
+    session: &Session,
+    state: &AppState,
+    headers: &HeaderMap,
+    msg: &str,
+) -> Response {
+    let csrf = csrf::public_token(session, headers, state.config.secure_cookies).await;
+    let page = templates::register_page(&csrf.value, Some(msg));
+    csrf.respond(Html(page))
 }
 
 pub async fn process_register(
     session: Session,
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Form(form): Form<RegisterForm>,
 ) -> Response {
     let name = form.name.trim().to_string();
     let email = form.email.trim().to_lowercase();
 
     if name.is_empty() || email.is_empty() || form.password.is_empty() {
-        return Html(templates::register_page(Some("All fields are required."))).into_response();
+        return register_error(&session, &state, &headers, "All fields are required.").await;
     }
 
     if form.password != form.confirm_password {
-        return Html(templates::register_page(Some("Passwords do not match."))).into_response();
+        return register_error(&session, &state, &headers, "Passwords do not match.").await;
     }
 
     if form.password.len() < 8 {
-        return Html(templates::register_page(Some(
+        return register_error(
+            &session,
+            &state,
+            &headers,
             "Password must be at least 8 characters long.",
-        )))
-        .into_response();
+        )
+        .await;
     }
 
+    // An unrecognised zone isn't worth failing sign-up over; it can be fixed on the profile
+    let timezone = parse_timezone(&form.timezone).unwrap_or(Tz::UTC);
+
     match state
         .user_store
-        .create_user(name, email.clone(), form.password)
+        .create_user(
+            name,
+            email.clone(),
+            form.password,
+            timezone.name().to_string(),
+        )
         .await
     {
-        Ok(_) => {
-            let _ = session.insert(SESSION_USER_KEY, email).await;
+        Ok(user) => {
+            send_verification_email(&state, &user, &user.email).await;
+            sign_in(&session, user.id).await;
             Redirect::to("/dashboard").into_response()
         }
-        Err(e) => Html(templates::register_page(Some(&e))).into_response(),
+        Err(e) => register_error(&session, &state, &headers, &e).await,
+    }
+}
+
+// ── Password reset ─────────────────────────────────────────────────────────────
+
+pub async fn show_forgot_password(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Response {
+    let csrf = csrf::public_token(&session, &headers, state.config.secure_cookies).await;
+    csrf.respond(Html(templates::forgot_password_page(
+        &csrf.value,
+        false,
+        None,
+    )))
+}
+
+pub async fn process_forgot_password(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Form(form): Form<ForgotPasswordForm>,
+) -> Response {
+    let csrf = csrf::public_token(&session, &headers, state.config.secure_cookies).await;
+    let email = form.email.trim().to_lowercase();
+
+    if email.is_empty() || !email.contains('@') {
+        return csrf.respond(Html(templates::forgot_password_page(
+            &csrf.value,
+            false,
+            Some("Please enter a valid email address."),
+        )));
+    }
+
+    // Same response whether or not the account exists, so this form can't be
+    // used to discover who is registered.
+    if let Some(user) = state.user_store.find_by_email(&email).await {
+        let (raw, hashed) = tokens::generate();
+        let ttl = state.config.password_reset_ttl_minutes;
+
+        match state
+            .user_store
+            .create_password_reset_token(&user.id, &hashed, ttl)
+            .await
+        {
+            Ok(_) => {
+                let link = format!("{}/reset-password?token={raw}", state.config.app_base_url);
+                let email = Email {
+                    to: user.email.clone(),
+                    subject: "Reset your CalmControl password".to_string(),
+                    body: format!(
+                        "Hi {},\n\n\
+                         Someone (hopefully you) asked to reset the password for your \
+                         CalmControl account. Open this link to choose a new one:\n\n\
+                         {link}\n\n\
+                         The link works once and expires in {ttl} minutes. If you didn't \
+                         ask for this, you can safely ignore this email.\n",
+                        user.name
+                    ),
+                };
+                let mailer = state.mailer.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = mailer.send(email).await {
+                        eprintln!("Failed to send password reset email: {e}");
+                    }
+                });
+            }
+            Err(e) => eprintln!("Failed to create password reset token: {e}"),
+        }
+    }
+
+    csrf.respond(Html(templates::forgot_password_page(
+        &csrf.value,
+        true,
+        None,
+    )))
+}
+
+pub async fn show_reset_password(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(params): Query<ResetPasswordQuery>,
+) -> Response {
+    let csrf = csrf::public_token(&session, &headers, state.config.secure_cookies).await;
+    let token = params.token.trim();
+
+    if state
+        .user_store
+        .find_password_reset_user(&tokens::hash(token))
+        .await
+        .is_none()
+    {
+        return csrf.respond(Html(templates::reset_password_page(
+            &csrf.value,
+            "",
+            Some("This reset link is invalid or has expired. Please request a new one."),
+        )));
+    }
+
+    csrf.respond(Html(templates::reset_password_page(
+        &csrf.value,
+        token,
+        None,
+    )))
+}
+
+pub async fn process_reset_password(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Form(form): Form<ResetPasswordForm>,
+) -> Response {
+    let csrf = csrf::public_token(&session, &headers, state.config.secure_cookies).await;
+    let token = form.token.trim();
+
+    if form.password != form.confirm_password {
+        return csrf.respond(Html(templates::reset_password_page(
+            &csrf.value,
+            token,
+            Some("Passwords do not match."),
+        )));
+    }
+
+    if form.password.len() < 8 {
+        return csrf.respond(Html(templates::reset_password_page(
+            &csrf.value,
+            token,
+            Some("Password must be at least 8 characters long."),
+        )));
+    }
+
+    match state
+        .user_store
+        .reset_password(&tokens::hash(token), &form.password)
+        .await
+    {
+        Ok((user_id, email)) => {
+            // Proving ownership of the mailbox lifts any lockout
+            let ip = client_ip(&state, &headers, addr);
+            let _ = state
+                .user_store
+                .record_login_attempt(&email, &ip, AttemptOutcome::PasswordReset)
+                .await;
+            // Anyone holding the old password may also hold a session
+            if let Err(e) = state
+                .session_store
+                .delete_user_sessions(&user_id, None)
+                .await
+            {
+                eprintln!("Failed to sign out sessions for {user_id}: {e}");
+                return csrf.respond(Html(templates::reset_password_page(
+                    &csrf.value,
+                    "",
+                    Some(
+                        "Your password was reset, but we couldn't sign out your other devices. \
+                         Please request a new link and reset it again.",
+                    ),
+                )));
+            }
+            Redirect::to("/login?reset=1").into_response()
+        }
+        Err(e) => csrf.respond(Html(templates::reset_password_page(
+            &csrf.value,
+            "",
+            Some(&e),
+        ))),
+    }
+}
+
+// ── Email verification ─────────────────────────────────────────────────────────
+
+/// Issue a fresh verification token for `email` and mail the link to it.
+/// Failures are logged rather than surfaced; the user can always resend.
+pub async fn send_verification_email(state: &AppState, user: &User, email: &str) {
+    let (raw, hashed) = tokens::generate();
+    let ttl = state.config.email_verification_ttl_hours;
+
+    if let Err(e) = state
+        .user_store
+        .create_email_verification_token(&user.id, email, &hashed, ttl)
+        .await
+    {
+        eprintln!("Failed to create email verification token: {e}");
+        return;
+    }
+
+    let link = format!("{}/verify-email?token={raw}", state.config.app_base_url);
+    let message = Email {
+        to: email.to_string(),
+        subject: "Confirm your CalmControl email address".to_string(),
+        body: format!(
+            "Hi {},\n\n\
+             Please confirm this is your email address by opening the link below:\n\n\
+             {link}\n\n\
+             The link expires in {ttl} hours. If you didn't sign up for CalmControl, \
+             you can ignore this email.\n",
+            user.name
+        ),
+    };
+    let mailer = state.mailer.clone();
+    tokio::spawn(async move {
+        if let Err(e) = mailer.send(message).await {
+            eprintln!("Failed to send verification email: {e}");
+        }
+    });
+}
+
+/// Gate an action behind email verification according to
+/// `UNVERIFIED_USER_POLICY`. Returns the response to send when it's denied.
+pub async fn capability_denied(
+    session: &Session,
+    state: &AppState,
+    user: &User,
+    capability: Capability,
+) -> Option<Response> {
+    if user.is_verified() || state.config.unverified_policy.permits(capability) {
+        return None;
+    }
+    let csrf = csrf::token(session).await;
+    Some(
+        (
+            StatusCode::FORBIDDEN,
+            Html(templates::verification_required_page(&csrf, &user.email)),
+        )
+            .into_response(),
+    )
+}
+
+pub async fn verify_email(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<VerifyEmailQuery>,
+) -> Response {
+    let signed_in_as = session.get::<String>(SESSION_USER_KEY).await.ok().flatten();
+    let logged_in = match &signed_in_as {
+        Some(user_id) => state.user_store.find_by_id(user_id).await.is_some(),
+        None => false,
+    };
+
+    match state
+        .user_store
+        .verify_email(&tokens::hash(params.token.trim()))
+        .await
+    {
+        Ok((user_id, true)) => {
+            // Like a password change, a new address signs out every other device
+            let keep = session
+                .id()
+                .filter(|_| signed_in_as.as_deref() == Some(user_id.as_str()));
+            if let Err(e) = state
+                .session_store
+                .delete_user_sessions(&user_id, keep)
+                .await
+            {
+                eprintln!("Failed to sign out other sessions for {user_id}: {e}");
+                return Html(templates::verify_email_page(
+                    None,
+                    Some(
+                        "Your new address is confirmed, but we couldn't sign out your other \
+                         devices. Change your password from your profile to sign them out.",
+                    ),
+                    logged_in,
+                ))
+                .into_response();
+            }
+            Html(templates::verify_email_page(None, None, logged_in)).into_response()
+        }
+        Ok(_) => Html(templates::verify_email_page(None, None, logged_in)).into_response(),
+        Err(e) => Html(templates::verify_email_page(Some(&e), None, logged_in)).into_response(),
+    }
+}
+
+pub async fn resend_verification(session: Session, State(state): State<Arc<AppState>>) -> Response {
+    let user_id = match session.get::<String>(SESSION_USER_KEY).await.ok().flatten() {
+        Some(id) => id,
+        None => return Redirect::to("/login").into_response(),
+    };
+
+    let user = match state.user_store.find_by_id(&user_id).await {
+        Some(u) => u,
+        None => {
+            let _ = session.flush().await;
+            return Redirect::to("/login").into_response();
+        }
+    };
+
+    if user.is_verified() {
+        return Redirect::to("/dashboard").into_response();
+    }
+
+    send_verification_email(&state, &user, &user.email).await;
+    Html(templates::verification_sent_page(&user.email)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::{forwarded_client, lockout_message, record_login_failure};
+    use crate::mailer::RecordingMailer;
+    use crate::models::login_attempt::AttemptOutcome;
+    use crate::state::AppState;
+
+    #[test]
+    fn takes_the_entry_our_proxy_appended() {
+        // The client wrote "6.6.6.6"; the proxy appended the real address
+        assert_eq!(
+            forwarded_client("6.6.6.6, 203.0.113.7", 1),
+            Some("203.0.113.7")
+        );
+        assert_eq!(forwarded_client("203.0.113.7", 1), Some("203.0.113.7"));
+    }
+
+    #[test]
+    fn counts_hops_from_the_right() {
+        let header = "6.6.6.6, 203.0.113.7, 10.0.0.2";
+        assert_eq!(forwarded_client(header, 2), Some("203.0.113.7"));
+        assert_eq!(forwarded_client(header, 4), None);
+    }
+
+    #[test]
+    fn rejects_values_that_are_not_addresses() {
+        assert_eq!(forwarded_client("", 1), None);
+        assert_eq!(forwarded_client("1.2.3.4, unknown", 1), None);
+        assert_eq!(forwarded_client("1.2.3.4", 0), None);
+    }
+
+    #[tokio::test]
+    async fn account_lockout_emails_the_owner_once() {
+        let mailer = Arc::new(RecordingMailer::default());
+        let state = AppState::for_tests(mailer.clone()).await;
+        state
+            .user_store
+            .create_user(
+                "Ada".to_string(),
+                "ada@example.com".to_string(),
+                "correct horse".to_string(),
+                "UTC".to_string(),
+            )
+            .await
+            .unwrap();
+        let threshold = state.config.login_max_failures_per_account;
+        let ip = "198.51.100.1";
+
+        for _ in 1..threshold {
+            record_login_failure(&state, "ada@example.com", ip, AttemptOutcome::BadPassword).await;
+        }
+        tokio::task::yield_now().await;
+        assert!(
+            lockout_message(&state, "ada@example.com", ip)
+                .await
+                .is_none()
+        );
+        assert!(mailer.sent.lock().unwrap().is_empty());
+
+        record_login_failure(&state, "ada@example.com", ip, AttemptOutcome::BadPassword).await;
+        tokio::task::yield_now().await;
+        assert!(
+            lockout_message(&state, "ada@example.com", ip)
+                .await
+                .is_some()
+        );
+
+        // Further failures extend the lockout without another email
+        record_login_failure(&state, "ada@example.com", ip, AttemptOutcome::BadCode).await;
+        record_login_failure(&state, "ada@example.com", ip, AttemptOutcome::BadPassword).await;
+        tokio::task::yield_now().await;
+
+        let sent = mailer.sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].to, "ada@example.com");
+        assert!(sent[0].body.contains(ip));
+    }
+
+    #[tokio::test]
+    async fn unknown_accounts_get_no_lockout_email() {
+        let mailer = Arc::new(RecordingMailer::default());
+        let state = AppState::for_tests(mailer.clone()).await;
+        let ip = "198.51.100.1";
+
+        for _ in 0..state.config.login_max_failures_per_account {
+            record_login_failure(
+                &state,
+                "nobody@example.com",
+                ip,
+                AttemptOutcome::BadPassword,
+            )
+            .await;
+        }
+        tokio::task::yield_now().await;
+
+        assert!(
+            lockout_message(&state, "nobody@example.com", ip)
+                .await
+                .is_some()
+        );
+        assert!(mailer.sent.lock().unwrap().is_empty());
     }
 }
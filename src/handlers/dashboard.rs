@@ -2,18 +2,22 @@ use axum::{
     extract::State,
     response::{Html, IntoResponse, Redirect, Response},
 };
+use chrono::Duration;
 use std::sync::Arc;
 use tower_sessions::Session;
 
-use crate::{handlers::auth::SESSION_USER_KEY, state::AppState, templates};
+use crate::{csrf, handlers::auth::SESSION_USER_KEY, state::AppState, templates};
+
+/// Days covered by the year-in-practice heatmap.
+const HEATMAP_DAYS: i64 = 365;
 
 pub async fn show_dashboard(session: Session, State(state): State<Arc<AppState>>) -> Response {
-    let user_email = match session.get::<String>(SESSION_USER_KEY).await.ok().flatten() {
-        Some(email) => email,
+    let user_id = match session.get::<String>(SESSION_USER_KEY).await.ok().flatten() {
+        Some(id) => id,
         None => return Redirect::to("/login").into_response(),
     };
 
-    let user = match state.user_store.find_by_email(&user_email).await {
+    let user = match state.user_store.find_by_id(&user_id).await {
         Some(u) => u,
         None => {
             let _ = session.flush().await;
@@ -21,8 +25,23 @@ pub async fn show_dashboard(session: Session, State(state): State<Arc<AppState>>
         }
     };
 
+    let csrf = csrf::token(&session).await;
     let stats = state.user_store.get_stats(&user.id).await;
-    let weekly = state.user_store.get_weekly_minutes(&user.id).await;
+    let goals = state.user_store.get_goal_overview(&user.id).await;
+    let new_badges = state.user_store.take_new_achievements(&user.id).await;
+    let today = user.today();
+    let year = state
+        .user_store
+        .get_daily_activity(&user.id, today - Duration::days(HEATMAP_DAYS - 1), today)
+        .await;
 
-    Html(templates::dashboard_page(&user, &stats, &weekly)).into_response()
+    Html(templates::dashboard_page(
+        &csrf,
+        &user,
+        &stats,
+        &goals,
+        &new_badges,
+        &year,
+    ))
+    .into_response()
 }
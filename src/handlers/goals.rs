@@ -0,0 +1,108 @@
+use axum::{
+    extract::{Form, Query, State},
+    response::{Html, IntoResponse, Redirect, Response},
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use tower_sessions::Session;
+
+use crate::{
+    csrf,
+    handlers::auth::require_user,
+    models::{
+        goals::{GOAL_KINDS, GoalKind, parse_target},
+        user::User,
+    },
+    state::AppState,
+    templates,
+};
+
+/// Periods of history shown per goal on `/goals`.
+const HISTORY_PERIODS: i64 = 8;
+
+#[derive(Deserialize)]
+pub struct GoalsQuery {
+    pub saved: Option<String>,
+}
+
+/// Targets as typed; blank clears a goal.
+#[derive(Deserialize)]
+pub struct GoalsForm {
+    #[serde(default)]
+    pub daily_minutes: String,
+    #[serde(default)]
+    pub weekly_sessions: String,
+    #[serde(default)]
+    pub weekly_journal: String,
+}
+
+impl GoalsForm {
+    fn value(&self, kind: GoalKind) -> &str {
+        match kind {
+            GoalKind::DailyMinutes => &self.daily_minutes,
+            GoalKind::WeeklySessions => &self.weekly_sessions,
+            GoalKind::WeeklyJournal => &self.weekly_journal,
+        }
+    }
+}
+
+async fn render_goals(
+    session: &Session,
+    state: &AppState,
+    user: &User,
+    notice: Option<&str>,
+    error: Option<&str>,
+) -> Response {
+    let csrf = csrf::token(session).await;
+    let goals = state.user_store.get_goals(&user.id).await;
+    let history = state
+        .user_store
+        .get_goal_history(&user.id, HISTORY_PERIODS)
+        .await;
+    Html(templates::goals_page(
+        &csrf, &goals, &history, notice, error,
+    ))
+    .into_response()
+}
+
+pub async fn show_goals(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<GoalsQuery>,
+) -> Response {
+    let user = match require_user(&session, &state).await {
+        Ok(u) => u,
+        Err(r) => return r,
+    };
+
+    let notice = query
+        .saved
+        .is_some()
+        .then_some("Your goals have been saved.");
+    render_goals(&session, &state, &user, notice, None).await
+}
+
+pub async fn update_goals(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    Form(form): Form<GoalsForm>,
+) -> Response {
+    let user = match require_user(&session, &state).await {
+        Ok(u) => u,
+        Err(r) => return r,
+    };
+
+    let targets = match GOAL_KINDS
+        .into_iter()
+        .map(|kind| parse_target(kind, form.value(kind)).map(|t| (kind, t)))
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(t) => t,
+        Err(e) => return render_goals(&session, &state, &user, None, Some(&e)).await,
+    };
+
+    match state.user_store.set_goals(&user.id, &targets).await {
+        Ok(_) => Redirect::to("/goals?saved=1").into_response(),
+        Err(e) => render_goals(&session, &state, &user, None, Some(&e)).await,
+    }
+}
@@ -0,0 +1,131 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::{Html, IntoResponse, Redirect, Response},
+};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tower_sessions::Session;
+
+use crate::{
+    csrf,
+    handlers::auth::require_user,
+    models::{
+        breathing::{BreathingPattern, builtin_patterns},
+        history::{HISTORY_TYPES, HistoryFilter},
+    },
+    state::AppState,
+    templates,
+};
+
+#[derive(Default, Deserialize, Serialize)]
+pub struct HistoryQuery {
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub item_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<i64>,
+    #[serde(skip_serializing)]
+    pub deleted: Option<String>,
+}
+
+/// Parse optional `YYYY-MM-DD` bounds from a filter form, swapping them if
+/// they're the wrong way round. Invalid dates are dropped with a message.
+pub(crate) fn parse_date_range(
+    from: Option<&str>,
+    to: Option<&str>,
+) -> (Option<NaiveDate>, Option<NaiveDate>, Option<&'static str>) {
+    let mut error = None;
+    let mut parse = |value: Option<&str>| match value.map(str::trim) {
+        None | Some("") => None,
+        Some(v) => {
+            let date = NaiveDate::parse_from_str(v, "%Y-%m-%d").ok();
+            if date.is_none() {
+                error = Some("Dates must be in YYYY-MM-DD format.");
+            }
+            date
+        }
+    };
+    let mut from = parse(from);
+    let mut to = parse(to);
+
+    if let (Some(f), Some(t)) = (from, to)
+        && f > t
+    {
+        (from, to) = (to, from);
+    }
+    (from, to, error)
+}
+
+/// Turn the raw query into a filter, dropping anything invalid and saying why.
+fn parse_filter(query: &HistoryQuery) -> (HistoryFilter, Option<&'static str>) {
+    let item_type = query
+        .item_type
+        .as_deref()
+        .filter(|t| HISTORY_TYPES.iter().any(|(v, _)| v == t))
+        .map(str::to_string);
+
+    let (from, to, error) = parse_date_range(query.from.as_deref(), query.to.as_deref());
+
+    let filter = HistoryFilter {
+        item_type,
+        from,
+        to,
+        page: query.page.unwrap_or(1).max(1),
+    };
+    (filter, error)
+}
+
+pub async fn show_history(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<HistoryQuery>,
+) -> Response {
+    let user = match require_user(&session, &state).await {
+        Ok(u) => u,
+        Err(r) => return r,
+    };
+
+    let (filter, error) = parse_filter(&query);
+    let page = state.user_store.get_history(&user.id, &filter).await;
+
+    let patterns: Vec<BreathingPattern> = builtin_patterns()
+        .into_iter()
+        .chain(state.user_store.get_breathing_patterns(&user.id).await)
+        .collect();
+    let notice = query
+        .deleted
+        .is_some()
+        .then_some("Session deleted. Your streak and totals have been updated.");
+    let csrf = csrf::token(&session).await;
+
+    Html(templates::history_page(
+        &csrf, &page, &filter, &patterns, error, notice,
+    ))
+    .into_response()
+}
+
+pub async fn delete_session(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(query): Query<HistoryQuery>,
+) -> Response {
+    let user = match require_user(&session, &state).await {
+        Ok(u) => u,
+        Err(r) => return r,
+    };
+
+    let back = serde_urlencoded::to_string(&query).unwrap_or_default();
+
+    match state.user_store.delete_session(&user.id, &id).await {
+        Ok(()) => {
+            let sep = if back.is_empty() { "" } else { "&" };
+            Redirect::to(&format!("/history?{back}{sep}deleted=1")).into_response()
+        }
+        Err(_) => Redirect::to(&format!("/history?{back}")).into_response(),
+    }
+}
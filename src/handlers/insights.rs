@@ -0,0 +1,47 @@
+use axum::{
+    extract::{Query, State},
+    response::{Html, IntoResponse, Response},
+};
+use chrono::Duration;
+use serde::Deserialize;
+use std::sync::Arc;
+use tower_sessions::Session;
+
+use crate::{
+    handlers::auth::require_user,
+    models::insights::{DEFAULT_PERIOD, INSIGHT_PERIODS, MoodInsights},
+    state::AppState,
+    templates,
+};
+
+#[derive(Deserialize)]
+pub struct InsightsQuery {
+    pub days: Option<i64>,
+}
+
+pub async fn show_insights(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<InsightsQuery>,
+) -> Response {
+    let user = match require_user(&session, &state).await {
+        Ok(u) => u,
+        Err(r) => return r,
+    };
+
+    let days = query
+        .days
+        .filter(|d| INSIGHT_PERIODS.contains(d))
+        .unwrap_or(DEFAULT_PERIOD);
+
+    // One query covers every period's summary, not just the charted one
+    let today = user.today();
+    let longest = INSIGHT_PERIODS.iter().max().copied().unwrap_or(days);
+    let rows = state
+        .user_store
+        .get_mood_days(&user.id, today - Duration::days(longest - 1))
+        .await;
+
+    let insights = MoodInsights::new(days, today, &rows);
+    Html(templates::insights_page(&insights)).into_response()
+}
@@ -1,6 +1,11 @@
 pub mod auth;
 pub mod dashboard;
+pub mod goals;
+pub mod history;
+pub mod insights;
 pub mod newsletter;
 pub mod profile;
 pub mod sessions;
+pub mod streak;
+pub mod two_factor;
 pub mod videos;
@@ -8,7 +8,12 @@ use serde::{Deserialize, Serialize};
 use std::{env, sync::Arc};
 use tower_sessions::Session;
 
-use crate::{state::AppState, templates};
+use crate::{
+    csrf,
+    sanitize::{self, Stripped},
+    state::AppState,
+    templates,
+};
 
 // ── Forms & query params ───────────────────────────────────────────────────────
 
@@ -44,6 +49,8 @@ struct SubscriberDto {
 struct ArticleCreatedDto {
     id: String,
     published_at: String,
+    /// Markup removed from `content_html` by the sanitizer.
+    stripped: Stripped,
 }
 
 // ── API key guard ──────────────────────────────────────────────────────────────
@@ -72,45 +79,73 @@ fn api_key_valid(headers: &HeaderMap) -> bool {
 
 // ── Public: archive ────────────────────────────────────────────────────────────
 
-pub async fn show_newsletter(State(state): State<Arc<AppState>>) -> Response {
+pub async fn show_newsletter(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Response {
+    let csrf = csrf::public_token(&session, &headers, state.config.secure_cookies).await;
     let articles = state.user_store.get_all_newsletter_articles().await;
-    Html(templates::newsletter_page(&articles)).into_response()
+    let page = Html(templates::newsletter_page(&csrf.value, &articles));
+    csrf.respond(page)
 }
 
 // ── Public: single article ─────────────────────────────────────────────────────
 
-pub async fn show_article(State(state): State<Arc<AppState>>, Path(id): Path<String>) -> Response {
-    match state.user_store.get_newsletter_article_by_id(&id).await {
-        Some(article) => Html(templates::newsletter_article_page(&article)).into_response(),
-        None => Redirect::to("/newsletter").into_response(),
-    }
+pub async fn show_article(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    let Some(article) = state.user_store.get_newsletter_article_by_id(&id).await else {
+        return Redirect::to("/newsletter").into_response();
+    };
+    let csrf = csrf::public_token(&session, &headers, state.config.secure_cookies).await;
+    let page = Html(templates::newsletter_article_page(&csrf.value, &article));
+    csrf.respond(page)
 }
 
 // ── Public: subscribe ──────────────────────────────────────────────────────────
 
-pub async fn show_subscribe(_session: Session) -> Response {
-    Html(templates::newsletter_subscribe_page(false, None)).into_response()
+pub async fn show_subscribe(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Response {
+    let csrf = csrf::public_token(&session, &headers, state.config.secure_cookies).await;
+    let page = Html(templates::newsletter_subscribe_page(
+        &csrf.value,
+        false,
+        None,
+    ));
+    csrf.respond(page)
 }
 
 pub async fn process_subscribe(
+    session: Session,
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Form(form): Form<SubscribeForm>,
 ) -> Response {
+    let csrf = csrf::public_token(&session, &headers, state.config.secure_cookies).await;
     let email = form.email.trim().to_lowercase();
     let name = form.name.trim().to_string();
 
     if email.is_empty() || !email.contains('@') {
-        return Html(templates::newsletter_subscribe_page(
+        let page = Html(templates::newsletter_subscribe_page(
+            &csrf.value,
             false,
             Some("Please enter a valid email address."),
-        ))
-        .into_response();
+        ));
+        return csrf.respond(page);
     }
 
-    match state.user_store.subscribe(email, name).await {
-        Ok(_) => Html(templates::newsletter_subscribe_page(true, None)).into_response(),
-        Err(e) => Html(templates::newsletter_subscribe_page(false, Some(&e))).into_response(),
-    }
+    let page = match state.user_store.subscribe(email, name).await {
+        Ok(_) => templates::newsletter_subscribe_page(&csrf.value, true, None),
+        Err(e) => templates::newsletter_subscribe_page(&csrf.value, false, Some(&e)),
+    };
+    csrf.respond(Html(page))
 }
 
 // ── Public: unsubscribe ────────────────────────────────────────────────────────
@@ -184,9 +219,11 @@ pub async fn api_post_article(
             .into_response();
     }
 
+    let (content_html, stripped) = sanitize::clean_with_report(&payload.content_html);
+
     match state
         .user_store
-        .create_newsletter_article(title, summary, payload.content_html, payload.source_urls)
+        .create_newsletter_article(title, summary, content_html, payload.source_urls)
         .await
     {
         Ok(article) => (
@@ -194,6 +231,7 @@ pub async fn api_post_article(
             Json(ArticleCreatedDto {
                 id: article.id,
                 published_at: article.published_at,
+                stripped,
             }),
         )
             .into_response(),
@@ -1,25 +1,273 @@
 use axum::{
-    extract::State,
+    extract::{Form, Query, State},
     response::{Html, IntoResponse, Redirect, Response},
 };
+use serde::Deserialize;
 use std::sync::Arc;
 use tower_sessions::Session;
 
-use crate::{handlers::auth::SESSION_USER_KEY, state::AppState, templates};
+use crate::{
+    csrf,
+    handlers::auth::{require_user, send_verification_email},
+    models::user::{User, parse_timezone},
+    state::AppState,
+    templates,
+};
+
+// ── Forms ──────────────────────────────────────────────────────────────────────
+
+#[derive(Deserialize)]
+pub struct ProfileQuery {
+    pub saved: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct NameForm {
+    pub name: String,
+}
+
+#[derive(Deserialize)]
+pub struct TimezoneForm {
+    pub timezone: String,
+}
+
+#[derive(Deserialize)]
+pub struct EmailForm {
+    pub email: String,
+    pub current_password: String,
+}
+
+#[derive(Deserialize)]
+pub struct PasswordForm {
+    pub current_password: String,
+    pub new_password: String,
+    pub confirm_password: String,
+}
 
-pub async fn show_profile(session: Session, State(state): State<Arc<AppState>>) -> Response {
-    let user_email = match session.get::<String>(SESSION_USER_KEY).await.ok().flatten() {
-        Some(email) => email,
-        None => return Redirect::to("/login").into_response(),
+// ── Helpers ────────────────────────────────────────────────────────────────────
+
+/// Re-render the profile page with an error.
+pub async fn profile_error(
+    session: &Session,
+    state: &AppState,
+    user: &User,
+    msg: &str,
+) -> Response {
+    let csrf = csrf::token(session).await;
+    let badges = state.user_store.get_achievements(&user.id).await;
+    Html(templates::profile_page(
+        &csrf,
+        user,
+        &badges,
+        None,
+        Some(msg),
+    ))
+    .into_response()
+}
+
+// ── Handlers ───────────────────────────────────────────────────────────────────
+
+pub async fn show_profile(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ProfileQuery>,
+) -> Response {
+    let user = match require_user(&session, &state).await {
+        Ok(u) => u,
+        Err(r) => return r,
     };
+    let csrf = csrf::token(&session).await;
 
-    let user = match state.user_store.find_by_email(&user_email).await {
-        Some(u) => u,
-        None => {
-            let _ = session.flush().await;
-            return Redirect::to("/login").into_response();
+    let notice = match params.saved.as_deref() {
+        Some("name") => Some("Your name has been updated."),
+        Some("timezone") => Some("Your timezone has been updated."),
+        Some("email") => Some(
+            "We've sent a confirmation link to your new address. \
+             Your email will change once you open it.",
+        ),
+        Some("password") => {
+            Some("Your password has been changed and your other sessions were signed out.")
         }
+        Some("2fa-off") => Some("Two-factor authentication has been turned off."),
+        _ => None,
+    };
+
+    let badges = state.user_store.get_achievements(&user.id).await;
+    Html(templates::profile_page(&csrf, &user, &badges, notice, None)).into_response()
+}
+
+pub async fn update_name(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    Form(form): Form<NameForm>,
+) -> Response {
+    let user = match require_user(&session, &state).await {
+        Ok(u) => u,
+        Err(r) => return r,
     };
 
-    Html(templates::profile_page(&user)).into_response()
+    let name = form.name.trim();
+
+    if name.is_empty() {
+        return profile_error(&session, &state, &user, "Name cannot be empty.").await;
+    }
+
+    if name.chars().count() > 80 {
+        return profile_error(
+            &session,
+            &state,
+            &user,
+            "Name must be 80 characters or fewer.",
+        )
+        .await;
+    }
+
+    match state.user_store.update_name(&user.id, name).await {
+        Ok(_) => Redirect::to("/profile?saved=name").into_response(),
+        Err(e) => profile_error(&session, &state, &user, &e).await,
+    }
+}
+
+pub async fn update_timezone(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    Form(form): Form<TimezoneForm>,
+) -> Response {
+    let user = match require_user(&session, &state).await {
+        Ok(u) => u,
+        Err(r) => return r,
+    };
+
+    let Some(tz) = parse_timezone(&form.timezone) else {
+        return profile_error(
+            &session,
+            &state,
+            &user,
+            "Please choose a timezone from the list.",
+        )
+        .await;
+    };
+
+    match state.user_store.update_timezone(&user.id, tz.name()).await {
+        Ok(_) => Redirect::to("/profile?saved=timezone").into_response(),
+        Err(e) => profile_error(&session, &state, &user, &e).await,
+    }
+}
+
+pub async fn update_email(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    Form(form): Form<EmailForm>,
+) -> Response {
+    let user = match require_user(&session, &state).await {
+        Ok(u) => u,
+        Err(r) => return r,
+    };
+
+    let email = form.email.trim().to_lowercase();
+
+    if email.is_empty() || !email.contains('@') {
+        return profile_error(
+            &session,
+            &state,
+            &user,
+            "Please enter a valid email address.",
+        )
+        .await;
+    }
+
+    if email == user.email {
+        return profile_error(
+            &session,
+            &state,
+            &user,
+            "That is already your email address.",
+        )
+        .await;
+    }
+
+    if !user.verify_password(&form.current_password) {
+        return profile_error(
+            &session,
+            &state,
+            &user,
+            "Your current password is incorrect.",
+        )
+        .await;
+    }
+
+    if state.user_store.email_in_use(&email).await {
+        return profile_error(
+            &session,
+            &state,
+            &user,
+            "An account with this email already exists.",
+        )
+        .await;
+    }
+
+    // The address only changes once the link sent to it is opened
+    send_verification_email(&state, &user, &email).await;
+    Redirect::to("/profile?saved=email").into_response()
+}
+
+pub async fn update_password(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    Form(form): Form<PasswordForm>,
+) -> Response {
+    let user = match require_user(&session, &state).await {
+        Ok(u) => u,
+        Err(r) => return r,
+    };
+
+    if !user.verify_password(&form.current_password) {
+        return profile_error(
+            &session,
+            &state,
+            &user,
+            "Your current password is incorrect.",
+        )
+        .await;
+    }
+
+    if form.new_password != form.confirm_password {
+        return profile_error(&session, &state, &user, "New passwords do not match.").await;
+    }
+
+    if form.new_password.len() < 8 {
+        return profile_error(
+            &session,
+            &state,
+            &user,
+            "Password must be at least 8 characters long.",
+        )
+        .await;
+    }
+
+    if let Err(e) = state
+        .user_store
+        .update_password(&user.id, &form.new_password)
+        .await
+    {
+        return profile_error(&session, &state, &user, &e).await;
+    }
+
+    if let Err(e) = state
+        .session_store
+        .delete_user_sessions(&user.id, session.id())
+        .await
+    {
+        eprintln!("Failed to sign out other sessions for {}: {e}", user.id);
+        return profile_error(
+            &session,
+            &state,
+            &user,
+            "Your password was changed, but we couldn't sign out your other devices. \
+             Please try changing it again.",
+        )
+        .await;
+    }
+
+    Redirect::to("/profile?saved=password").into_response()
 }
@@ -1,106 +1,605 @@
 use axum::{
-    extract::{Form, State},
+    extract::{Form, Path, Query, State},
+    http::StatusCode,
     response::{Html, IntoResponse, Redirect, Response},
 };
 use serde::Deserialize;
 use std::sync::Arc;
 use tower_sessions::Session;
 
-use crate::{handlers::auth::SESSION_USER_KEY, state::AppState, templates};
+use crate::{
+    config::Capability,
+    csrf,
+    handlers::{
+        auth::{capability_denied, require_user},
+        history::parse_date_range,
+    },
+    models::{
+        breathing::{
+            BreathingPattern, MAX_CYCLES, MAX_PHASE_SECS, MIN_CYCLES, builtin_patterns,
+            find_pattern,
+        },
+        journal::{JournalFilter, JournalPrompt, find_prompt, parse_tags, prompt_for_day},
+        meditation::{MeditationPreset, MeditationSettings},
+        user::User,
+    },
+    state::AppState,
+    templates,
+};
 
 // ── Forms ──────────────────────────────────────────────────────────────────────
 
+#[derive(Deserialize)]
+pub struct BreatheQuery {
+    pub pattern: Option<String>,
+    pub cycles: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct BreatheCompleteForm {
+    pub start_id: String,
+    pub pattern: Option<String>,
+    pub cycles: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct PatternForm {
+    pub name: String,
+    pub inhale: String,
+    pub hold_in: String,
+    pub exhale: String,
+    pub hold_out: String,
+    pub pattern: Option<String>,
+    pub cycles: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct MeditateQuery {
+    pub preset: Option<String>,
+    pub minutes: Option<i64>,
+    pub interval: Option<i64>,
+    pub prep: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct MeditateCompleteForm {
+    pub start_id: String,
+    pub minutes: Option<i64>,
+    pub interval: Option<i64>,
+    pub prep: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct PresetForm {
+    pub name: String,
+    pub minutes: i64,
+    pub interval: i64,
+    pub prep: i64,
+}
+
+#[derive(Deserialize)]
+pub struct JournalQuery {
+    pub prompt: Option<String>,
+}
+
 #[derive(Deserialize)]
 pub struct JournalForm {
     pub mood: i64,
     pub note: String,
+    /// Key of the prompt shown above the note, empty if none.
+    #[serde(default)]
+    pub prompt: String,
+    /// Comma-separated tag names.
+    #[serde(default)]
+    pub tags: String,
+}
+
+#[derive(Deserialize)]
+pub struct JournalEntriesQuery {
+    pub q: Option<String>,
+    pub tag: Option<String>,
+    pub mood_min: Option<String>,
+    pub mood_max: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub page: Option<i64>,
+    pub deleted: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct JournalEntryQuery {
+    pub saved: Option<String>,
+}
+
+// ── Achievements ───────────────────────────────────────────────────────────────
+
+/// Check for newly earned badges after recording activity. The dashboard
+/// announces them, so a failure here only delays a badge.
+async fn check_achievements(state: &AppState, user_id: &str) {
+    if let Err(e) = state.user_store.evaluate_achievements(user_id).await {
+        eprintln!("Failed to evaluate achievements: {e}");
+    }
+}
+
+/// Start the clock on a breathing or meditation session. Called by the
+/// page's Begin button, so it answers with the id for the save form rather
+/// than a page. Nothing is recorded until then, so reloading a page is free.
+async fn begin_session(
+    state: &AppState,
+    user: &User,
+    session_type: &str,
+    planned_secs: i64,
+    lead_in_secs: i64,
+    pattern: Option<&str>,
+    cycles: Option<i64>,
+) -> Response {
+    match state
+        .user_store
+        .start_session(
+            &user.id,
+            session_type,
+            planned_secs,
+            lead_in_secs,
+            pattern,
+            cycles,
+        )
+        .await
+    {
+        Ok(id) => id.into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+    }
 }
 
 // ── Breathing ──────────────────────────────────────────────────────────────────
 
-pub async fn show_breathe(session: Session, State(state): State<Arc<AppState>>) -> Response {
-    let user_email = match session.get::<String>(SESSION_USER_KEY).await.ok().flatten() {
-        Some(e) => e,
-        None => return Redirect::to("/login").into_response(),
+/// The pattern and cycle count asked for, defaulting to the user's last
+/// pattern and that pattern's usual length.
+async fn breathe_choice(
+    state: &AppState,
+    user: &User,
+    custom: &[BreathingPattern],
+    pattern: Option<String>,
+    cycles: Option<i64>,
+) -> (BreathingPattern, i64) {
+    let key = match pattern {
+        Some(k) => Some(k),
+        None => state.user_store.last_breathing_pattern(&user.id).await,
     };
+    let selected = key
+        .and_then(|k| find_pattern(&k, custom))
+        .unwrap_or_else(|| builtin_patterns().remove(0));
+    let cycles = cycles
+        .unwrap_or(selected.default_cycles)
+        .clamp(MIN_CYCLES, MAX_CYCLES);
+    (selected, cycles)
+}
 
-    if state.user_store.find_by_email(&user_email).await.is_none() {
-        let _ = session.flush().await;
-        return Redirect::to("/login").into_response();
+async fn render_breathe(
+    session: &Session,
+    state: &AppState,
+    user: &User,
+    pattern: Option<String>,
+    cycles: Option<i64>,
+    error: Option<&str>,
+) -> Response {
+    let custom = state.user_store.get_breathing_patterns(&user.id).await;
+    let (selected, cycles) = breathe_choice(state, user, &custom, pattern, cycles).await;
+
+    let patterns: Vec<BreathingPattern> = builtin_patterns().into_iter().chain(custom).collect();
+    let csrf = csrf::token(session).await;
+
+    Html(templates::breathe_page(
+        &csrf, &patterns, &selected, cycles, error,
+    ))
+    .into_response()
+}
+
+pub async fn show_breathe(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<BreatheQuery>,
+) -> Response {
+    let user = match require_user(&session, &state).await {
+        Ok(u) => u,
+        Err(r) => return r,
+    };
+
+    if let Some(r) = capability_denied(&session, &state, &user, Capability::LogActivity).await {
+        return r;
+    }
+
+    render_breathe(&session, &state, &user, params.pattern, params.cycles, None).await
+}
+
+pub async fn begin_breathe(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    Form(form): Form<BreatheQuery>,
+) -> Response {
+    let user = match require_user(&session, &state).await {
+        Ok(u) => u,
+        Err(r) => return r,
+    };
+
+    if let Some(r) = capability_denied(&session, &state, &user, Capability::LogActivity).await {
+        return r;
     }
 
-    Html(templates::breathe_page()).into_response()
+    let custom = state.user_store.get_breathing_patterns(&user.id).await;
+    let (selected, cycles) =
+        breathe_choice(&state, &user, &custom, form.pattern, form.cycles).await;
+    begin_session(
+        &state,
+        &user,
+        "breathing",
+        selected.planned_secs(cycles),
+        0,
+        Some(&selected.key),
+        Some(cycles),
+    )
+    .await
 }
 
-pub async fn complete_breathe(session: Session, State(state): State<Arc<AppState>>) -> Response {
-    let user_email = match session.get::<String>(SESSION_USER_KEY).await.ok().flatten() {
-        Some(e) => e,
-        None => return Redirect::to("/login").into_response(),
+pub async fn complete_breathe(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    Form(form): Form<BreatheCompleteForm>,
+) -> Response {
+    let user = match require_user(&session, &state).await {
+        Ok(u) => u,
+        Err(r) => return r,
     };
 
-    let user = match state.user_store.find_by_email(&user_email).await {
-        Some(u) => u,
-        None => {
-            let _ = session.flush().await;
-            return Redirect::to("/login").into_response();
+    if let Some(r) = capability_denied(&session, &state, &user, Capability::LogActivity).await {
+        return r;
+    }
+
+    match state
+        .user_store
+        .finish_session(&user.id, &form.start_id, "breathing")
+        .await
+    {
+        Ok(()) => {
+            check_achievements(&state, &user.id).await;
+            Redirect::to("/dashboard?completed=breathing").into_response()
         }
+        Err(e) => {
+            render_breathe(&session, &state, &user, form.pattern, form.cycles, Some(&e)).await
+        }
+    }
+}
+
+fn parse_phase(value: &str, min: f64) -> Option<f64> {
+    let secs: f64 = value.trim().parse().ok()?;
+    // Half-second resolution is plenty and keeps summaries readable
+    let secs = (secs * 2.0).round() / 2.0;
+    (min..=MAX_PHASE_SECS).contains(&secs).then_some(secs)
+}
+
+pub async fn create_pattern(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    Form(form): Form<PatternForm>,
+) -> Response {
+    let user = match require_user(&session, &state).await {
+        Ok(u) => u,
+        Err(r) => return r,
     };
 
-    let _ = state.user_store.log_session(&user.id, "breathing", 5).await;
-    Redirect::to("/dashboard?completed=breathing").into_response()
+    if let Some(r) = capability_denied(&session, &state, &user, Capability::LogActivity).await {
+        return r;
+    }
+
+    let name = form.name.trim();
+    if name.is_empty() || name.chars().count() > 40 {
+        return render_breathe(
+            &session,
+            &state,
+            &user,
+            form.pattern,
+            form.cycles,
+            Some("Pattern name must be between 1 and 40 characters."),
+        )
+        .await;
+    }
+
+    let phases = [
+        parse_phase(&form.inhale, 1.0),
+        parse_phase(&form.hold_in, 0.0),
+        parse_phase(&form.exhale, 1.0),
+        parse_phase(&form.hold_out, 0.0),
+    ];
+    let [Some(inhale), Some(hold_in), Some(exhale), Some(hold_out)] = phases else {
+        return render_breathe(
+            &session,
+            &state,
+            &user,
+            form.pattern,
+            form.cycles,
+            Some("Inhale and exhale must be 1–20 seconds, holds 0–20 seconds."),
+        )
+        .await;
+    };
+
+    match state
+        .user_store
+        .create_breathing_pattern(&user.id, name, [inhale, hold_in, exhale, hold_out])
+        .await
+    {
+        Ok(key) => Redirect::to(&format!("/breathe?pattern={key}")).into_response(),
+        Err(e) => {
+            render_breathe(&session, &state, &user, form.pattern, form.cycles, Some(&e)).await
+        }
+    }
+}
+
+pub async fn delete_pattern(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Form(form): Form<BreatheQuery>,
+) -> Response {
+    let user = match require_user(&session, &state).await {
+        Ok(u) => u,
+        Err(r) => return r,
+    };
+
+    if let Some(r) = capability_denied(&session, &state, &user, Capability::LogActivity).await {
+        return r;
+    }
+
+    match state
+        .user_store
+        .delete_breathing_pattern(&user.id, &id)
+        .await
+    {
+        Ok(_) => Redirect::to("/breathe").into_response(),
+        Err(e) => {
+            render_breathe(&session, &state, &user, form.pattern, form.cycles, Some(&e)).await
+        }
+    }
 }
 
 // ── Meditation ─────────────────────────────────────────────────────────────────
 
-pub async fn show_meditate(session: Session, State(state): State<Arc<AppState>>) -> Response {
-    let user_email = match session.get::<String>(SESSION_USER_KEY).await.ok().flatten() {
-        Some(e) => e,
-        None => return Redirect::to("/login").into_response(),
+async fn render_meditate(
+    session: &Session,
+    state: &AppState,
+    user: &User,
+    settings: MeditationSettings,
+    error: Option<&str>,
+) -> Response {
+    let presets = state.user_store.get_meditation_presets(&user.id).await;
+    let csrf = csrf::token(session).await;
+
+    Html(templates::meditate_page(&csrf, &presets, &settings, error)).into_response()
+}
+
+pub async fn show_meditate(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<MeditateQuery>,
+) -> Response {
+    let user = match require_user(&session, &state).await {
+        Ok(u) => u,
+        Err(r) => return r,
     };
 
-    if state.user_store.find_by_email(&user_email).await.is_none() {
-        let _ = session.flush().await;
-        return Redirect::to("/login").into_response();
+    if let Some(r) = capability_denied(&session, &state, &user, Capability::LogActivity).await {
+        return r;
     }
 
-    Html(templates::meditate_page()).into_response()
+    let preset = match params.preset {
+        Some(id) => preset_settings(&state, &user.id, &id).await,
+        None => None,
+    };
+    let settings =
+        preset.unwrap_or_else(|| meditate_settings(params.minutes, params.interval, params.prep));
+
+    render_meditate(&session, &state, &user, settings, None).await
+}
+
+/// The settings of one of the user's presets, if it exists.
+async fn preset_settings(state: &AppState, user_id: &str, id: &str) -> Option<MeditationSettings> {
+    state
+        .user_store
+        .get_meditation_presets(user_id)
+        .await
+        .iter()
+        .find(|p| p.id == id)
+        .map(MeditationPreset::settings)
+}
+
+/// Settings from a query or form, with defaults for anything missing.
+fn meditate_settings(
+    minutes: Option<i64>,
+    interval: Option<i64>,
+    prep: Option<i64>,
+) -> MeditationSettings {
+    let default = MeditationSettings::default();
+    MeditationSettings::clamped(
+        minutes.unwrap_or(default.minutes),
+        interval.unwrap_or(default.interval_min),
+        prep.unwrap_or(default.prep_secs),
+    )
 }
 
-pub async fn complete_meditate(session: Session, State(state): State<Arc<AppState>>) -> Response {
-    let user_email = match session.get::<String>(SESSION_USER_KEY).await.ok().flatten() {
-        Some(e) => e,
-        None => return Redirect::to("/login").into_response(),
+pub async fn begin_meditate(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    Form(form): Form<MeditateQuery>,
+) -> Response {
+    let user = match require_user(&session, &state).await {
+        Ok(u) => u,
+        Err(r) => return r,
     };
 
-    let user = match state.user_store.find_by_email(&user_email).await {
-        Some(u) => u,
-        None => {
-            let _ = session.flush().await;
-            return Redirect::to("/login").into_response();
-        }
+    if let Some(r) = capability_denied(&session, &state, &user, Capability::LogActivity).await {
+        return r;
+    }
+
+    let settings = meditate_settings(form.minutes, form.interval, form.prep);
+    begin_session(
+        &state,
+        &user,
+        "meditation",
+        settings.total_secs(),
+        settings.prep_secs,
+        None,
+        None,
+    )
+    .await
+}
+
+pub async fn complete_meditate(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    Form(form): Form<MeditateCompleteForm>,
+) -> Response {
+    let user = match require_user(&session, &state).await {
+        Ok(u) => u,
+        Err(r) => return r,
     };
 
-    let _ = state
+    if let Some(r) = capability_denied(&session, &state, &user, Capability::LogActivity).await {
+        return r;
+    }
+
+    match state
         .user_store
-        .log_session(&user.id, "meditation", 10)
+        .finish_session(&user.id, &form.start_id, "meditation")
+        .await
+    {
+        Ok(()) => {
+            check_achievements(&state, &user.id).await;
+            Redirect::to("/dashboard?completed=meditation").into_response()
+        }
+        Err(e) => {
+            let settings = meditate_settings(form.minutes, form.interval, form.prep);
+            render_meditate(&session, &state, &user, settings, Some(&e)).await
+        }
+    }
+}
+
+pub async fn create_preset(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    Form(form): Form<PresetForm>,
+) -> Response {
+    let user = match require_user(&session, &state).await {
+        Ok(u) => u,
+        Err(r) => return r,
+    };
+
+    if let Some(r) = capability_denied(&session, &state, &user, Capability::LogActivity).await {
+        return r;
+    }
+
+    let fallback = MeditationSettings::clamped(form.minutes, form.interval, form.prep);
+
+    let name = form.name.trim();
+    if name.is_empty() || name.chars().count() > 40 {
+        return render_meditate(
+            &session,
+            &state,
+            &user,
+            fallback,
+            Some("Preset name must be between 1 and 40 characters."),
+        )
         .await;
-    Redirect::to("/dashboard?completed=meditation").into_response()
+    }
+
+    let settings = match MeditationSettings::validated(form.minutes, form.interval, form.prep) {
+        Ok(s) => s,
+        Err(e) => return render_meditate(&session, &state, &user, fallback, Some(&e)).await,
+    };
+
+    match state
+        .user_store
+        .create_meditation_preset(&user.id, name, &settings)
+        .await
+    {
+        Ok(id) => Redirect::to(&format!("/meditate?preset={id}")).into_response(),
+        Err(e) => render_meditate(&session, &state, &user, settings, Some(&e)).await,
+    }
+}
+
+pub async fn delete_preset(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Response {
+    let user = match require_user(&session, &state).await {
+        Ok(u) => u,
+        Err(r) => return r,
+    };
+
+    if let Some(r) = capability_denied(&session, &state, &user, Capability::LogActivity).await {
+        return r;
+    }
+
+    let settings = preset_settings(&state, &user.id, &id).await;
+    match state
+        .user_store
+        .delete_meditation_preset(&user.id, &id)
+        .await
+    {
+        Ok(_) => Redirect::to("/meditate").into_response(),
+        Err(e) => {
+            let settings = settings.unwrap_or_default();
+            render_meditate(&session, &state, &user, settings, Some(&e)).await
+        }
+    }
 }
 
 // ── Journal ────────────────────────────────────────────────────────────────────
 
-pub async fn show_journal(session: Session, State(state): State<Arc<AppState>>) -> Response {
-    let user_email = match session.get::<String>(SESSION_USER_KEY).await.ok().flatten() {
-        Some(e) => e,
-        None => return Redirect::to("/login").into_response(),
+async fn render_journal(
+    session: &Session,
+    state: &AppState,
+    user: &User,
+    prompt: Option<&JournalPrompt>,
+    draft: Option<&JournalForm>,
+    error: Option<&str>,
+) -> Response {
+    let csrf = csrf::token(session).await;
+    let known_tags = state.user_store.get_journal_tags(&user.id).await;
+    let (note, tags) = draft
+        .map(|f| (f.note.as_str(), f.tags.as_str()))
+        .unwrap_or_default();
+
+    Html(templates::journal_page(
+        &csrf,
+        prompt,
+        &known_tags,
+        note,
+        tags,
+        error,
+    ))
+    .into_response()
+}
+
+pub async fn show_journal(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<JournalQuery>,
+) -> Response {
+    let user = match require_user(&session, &state).await {
+        Ok(u) => u,
+        Err(r) => return r,
     };
 
-    if state.user_store.find_by_email(&user_email).await.is_none() {
-        let _ = session.flush().await;
-        return Redirect::to("/login").into_response();
+    if let Some(r) = capability_denied(&session, &state, &user, Capability::LogActivity).await {
+        return r;
     }
 
-    Html(templates::journal_page(None)).into_response()
+    let prompt = match params.prompt.as_deref() {
+        Some("none") => None,
+        Some(key) => find_prompt(key).or(Some(prompt_for_day(user.today()))),
+        None => Some(prompt_for_day(user.today())),
+    };
+
+    render_journal(&session, &state, &user, prompt, None, None).await
 }
 
 pub async fn submit_journal(
@@ -108,31 +607,240 @@ pub async fn submit_journal(
     State(state): State<Arc<AppState>>,
     Form(form): Form<JournalForm>,
 ) -> Response {
-    let user_email = match session.get::<String>(SESSION_USER_KEY).await.ok().flatten() {
-        Some(e) => e,
-        None => return Redirect::to("/login").into_response(),
+    let user = match require_user(&session, &state).await {
+        Ok(u) => u,
+        Err(r) => return r,
     };
 
-    let user = match state.user_store.find_by_email(&user_email).await {
-        Some(u) => u,
-        None => {
-            let _ = session.flush().await;
-            return Redirect::to("/login").into_response();
-        }
-    };
+    if let Some(r) = capability_denied(&session, &state, &user, Capability::LogActivity).await {
+        return r;
+    }
+
+    let prompt = find_prompt(&form.prompt);
 
     if !(1..=5).contains(&form.mood) {
-        return Html(templates::journal_page(Some("Please select a mood."))).into_response();
+        return render_journal(
+            &session,
+            &state,
+            &user,
+            prompt,
+            Some(&form),
+            Some("Please select a mood."),
+        )
+        .await;
     }
 
+    let tags = match parse_tags(&form.tags) {
+        Ok(t) => t,
+        Err(e) => {
+            return render_journal(&session, &state, &user, prompt, Some(&form), Some(&e)).await;
+        }
+    };
+
     let note = form.note.trim().to_string();
 
     match state
         .user_store
-        .log_journal_entry(&user.id, form.mood, &note)
+        .log_journal_entry(&user.id, form.mood, &note, prompt.map(|p| p.text), &tags)
         .await
     {
-        Ok(_) => Redirect::to("/dashboard?completed=journal").into_response(),
-        Err(e) => Html(templates::journal_page(Some(&e))).into_response(),
+        Ok(_) => {
+            check_achievements(&state, &user.id).await;
+            Redirect::to("/dashboard?completed=journal").into_response()
+        }
+        Err(e) => render_journal(&session, &state, &user, prompt, Some(&form), Some(&e)).await,
+    }
+}
+
+/// Turn the search form into a filter, dropping anything invalid and saying why.
+fn parse_journal_filter(params: &JournalEntriesQuery) -> (JournalFilter, Option<&'static str>) {
+    let q = params
+        .q
+        .as_deref()
+        .map(str::trim)
+        .filter(|q| !q.is_empty())
+        .map(|q| q.chars().take(200).collect());
+
+    let mood = |value: &Option<String>| {
+        value
+            .as_deref()
+            .and_then(|v| v.trim().parse::<i64>().ok())
+            .map(|m| m.clamp(1, 5))
+    };
+    let mut mood_min = mood(&params.mood_min);
+    let mut mood_max = mood(&params.mood_max);
+    if let (Some(lo), Some(hi)) = (mood_min, mood_max)
+        && lo > hi
+    {
+        (mood_min, mood_max) = (mood_max, mood_min);
+    }
+
+    let tag = params
+        .tag
+        .as_deref()
+        .and_then(|t| parse_tags(t).ok())
+        .and_then(|tags| tags.into_iter().next());
+
+    let (from, to, error) = parse_date_range(params.from.as_deref(), params.to.as_deref());
+
+    let filter = JournalFilter {
+        q,
+        tag,
+        mood_min,
+        mood_max,
+        from,
+        to,
+    };
+    (filter, error)
+}
+
+pub async fn show_journal_entries(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<JournalEntriesQuery>,
+) -> Response {
+    let user = match require_user(&session, &state).await {
+        Ok(u) => u,
+        Err(r) => return r,
+    };
+
+    let (filter, error) = parse_journal_filter(&params);
+    let page = params.page.unwrap_or(1).max(1);
+    let results = state
+        .user_store
+        .get_journal_entries(&user.id, &filter, page)
+        .await;
+    let known_tags = state.user_store.get_journal_tags(&user.id).await;
+    let notice = params.deleted.is_some().then_some("Journal entry deleted.");
+
+    Html(templates::journal_entries_page(
+        &results,
+        &filter,
+        page.min(results.page_count()),
+        &known_tags,
+        error,
+        notice,
+    ))
+    .into_response()
+}
+
+pub async fn show_journal_entry(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(params): Query<JournalEntryQuery>,
+) -> Response {
+    let user = match require_user(&session, &state).await {
+        Ok(u) => u,
+        Err(r) => return r,
+    };
+
+    let Some(entry) = state.user_store.get_journal_entry(&user.id, &id).await else {
+        return Redirect::to("/journal/entries").into_response();
+    };
+
+    let csrf = csrf::token(&session).await;
+    let notice = params.saved.is_some().then_some("Changes saved.");
+    Html(templates::journal_entry_page(&csrf, &entry, notice)).into_response()
+}
+
+pub async fn edit_journal_entry(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Response {
+    let user = match require_user(&session, &state).await {
+        Ok(u) => u,
+        Err(r) => return r,
+    };
+
+    let Some(entry) = state.user_store.get_journal_entry(&user.id, &id).await else {
+        return Redirect::to("/journal/entries").into_response();
+    };
+
+    let csrf = csrf::token(&session).await;
+    let known_tags = state.user_store.get_journal_tags(&user.id).await;
+    Html(templates::journal_edit_page(
+        &csrf,
+        &entry,
+        &entry.tags().join(", "),
+        &known_tags,
+        None,
+    ))
+    .into_response()
+}
+
+pub async fn update_journal_entry(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Form(form): Form<JournalForm>,
+) -> Response {
+    let user = match require_user(&session, &state).await {
+        Ok(u) => u,
+        Err(r) => return r,
+    };
+
+    if let Some(r) = capability_denied(&session, &state, &user, Capability::LogActivity).await {
+        return r;
+    }
+
+    let Some(mut entry) = state.user_store.get_journal_entry(&user.id, &id).await else {
+        return Redirect::to("/journal/entries").into_response();
+    };
+
+    let note = form.note.trim().to_string();
+    let tags = if (1..=5).contains(&form.mood) {
+        parse_tags(&form.tags)
+    } else {
+        Err("Please select a mood.".to_string())
+    };
+
+    let result = match tags {
+        Ok(tags) => {
+            state
+                .user_store
+                .update_journal_entry(&user.id, &id, form.mood, &note, &tags)
+                .await
+        }
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(()) => Redirect::to(&format!("/journal/entries/{id}?saved=1")).into_response(),
+        Err(e) => {
+            // Keep what they typed so nothing is lost
+            entry.note = note;
+            let csrf = csrf::token(&session).await;
+            let known_tags = state.user_store.get_journal_tags(&user.id).await;
+            Html(templates::journal_edit_page(
+                &csrf,
+                &entry,
+                &form.tags,
+                &known_tags,
+                Some(&e),
+            ))
+            .into_response()
+        }
+    }
+}
+
+pub async fn delete_journal_entry(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Response {
+    let user = match require_user(&session, &state).await {
+        Ok(u) => u,
+        Err(r) => return r,
+    };
+
+    if let Some(r) = capability_denied(&session, &state, &user, Capability::LogActivity).await {
+        return r;
+    }
+
+    match state.user_store.delete_journal_entry(&user.id, &id).await {
+        Ok(()) => Redirect::to("/journal/entries?deleted=1").into_response(),
+        Err(_) => Redirect::to("/journal/entries").into_response(),
     }
 }
@@ -0,0 +1,38 @@
+use axum::{
+    extract::{Query, State},
+    response::{Html, IntoResponse, Response},
+};
+use chrono::{Datelike, NaiveDate};
+use serde::Deserialize;
+use std::sync::Arc;
+use tower_sessions::Session;
+
+use crate::{handlers::auth::require_user, state::AppState, templates};
+
+#[derive(Deserialize)]
+pub struct StreakQuery {
+    /// `YYYY-MM`; defaults to the current month.
+    pub month: Option<String>,
+}
+
+pub async fn show_streak(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<StreakQuery>,
+) -> Response {
+    let user = match require_user(&session, &state).await {
+        Ok(u) => u,
+        Err(r) => return r,
+    };
+
+    let today = user.today();
+    let this_month = today.with_day(1).unwrap_or(today);
+    let month = query
+        .month
+        .and_then(|m| NaiveDate::parse_from_str(&format!("{m}-01"), "%Y-%m-%d").ok())
+        .filter(|m| *m <= this_month)
+        .unwrap_or(this_month);
+
+    let history = state.user_store.get_streak_history(&user.id).await;
+    Html(templates::streak_page(&history, month, today)).into_response()
+}
@@ -0,0 +1,289 @@
+use axum::{
+    extract::{ConnectInfo, Form, State},
+    http::HeaderMap,
+    response::{Html, IntoResponse, Redirect, Response},
+};
+use chrono::Utc;
+use serde::Deserialize;
+use std::{net::SocketAddr, sync::Arc};
+use tower_sessions::Session;
+
+use crate::{
+    csrf,
+    handlers::{
+        auth::{client_ip, lockout_message, record_login_failure, require_user, sign_in},
+        profile::profile_error,
+    },
+    models::{login_attempt::AttemptOutcome, user::User},
+    state::AppState,
+    templates::{self, html::TrustedHtml},
+    tokens, totp,
+};
+
+/// Id of a user who passed the password check but not yet the code.
+const PENDING_USER_KEY: &str = "pending_2fa_user";
+const PENDING_SINCE_KEY: &str = "pending_2fa_since";
+const PENDING_ATTEMPTS_KEY: &str = "pending_2fa_attempts";
+/// Secret shown on the setup page, kept until the user confirms a code.
+const SETUP_SECRET_KEY: &str = "totp_setup_secret";
+
+/// How long the code step may take after the password step.
+const CHALLENGE_TTL_SECS: i64 = 5 * 60;
+/// Wrong codes allowed before the user must re-enter their password.
+const MAX_ATTEMPTS: i64 = 5;
+
+// ── Forms ──────────────────────────────────────────────────────────────────────
+
+#[derive(Deserialize)]
+pub struct CodeForm {
+    pub code: String,
+}
+
+#[derive(Deserialize)]
+pub struct DisableForm {
+    pub current_password: String,
+    pub code: String,
+}
+
+// ── Helpers ────────────────────────────────────────────────────────────────────
+
+/// Accept either a current authenticator code or an unused recovery code.
+async fn verify_second_factor(state: &AppState, user: &User, code: &str) -> bool {
+    let code = code.trim();
+
+    if code
+        .chars()
+        .all(|c| c.is_ascii_digit() || c.is_whitespace())
+    {
+        let Some((secret, last_step)) = state.user_store.get_totp_secret(&user.id).await else {
+            return false;
+        };
+        return match totp::verify(&secret, code, last_step.map(|s| s as u64)) {
+            Some(step) => state.user_store.claim_totp_step(&user.id, step).await,
+            None => false,
+        };
+    }
+
+    let hash = tokens::hash(&totp::normalize_recovery_code(code));
+    state.user_store.use_recovery_code(&user.id, &hash).await
+}
+
+async fn clear_challenge(session: &Session) {
+    let _ = session.remove::<String>(PENDING_USER_KEY).await;
+    let _ = session.remove::<i64>(PENDING_SINCE_KEY).await;
+    let _ = session.remove::<i64>(PENDING_ATTEMPTS_KEY).await;
+}
+
+/// Called by the login handler once the password is verified for a user with
+/// two-factor enabled. The session is not logged in until the code checks out.
+pub async fn begin_challenge(session: &Session, user: &User) -> Response {
+    let _ = session.insert(PENDING_USER_KEY, user.id.clone()).await;
+    let _ = session
+        .insert(PENDING_SINCE_KEY, Utc::now().timestamp())
+        .await;
+    let _ = session.insert(PENDING_ATTEMPTS_KEY, 0i64).await;
+    Redirect::to("/login/2fa").into_response()
+}
+
+// ── Login step ─────────────────────────────────────────────────────────────────
+
+pub async fn show_challenge(session: Session) -> Response {
+    if session
+        .get::<String>(PENDING_USER_KEY)
+        .await
+        .ok()
+        .flatten()
+        .is_none()
+    {
+        return Redirect::to("/login").into_response();
+    }
+    let csrf = csrf::token(&session).await;
+    Html(templates::two_factor_challenge_page(&csrf, None)).into_response()
+}
+
+pub async fn process_challenge(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Form(form): Form<CodeForm>,
+) -> Response {
+    let user_id = match session.get::<String>(PENDING_USER_KEY).await.ok().flatten() {
+        Some(id) => id,
+        None => return Redirect::to("/login").into_response(),
+    };
+    let csrf = csrf::token(&session).await;
+
+    let since = session
+        .get::<i64>(PENDING_SINCE_KEY)
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or(0);
+    let attempts = session
+        .get::<i64>(PENDING_ATTEMPTS_KEY)
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or(0);
+
+    if Utc::now().timestamp() - since > CHALLENGE_TTL_SECS || attempts >= MAX_ATTEMPTS {
+        clear_challenge(&session).await;
+        return Html(templates::login_page(
+            &csrf,
+            Some("Your sign-in timed out. Please enter your password again."),
+            None,
+        ))
+        .into_response();
+    }
+
+    let user = match state.user_store.find_by_id(&user_id).await {
+        Some(u) => u,
+        None => {
+            clear_challenge(&session).await;
+            return Redirect::to("/login").into_response();
+        }
+    };
+    let email = user.email.clone();
+
+    let ip = client_ip(&state, &headers, addr);
+    if let Some(msg) = lockout_message(&state, &email, &ip).await {
+        clear_challenge(&session).await;
+        return Html(templates::login_page(&csrf, Some(&msg), None)).into_response();
+    }
+
+    if !verify_second_factor(&state, &user, &form.code).await {
+        record_login_failure(&state, &email, &ip, AttemptOutcome::BadCode).await;
+        let _ = session.insert(PENDING_ATTEMPTS_KEY, attempts + 1).await;
+        return Html(templates::two_factor_challenge_page(
+            &csrf,
+            Some("That code didn't work. Please try again."),
+        ))
+        .into_response();
+    }
+
+    let _ = state
+        .user_store
+        .record_login_attempt(&email, &ip, AttemptOutcome::Success)
+        .await;
+    clear_challenge(&session).await;
+    sign_in(&session, user.id).await;
+    Redirect::to("/dashboard").into_response()
+}
+
+// ── Enrollment ─────────────────────────────────────────────────────────────────
+
+async fn render_setup(session: &Session, user: &User, error: Option<&str>) -> Response {
+    let csrf = csrf::token(session).await;
+    let secret = match session.get::<String>(SETUP_SECRET_KEY).await.ok().flatten() {
+        Some(s) => s,
+        None => {
+            let s = totp::generate_secret();
+            let _ = session.insert(SETUP_SECRET_KEY, s.clone()).await;
+            s
+        }
+    };
+    let uri = totp::otpauth_uri(&secret, &user.email);
+    // Markup generated by the qrcode crate from our own URI
+    let qr = TrustedHtml::new(totp::qr_svg(&uri).unwrap_or_default());
+
+    Html(templates::two_factor_setup_page(
+        &csrf, &secret, &uri, &qr, error,
+    ))
+    .into_response()
+}
+
+pub async fn show_setup(session: Session, State(state): State<Arc<AppState>>) -> Response {
+    let user = match require_user(&session, &state).await {
+        Ok(u) => u,
+        Err(r) => return r,
+    };
+
+    if user.has_two_factor() {
+        return Redirect::to("/profile").into_response();
+    }
+
+    render_setup(&session, &user, None).await
+}
+
+pub async fn enable(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    Form(form): Form<CodeForm>,
+) -> Response {
+    let user = match require_user(&session, &state).await {
+        Ok(u) => u,
+        Err(r) => return r,
+    };
+
+    if user.has_two_factor() {
+        return Redirect::to("/profile").into_response();
+    }
+
+    let secret = match session.get::<String>(SETUP_SECRET_KEY).await.ok().flatten() {
+        Some(s) => s,
+        None => return Redirect::to("/profile/2fa").into_response(),
+    };
+
+    let step = match totp::verify(&secret, &form.code, None) {
+        Some(step) => step,
+        None => {
+            return render_setup(
+                &session,
+                &user,
+                Some("That code didn't match. Check your device's clock and try again."),
+            )
+            .await;
+        }
+    };
+
+    let codes = totp::generate_recovery_codes();
+    let hashes: Vec<String> = codes
+        .iter()
+        .map(|c| tokens::hash(&totp::normalize_recovery_code(c)))
+        .collect();
+
+    if let Err(e) = state
+        .user_store
+        .enable_totp(&user.id, &secret, step, &hashes)
+        .await
+    {
+        return render_setup(&session, &user, Some(&e)).await;
+    }
+
+    let _ = session.remove::<String>(SETUP_SECRET_KEY).await;
+    Html(templates::recovery_codes_page(&codes)).into_response()
+}
+
+pub async fn disable(
+    session: Session,
+    State(state): State<Arc<AppState>>,
+    Form(form): Form<DisableForm>,
+) -> Response {
+    let user = match require_user(&session, &state).await {
+        Ok(u) => u,
+        Err(r) => return r,
+    };
+
+    if !user.has_two_factor() {
+        return Redirect::to("/profile").into_response();
+    }
+
+    // Re-authenticate with both factors before removing one
+    if !user.verify_password(&form.current_password)
+        || !verify_second_factor(&state, &user, &form.code).await
+    {
+        return profile_error(
+            &session,
+            &state,
+            &user,
+            "Your password or authentication code was incorrect.",
+        )
+        .await;
+    }
+
+    match state.user_store.disable_totp(&user.id).await {
+        Ok(_) => Redirect::to("/profile?saved=2fa-off").into_response(),
+        Err(e) => profile_error(&session, &state, &user, &e).await,
+    }
+}
@@ -6,7 +6,13 @@ use serde::Deserialize;
 use std::sync::Arc;
 use tower_sessions::Session;
 
-use crate::{handlers::auth::SESSION_USER_KEY, state::AppState, templates};
+use crate::{
+    config::Capability,
+    csrf,
+    handlers::auth::{capability_denied, require_user},
+    state::AppState,
+    templates,
+};
 
 // ── Forms ──────────────────────────────────────────────────────────────────────
 
@@ -19,26 +25,6 @@ pub struct VideoForm {
     pub category: String,
 }
 
-// ── Helpers ────────────────────────────────────────────────────────────────────
-
-async fn require_user(
-    session: &Session,
-    state: &Arc<AppState>,
-) -> Result<crate::models::user::User, Response> {
-    let email = session
-        .get::<String>(SESSION_USER_KEY)
-        .await
-        .ok()
-        .flatten()
-        .ok_or_else(|| Redirect::to("/login").into_response())?;
-
-    state
-        .user_store
-        .find_by_email(&email)
-        .await
-        .ok_or_else(|| Redirect::to("/login").into_response())
-}
-
 // ── Handlers ───────────────────────────────────────────────────────────────────
 
 pub async fn show_videos(session: Session, State(state): State<Arc<AppState>>) -> Response {
@@ -51,11 +37,17 @@ pub async fn show_videos(session: Session, State(state): State<Arc<AppState>>) -
 }
 
 pub async fn show_new_video(session: Session, State(state): State<Arc<AppState>>) -> Response {
-    if let Err(r) = require_user(&session, &state).await {
+    let user = match require_user(&session, &state).await {
+        Ok(u) => u,
+        Err(r) => return r,
+    };
+
+    if let Some(r) = capability_denied(&session, &state, &user, Capability::UploadVideos).await {
         return r;
     }
+    let csrf = csrf::token(&session).await;
 
-    Html(templates::new_video_page(None)).into_response()
+    Html(templates::new_video_page(&csrf, None)).into_response()
 }
 
 pub async fn create_video(
@@ -68,16 +60,25 @@ pub async fn create_video(
         Err(r) => return r,
     };
 
+    if let Some(r) = capability_denied(&session, &state, &user, Capability::UploadVideos).await {
+        return r;
+    }
+    let csrf = csrf::token(&session).await;
+
     let title = form.title.trim().to_string();
     let video_url = form.video_url.trim().to_string();
     let category = form.category.trim().to_string();
 
     if title.is_empty() {
-        return Html(templates::new_video_page(Some("Title is required."))).into_response();
+        return Html(templates::new_video_page(&csrf, Some("Title is required."))).into_response();
     }
 
     if video_url.is_empty() {
-        return Html(templates::new_video_page(Some("Video URL is required."))).into_response();
+        return Html(templates::new_video_page(
+            &csrf,
+            Some("Video URL is required."),
+        ))
+        .into_response();
     }
 
     let valid_categories = [
@@ -89,9 +90,10 @@ pub async fn create_video(
         "general",
     ];
     if !valid_categories.contains(&category.as_str()) {
-        return Html(templates::new_video_page(Some(
-            "Please select a valid category.",
-        )))
+        return Html(templates::new_video_page(
+            &csrf,
+            Some("Please select a valid category."),
+        ))
         .into_response();
     }
 
@@ -108,7 +110,7 @@ pub async fn create_video(
         .await
     {
         Ok(id) => Redirect::to(&format!("/videos/{id}")).into_response(),
-        Err(e) => Html(templates::new_video_page(Some(&e))).into_response(),
+        Err(e) => Html(templates::new_video_page(&csrf, Some(&e))).into_response(),
     }
 }
 
@@ -0,0 +1,144 @@
+use async_trait::async_trait;
+use lettre::{
+    AsyncFileTransport, AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+    message::{Mailbox, header::ContentType},
+    transport::smtp::authentication::Credentials,
+};
+use std::{fmt::Debug, sync::Arc};
+
+use crate::config::{Config, MailTransport};
+
+/// A plain-text email ready to be delivered.
+#[derive(Clone, Debug)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Delivery backend for transactional email (password resets, etc.).
+#[async_trait]
+pub trait Mailer: Debug + Send + Sync {
+    async fn send(&self, email: Email) -> Result<(), String>;
+}
+
+/// Build the mailer selected by `MAIL_TRANSPORT`.
+pub fn from_config(config: &Config) -> Result<Arc<dyn Mailer>, String> {
+    let from: Mailbox = config
+        .mail_from
+        .parse()
+        .map_err(|e| format!("Invalid MAIL_FROM: {e}"))?;
+
+    Ok(match &config.mail_transport {
+        MailTransport::Log => Arc::new(LogMailer),
+        MailTransport::File { dir } => {
+            std::fs::create_dir_all(dir).map_err(|e| format!("Cannot create {dir}: {e}"))?;
+            Arc::new(FileMailer {
+                from,
+                transport: AsyncFileTransport::new(dir),
+            })
+        }
+        MailTransport::Smtp {
+            host,
+            port,
+            username,
+            password,
+        } => {
+            let transport = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+                .map_err(|e| e.to_string())?
+                .port(*port)
+                .credentials(Credentials::new(username.clone(), password.clone()))
+                .build();
+            Arc::new(SmtpMailer { from, transport })
+        }
+    })
+}
+
+fn build_message(from: &Mailbox, email: &Email) -> Result<Message, String> {
+    let to: Mailbox = email
+        .to
+        .parse()
+        .map_err(|e| format!("Invalid recipient: {e}"))?;
+
+    Message::builder()
+        .from(from.clone())
+        .to(to)
+        .subject(&email.subject)
+        .header(ContentType::TEXT_PLAIN)
+        .body(email.body.clone())
+        .map_err(|e| e.to_string())
+}
+
+// ── SMTP ───────────────────────────────────────────────────────────────────────
+
+#[derive(Debug)]
+pub struct SmtpMailer {
+    from: Mailbox,
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: Email) -> Result<(), String> {
+        let message = build_message(&self.from, &email)?;
+        self.transport
+            .send(message)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+// ── File (one .eml per message) ────────────────────────────────────────────────
+
+#[derive(Debug)]
+pub struct FileMailer {
+    from: Mailbox,
+    transport: AsyncFileTransport<Tokio1Executor>,
+}
+
+#[async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, email: Email) -> Result<(), String> {
+        let message = build_message(&self.from, &email)?;
+        self.transport
+            .send(message)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+// ── Log (stdout) ───────────────────────────────────────────────────────────────
+
+#[derive(Debug)]
+pub struct LogMailer;
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, email: Email) -> Result<(), String> {
+        println!(
+            "✉️  To: {}\n    Subject: {}\n\n{}\n",
+            email.to, email.subject, email.body
+        );
+        Ok(())
+    }
+}
+
+// ── Recording (tests) ──────────────────────────────────────────────────────────
+
+/// Keeps every email instead of delivering it.
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct RecordingMailer {
+    pub sent: std::sync::Mutex<Vec<Email>>,
+}
+
+#[cfg(test)]
+#[async_trait]
+impl Mailer for RecordingMailer {
+    async fn send(&self, email: Email) -> Result<(), String> {
+        self.sent.lock().unwrap().push(email);
+        Ok(())
+    }
+}
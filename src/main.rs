@@ -1,21 +1,38 @@
 use axum::{
     Router,
     http::StatusCode,
+    middleware,
     response::{IntoResponse, Json},
     routing::{get, post},
 };
 use serde_json::json;
 use std::{env, sync::Arc};
-use tower_sessions::{MemoryStore, SessionManagerLayer};
+use tower_sessions::{
+    ExpiredDeletion, Expiry, SessionManagerLayer, cookie::time::Duration as CookieDuration,
+};
 
+mod config;
+mod crypto;
+mod csrf;
 mod db;
 mod handlers;
+mod mailer;
+mod migrations;
 mod models;
+mod sanitize;
+mod session_store;
 mod state;
 mod store;
 mod templates;
+mod tokens;
+mod totp;
 
-use handlers::{auth, dashboard, newsletter, profile, sessions, videos};
+use config::Config;
+use handlers::{
+    auth, dashboard, goals, history, insights, newsletter, profile, sessions, streak, two_factor,
+    videos,
+};
+use session_store::SqliteSessionStore;
 use state::AppState;
 use store::UserStore;
 
@@ -28,42 +45,151 @@ async fn not_found() -> impl IntoResponse {
     (StatusCode::NOT_FOUND, axum::response::Html(html))
 }
 
-#[tokio::main]
-async fn main() {
-    let database_url =
-        env::var("SQLITE_URL").unwrap_or_else(|_| "sqlite:calmcontrol.db".to_string());
+/// `CalmControl migrate [status|up]` — inspect or apply schema migrations
+/// without starting the web server.
+async fn run_migrate_command(database_url: &str, action: &str) {
+    let pool = db::connect(database_url)
+        .await
+        .expect("Failed to open database");
 
-    let pool = db::create_pool(&database_url)
+    match action {
+        "status" => {
+            let current = migrations::current_version(&pool)
+                .await
+                .expect("Failed to read schema version");
+            let latest = migrations::latest_version();
+            println!("Schema version: {current} (latest: {latest})");
+            for m in migrations::MIGRATIONS
+                .iter()
+                .filter(|m| m.version > current)
+            {
+                println!("  pending {:03} {}", m.version, m.name);
+            }
+        }
+        "up" => {
+            let applied = migrations::run_pending(&pool)
+                .await
+                .expect("Failed to apply migrations");
+            if applied.is_empty() {
+                println!("Schema is already up to date.");
+            }
+        }
+        other => {
+            eprintln!("Unknown migrate action '{other}'. Use: migrate [status|up]");
+            std::process::exit(2);
+        }
+    }
+}
+
+/// `CalmControl rotate-keys` — re-wrap every journal data key under
+/// `JOURNAL_MASTER_KEY`, unwrapping with `JOURNAL_PREVIOUS_MASTER_KEYS`.
+async fn run_rotate_keys_command(database_url: &str) {
+    let keyring = crypto::Keyring::from_env().unwrap_or_else(|e| {
+        eprintln!("{e}");
+        std::process::exit(2);
+    });
+    let current = keyring.current_id().to_string();
+    let pool = db::create_pool(database_url)
         .await
         .expect("Failed to initialise database");
 
-    let user_store = UserStore::new(pool);
-    let app_state = Arc::new(AppState { user_store });
+    let (rewrapped, failures) = UserStore::new(pool, keyring)
+        .rewrap_data_keys()
+        .await
+        .expect("Failed to rotate data keys");
 
-    let session_store = MemoryStore::default();
-    let session_layer = SessionManagerLayer::new(session_store).with_secure(false);
+    println!("Re-wrapped {rewrapped} data key(s) under master key {current}.");
+    if failures.is_empty() {
+        println!("Previous master keys can now be removed from JOURNAL_PREVIOUS_MASTER_KEYS.");
+    } else {
+        for failure in &failures {
+            eprintln!("  failed: {failure}");
+        }
+        eprintln!("Keep the previous master keys until every data key is re-wrapped.");
+        std::process::exit(1);
+    }
+}
+
+/// Every route, behind the CSRF check and the session layer.
+fn app(state: Arc<AppState>) -> Router {
+    let session_layer = SessionManagerLayer::new(state.session_store.clone())
+        .with_secure(state.config.secure_cookies)
+        .with_expiry(Expiry::OnInactivity(CookieDuration::hours(
+            state.config.session_ttl_hours,
+        )));
 
-    let app = Router::new()
+    Router::new()
         .route("/", get(dashboard::show_dashboard))
         .route(
             "/login",
             get(auth::show_login_page).post(auth::process_login),
         )
+        .route(
+            "/login/2fa",
+            get(two_factor::show_challenge).post(two_factor::process_challenge),
+        )
         .route("/logout", get(auth::logout))
+        .route(
+            "/forgot-password",
+            get(auth::show_forgot_password).post(auth::process_forgot_password),
+        )
+        .route(
+            "/reset-password",
+            get(auth::show_reset_password).post(auth::process_reset_password),
+        )
         .route(
             "/register",
             get(auth::show_register_page).post(auth::process_register),
         )
+        .route("/verify-email", get(auth::verify_email))
+        .route("/verify-email/resend", post(auth::resend_verification))
         .route("/dashboard", get(dashboard::show_dashboard))
         .route("/profile", get(profile::show_profile))
+        .route("/profile/name", post(profile::update_name))
+        .route("/profile/timezone", post(profile::update_timezone))
+        .route("/profile/email", post(profile::update_email))
+        .route("/profile/password", post(profile::update_password))
+        .route("/profile/2fa", get(two_factor::show_setup))
+        .route("/profile/2fa/enable", post(two_factor::enable))
+        .route("/profile/2fa/disable", post(two_factor::disable))
         .route("/breathe", get(sessions::show_breathe))
+        .route("/breathe/begin", post(sessions::begin_breathe))
         .route("/breathe/complete", post(sessions::complete_breathe))
+        .route("/breathe/patterns", post(sessions::create_pattern))
+        .route(
+            "/breathe/patterns/:id/delete",
+            post(sessions::delete_pattern),
+        )
+        .route("/goals", get(goals::show_goals).post(goals::update_goals))
+        .route("/history", get(history::show_history))
+        .route(
+            "/history/sessions/:id/delete",
+            post(history::delete_session),
+        )
+        .route("/insights", get(insights::show_insights))
+        .route("/streak", get(streak::show_streak))
         .route("/meditate", get(sessions::show_meditate))
+        .route("/meditate/begin", post(sessions::begin_meditate))
         .route("/meditate/complete", post(sessions::complete_meditate))
+        .route("/meditate/presets", post(sessions::create_preset))
+        .route(
+            "/meditate/presets/:id/delete",
+            post(sessions::delete_preset),
+        )
         .route(
             "/journal",
             get(sessions::show_journal).post(sessions::submit_journal),
         )
+        .route("/journal/entries", get(sessions::show_journal_entries))
+        .route("/journal/entries/:id", get(sessions::show_journal_entry))
+        .route(
+            "/journal/entries/:id/edit",
+            get(sessions::edit_journal_entry).post(sessions::update_journal_entry),
+        )
+        .route(
+            "/journal/entries/:id/delete",
+            post(sessions::delete_journal_entry),
+        )
         .route(
             "/videos",
             get(videos::show_videos).post(videos::create_video),
@@ -90,13 +216,843 @@ async fn main() {
         )
         .route("/health", get(health))
         .fallback(not_found)
-        .with_state(app_state)
-        .layer(session_layer);
+        .with_state(state)
+        .layer(middleware::from_fn(csrf::verify))
+        .layer(session_layer)
+}
+
+#[tokio::main]
+async fn main() {
+    let database_url =
+        env::var("SQLITE_URL").unwrap_or_else(|_| "sqlite:calmcontrol.db".to_string());
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        None => {}
+        Some("migrate") => {
+            let action = args.get(1).map(String::as_str).unwrap_or("status");
+            run_migrate_command(&database_url, action).await;
+            return;
+        }
+        Some("rotate-keys") => {
+            run_rotate_keys_command(&database_url).await;
+            return;
+        }
+        Some(other) => {
+            eprintln!("Unknown command '{other}'. Available: migrate [status|up], rotate-keys");
+            std::process::exit(2);
+        }
+    }
+
+    let config = Config::from_env();
+    let keyring = crypto::Keyring::from_env().expect("Failed to load journal encryption keys");
+
+    let pool = db::create_pool(&database_url)
+        .await
+        .expect("Failed to initialise database");
+
+    let session_store = SqliteSessionStore::new(pool.clone());
+    let user_store = UserStore::new(pool, keyring);
+    let mailer = mailer::from_config(&config).expect("Failed to configure mailer");
+    let app_state = Arc::new(AppState {
+        user_store,
+        session_store: session_store.clone(),
+        config: config.clone(),
+        mailer,
+    });
+
+    let notes_store = app_state.user_store.clone();
+
+    // Purge expired session rows and abandoned activity starts in the background
+    let cleanup_store = session_store.clone();
+    let cleanup_users = app_state.user_store.clone();
+    let cleanup_every = std::time::Duration::from_secs(config.session_cleanup_interval_secs.max(1));
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(cleanup_every);
+        loop {
+            interval.tick().await;
+            if let Err(e) = cleanup_store.delete_expired().await {
+                eprintln!("Session cleanup failed: {e}");
+            }
+            if let Err(e) = cleanup_users.delete_stale_session_starts().await {
+                eprintln!("Session start cleanup failed: {e}");
+            }
+        }
+    });
+
+    let app = app(app_state);
 
     let port = env::var("PORT").unwrap_or_else(|_| "3000".to_string());
     let addr = format!("0.0.0.0:{port}");
 
     let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
     println!("🌿 CalmControl running on http://{addr}");
-    axum::serve(listener, app).await.unwrap();
+
+    // Encrypt and index notes left by older versions once we're serving, so a
+    // large journal doesn't hold up the health check. Until it finishes those
+    // notes are readable but don't show up in searches.
+    tokio::spawn(async move {
+        match notes_store.encrypt_plaintext_notes().await {
+            Ok(0) => {}
+            Ok(n) => println!("🔐 Encrypted {n} existing journal notes"),
+            Err(e) => eprintln!("Journal note encryption failed: {e}"),
+        }
+        match notes_store.index_journal_notes().await {
+            Ok(0) => {}
+            Ok(n) => println!("🔎 Indexed {n} existing journal notes for search"),
+            Err(e) => eprintln!("Journal search indexing failed: {e}"),
+        }
+    });
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .await
+    .unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::UnverifiedPolicy, models::meditation::MeditationSettings};
+    use axum::{
+        body::{Body, to_bytes},
+        extract::{ConnectInfo, Request},
+        http::{Response, header},
+    };
+    use std::{collections::BTreeMap, net::SocketAddr};
+    use tower::ServiceExt;
+
+    const EMAIL: &str = "ada@example.com";
+    const PASSWORD: &str = "correct horse";
+
+    /// Drives the app the way a browser would, carrying cookies between
+    /// requests.
+    struct Browser {
+        app: Router,
+        cookies: BTreeMap<String, String>,
+    }
+
+    impl Browser {
+        fn new(state: Arc<AppState>) -> Self {
+            Browser {
+                app: app(state),
+                cookies: BTreeMap::new(),
+            }
+        }
+
+        async fn send(&mut self, mut request: Request) -> Response<Body> {
+            request
+                .extensions_mut()
+                .insert(ConnectInfo(SocketAddr::from(([203, 0, 113, 7], 40000))));
+            if !self.cookies.is_empty() {
+                let cookies: Vec<String> = self
+                    .cookies
+                    .iter()
+                    .map(|(k, v)| format!("{k}={v}"))
+                    .collect();
+                request.headers_mut().insert(
+                    header::COOKIE,
+                    cookies.join("; ").parse().expect("valid cookie header"),
+                );
+            }
+
+            let response = self.app.clone().oneshot(request).await.expect("infallible");
+            for set in response.headers().get_all(header::SET_COOKIE) {
+                let set = set.to_str().expect("ASCII cookie");
+                let (name, value) = set
+                    .split(';')
+                    .next()
+                    .and_then(|pair| pair.split_once('='))
+                    .expect("name=value cookie");
+                if value.is_empty() || set.contains("Max-Age=0") {
+                    self.cookies.remove(name);
+                } else {
+                    self.cookies.insert(name.to_string(), value.to_string());
+                }
+            }
+            response
+        }
+
+        async fn get(&mut self, path: &str) -> Response<Body> {
+            let request = Request::get(path).body(Body::empty()).unwrap();
+            self.send(request).await
+        }
+
+        async fn post(&mut self, path: &str, form: &[(&str, &str)]) -> Response<Body> {
+            let request = Request::post(path)
+                .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .body(Body::from(serde_urlencoded::to_string(form).unwrap()))
+                .unwrap();
+            self.send(request).await
+        }
+
+        /// The CSRF token in the form on `path`.
+        async fn csrf_token(&mut self, path: &str) -> String {
+            let body = to_bytes(self.get(path).await.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let body = String::from_utf8(body.to_vec()).unwrap();
+            let start = body
+                .find(r#"name="_csrf" value=""#)
+                .expect("page has a form")
+                + r#"name="_csrf" value=""#.len();
+            body[start..].split('"').next().unwrap().to_string()
+        }
+    }
+
+    fn location(response: &Response<Body>) -> &str {
+        response.headers()[header::LOCATION].to_str().unwrap()
+    }
+
+    async fn state_with_user() -> Arc<AppState> {
+        let state = AppState::for_tests(Arc::new(mailer::LogMailer)).await;
+        state
+            .user_store
+            .create_user(
+                "Ada".to_string(),
+                EMAIL.to_string(),
+                PASSWORD.to_string(),
+                "UTC".to_string(),
+            )
+            .await
+            .unwrap();
+        state
+    }
+
+    #[tokio::test]
+    async fn anonymous_pages_do_not_create_sessions() {
+        let state = AppState::for_tests(Arc::new(mailer::LogMailer)).await;
+
+        for path in [
+            "/",
+            "/dashboard",
+            "/login",
+            "/register",
+            "/forgot-password",
+            "/profile",
+            "/history",
+            "/journal/entries",
+            "/videos",
+            "/videos/new",
+        ] {
+            let response = Browser::new(state.clone()).get(path).await;
+            assert!(
+                response.status().is_success() || response.status().is_redirection(),
+                "{path}: {}",
+                response.status()
+            );
+            assert_eq!(state.session_store.count().await, 0, "{path}");
+        }
+
+        let response = Browser::new(state.clone()).get("/").await;
+        assert_eq!(location(&response), "/login");
+    }
+
+    #[tokio::test]
+    async fn anonymous_forms_work_without_a_session() {
+        let state = state_with_user().await;
+        let mut browser = Browser::new(state.clone());
+
+        let csrf = browser.csrf_token("/login").await;
+        let response = browser
+            .post(
+                "/login",
+                &[("email", EMAIL), ("password", "wrong"), ("_csrf", &csrf)],
+            )
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(state.session_store.count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn pre_login_token_is_rejected_after_sign_in() {
+        let state = state_with_user().await;
+        let mut browser = Browser::new(state);
+
+        let before = browser.csrf_token("/login").await;
+        let response = browser
+            .post(
+                "/login",
+                &[("email", EMAIL), ("password", PASSWORD), ("_csrf", &before)],
+            )
+            .await;
+        assert_eq!(location(&response), "/dashboard");
+
+        let response = browser
+            .post("/profile/name", &[("name", "Eve"), ("_csrf", &before)])
+            .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let after = browser.csrf_token("/profile").await;
+        assert_ne!(after, before);
+        let response = browser
+            .post("/profile/name", &[("name", "Ada L."), ("_csrf", &after)])
+            .await;
+        assert_ne!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn two_factor_sign_in_rotates_the_session_token() {
+        let state = state_with_user().await;
+        let user = state.user_store.find_by_email(EMAIL).await.unwrap();
+        let recovery = "abcde-fghij";
+        state
+            .user_store
+            .enable_totp(
+                &user.id,
+                &totp::generate_secret(),
+                0,
+                &[tokens::hash(&totp::normalize_recovery_code(recovery))],
+            )
+            .await
+            .unwrap();
+        let mut browser = Browser::new(state);
+
+        let csrf = browser.csrf_token("/login").await;
+        let response = browser
+            .post(
+                "/login",
+                &[("email", EMAIL), ("password", PASSWORD), ("_csrf", &csrf)],
+            )
+            .await;
+        assert_eq!(location(&response), "/login/2fa");
+
+        // The half-signed-in session holds a token of its own
+        let before = browser.csrf_token("/login/2fa").await;
+        let response = browser
+            .post("/login/2fa", &[("code", recovery), ("_csrf", &before)])
+            .await;
+        assert_eq!(location(&response), "/dashboard");
+
+        let response = browser
+            .post("/profile/name", &[("name", "Eve"), ("_csrf", &before)])
+            .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn changed_email_is_not_inherited_by_its_next_owner() {
+        let mailer = Arc::new(mailer::RecordingMailer::default());
+        let state = AppState::for_tests(mailer.clone()).await;
+        state
+            .user_store
+            .create_user(
+                "Ada".to_string(),
+                EMAIL.to_string(),
+                PASSWORD.to_string(),
+                "UTC".to_string(),
+            )
+            .await
+            .unwrap();
+
+        let mut first = Browser::new(state.clone());
+        let mut second = Browser::new(state.clone());
+        for browser in [&mut first, &mut second] {
+            let csrf = browser.csrf_token("/login").await;
+            let response = browser
+                .post(
+                    "/login",
+                    &[("email", EMAIL), ("password", PASSWORD), ("_csrf", &csrf)],
+                )
+                .await;
+            assert_eq!(location(&response), "/dashboard");
+        }
+
+        let csrf = first.csrf_token("/profile").await;
+        first
+            .post(
+                "/profile/email",
+                &[
+                    ("email", "ada@new.example.com"),
+                    ("current_password", PASSWORD),
+                    ("_csrf", &csrf),
+                ],
+            )
+            .await;
+        tokio::task::yield_now().await;
+        let link = {
+            let sent = mailer.sent.lock().unwrap();
+            let email = sent.last().expect("verification email was sent");
+            assert_eq!(email.to, "ada@new.example.com");
+            let start = email.body.find("/verify-email?token=").unwrap();
+            email.body[start..]
+                .split_whitespace()
+                .next()
+                .unwrap()
+                .to_string()
+        };
+        first.get(&link).await;
+
+        let mut mallory = Browser::new(state.clone());
+        let csrf = mallory.csrf_token("/register").await;
+        let response = mallory
+            .post(
+                "/register",
+                &[
+                    ("name", "Mallory"),
+                    ("email", EMAIL),
+                    ("password", "mallory's password"),
+                    ("confirm_password", "mallory's password"),
+                    ("_csrf", &csrf),
+                ],
+            )
+            .await;
+        assert_eq!(location(&response), "/dashboard");
+
+        // Verifying the new address signed out every other session
+        let response = second.get("/profile").await;
+        assert_eq!(location(&response), "/login");
+
+        let response = first.get("/profile").await;
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains("ada@new.example.com"));
+        assert!(!body.contains("Mallory"));
+    }
+
+    #[tokio::test]
+    async fn reset_links_work_once_for_their_own_account() {
+        let state = state_with_user().await;
+        state
+            .user_store
+            .create_user(
+                "Grace".to_string(),
+                "grace@example.com".to_string(),
+                "grace's password".to_string(),
+                "UTC".to_string(),
+            )
+            .await
+            .unwrap();
+        let ada = state.user_store.find_by_email(EMAIL).await.unwrap();
+        let grace = state
+            .user_store
+            .find_by_email("grace@example.com")
+            .await
+            .unwrap();
+
+        let mut signed_in = Browser::new(state.clone());
+        let csrf = signed_in.csrf_token("/login").await;
+        signed_in
+            .post(
+                "/login",
+                &[("email", EMAIL), ("password", PASSWORD), ("_csrf", &csrf)],
+            )
+            .await;
+
+        let mut browser = Browser::new(state.clone());
+        let csrf = browser.csrf_token("/forgot-password").await;
+        let mut reset = async |token: &str, password: &str| {
+            browser
+                .post(
+                    "/reset-password",
+                    &[
+                        ("token", token),
+                        ("password", password),
+                        ("confirm_password", password),
+                        ("_csrf", &csrf),
+                    ],
+                )
+                .await
+        };
+
+        // An expired link is refused
+        let (expired, hashed) = tokens::generate();
+        state
+            .user_store
+            .create_password_reset_token(&ada.id, &hashed, 0)
+            .await
+            .unwrap();
+        let response = reset(&expired, "expired password").await;
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(String::from_utf8_lossy(&body).contains("invalid or has expired"));
+
+        // Grace's link only changes Grace's password
+        let (theirs, hashed) = tokens::generate();
+        state
+            .user_store
+            .create_password_reset_token(&grace.id, &hashed, 30)
+            .await
+            .unwrap();
+        let response = reset(&theirs, "grace's new password").await;
+        assert_eq!(location(&response), "/login?reset=1");
+        let ada = state.user_store.find_by_id(&ada.id).await.unwrap();
+        assert!(ada.verify_password(PASSWORD));
+        assert_eq!(signed_in.get("/profile").await.status(), StatusCode::OK);
+
+        let (token, hashed) = tokens::generate();
+        state
+            .user_store
+            .create_password_reset_token(&ada.id, &hashed, 30)
+            .await
+            .unwrap();
+        let response = reset(&token, "a new password").await;
+        assert_eq!(location(&response), "/login?reset=1");
+
+        // The link can't be used twice
+        let response = reset(&token, "another password").await;
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(String::from_utf8_lossy(&body).contains("invalid or has expired"));
+        let ada = state.user_store.find_by_id(&ada.id).await.unwrap();
+        assert!(ada.verify_password("a new password"));
+
+        // Resetting signed out the session that knew the old password
+        let response = signed_in.get("/profile").await;
+        assert_eq!(location(&response), "/login");
+    }
+
+    #[tokio::test]
+    async fn verification_links_work_once_and_unlock_uploads() {
+        let mailer = Arc::new(mailer::RecordingMailer::default());
+        let mut state = AppState::for_tests(mailer.clone()).await;
+        Arc::get_mut(&mut state).unwrap().config.unverified_policy = UnverifiedPolicy::NoUploads;
+
+        let mut browser = Browser::new(state.clone());
+        let csrf = browser.csrf_token("/register").await;
+        browser
+            .post(
+                "/register",
+                &[
+                    ("name", "Ada"),
+                    ("email", EMAIL),
+                    ("password", PASSWORD),
+                    ("confirm_password", PASSWORD),
+                    ("_csrf", &csrf),
+                ],
+            )
+            .await;
+        tokio::task::yield_now().await;
+        let link = {
+            let sent = mailer.sent.lock().unwrap();
+            let email = sent.last().expect("verification email was sent");
+            let start = email.body.find("/verify-email?token=").unwrap();
+            email.body[start..]
+                .split_whitespace()
+                .next()
+                .unwrap()
+                .to_string()
+        };
+
+        // Unverified accounts can practise but not upload
+        assert_eq!(browser.get("/breathe").await.status(), StatusCode::OK);
+        assert_eq!(
+            browser.get("/videos/new").await.status(),
+            StatusCode::FORBIDDEN
+        );
+
+        let response = browser.get(&link).await;
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(String::from_utf8_lossy(&body).contains("Email confirmed"));
+        assert_eq!(browser.get("/videos/new").await.status(), StatusCode::OK);
+
+        // The link can't be used twice
+        let response = browser.get(&link).await;
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(String::from_utf8_lossy(&body).contains("Link not valid"));
+
+        // Nor once it has expired
+        let user = state.user_store.find_by_email(EMAIL).await.unwrap();
+        let (expired, hashed) = tokens::generate();
+        state
+            .user_store
+            .create_email_verification_token(&user.id, "ada@new.example.com", &hashed, 0)
+            .await
+            .unwrap();
+        let response = browser.get(&format!("/verify-email?token={expired}")).await;
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(String::from_utf8_lossy(&body).contains("Link not valid"));
+        let user = state.user_store.find_by_id(&user.id).await.unwrap();
+        assert_eq!(user.email, EMAIL);
+    }
+
+    #[tokio::test]
+    async fn sessions_are_recorded_from_begin_not_page_loads() {
+        let state = state_with_user().await;
+        let mut browser = Browser::new(state.clone());
+        let csrf = browser.csrf_token("/login").await;
+        browser
+            .post(
+                "/login",
+                &[("email", EMAIL), ("password", PASSWORD), ("_csrf", &csrf)],
+            )
+            .await;
+
+        for path in ["/breathe", "/meditate", "/breathe?pattern=4-7-8"] {
+            browser.get(path).await;
+        }
+        assert_eq!(state.user_store.session_start_count().await, 0);
+
+        let csrf = browser.csrf_token("/breathe").await;
+        let response = browser
+            .post(
+                "/breathe/begin",
+                &[
+                    ("start_id", ""),
+                    ("pattern", "box"),
+                    ("cycles", "3"),
+                    ("_csrf", &csrf),
+                ],
+            )
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let start_id = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let start_id = String::from_utf8(start_id.to_vec()).unwrap();
+        assert_eq!(state.user_store.session_start_count().await, 1);
+
+        // Saving straight away is too short to log, but finds the start
+        let response = browser
+            .post(
+                "/breathe/complete",
+                &[
+                    ("start_id", &start_id),
+                    ("pattern", "box"),
+                    ("cycles", "3"),
+                    ("_csrf", &csrf),
+                ],
+            )
+            .await;
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains("shorter than 30 seconds"));
+        assert_eq!(state.user_store.session_start_count().await, 1);
+    }
+
+    #[tokio::test]
+    async fn failed_breathing_keeps_the_submitted_pattern() {
+        let state = state_with_user().await;
+        let mut browser = Browser::new(state);
+        let csrf = browser.csrf_token("/login").await;
+        browser
+            .post(
+                "/login",
+                &[("email", EMAIL), ("password", PASSWORD), ("_csrf", &csrf)],
+            )
+            .await;
+
+        let csrf = browser.csrf_token("/breathe").await;
+        let response = browser
+            .post(
+                "/breathe/complete",
+                &[
+                    ("start_id", "not-a-session"),
+                    ("pattern", "4-7-8"),
+                    ("cycles", "9"),
+                    ("_csrf", &csrf),
+                ],
+            )
+            .await;
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains("find the start of this session"));
+        assert!(body.contains("You completed 9 rounds of 4-7-8 relaxing breath."));
+
+        let response = browser
+            .post(
+                "/breathe/patterns",
+                &[
+                    ("name", ""),
+                    ("inhale", "4"),
+                    ("hold_in", "0"),
+                    ("exhale", "6"),
+                    ("hold_out", "0"),
+                    ("pattern", "coherent"),
+                    ("cycles", "12"),
+                    ("_csrf", &csrf),
+                ],
+            )
+            .await;
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains("Pattern name must be between 1 and 40 characters."));
+        assert!(body.contains("You completed 12 rounds of Coherent breathing."));
+    }
+
+    #[tokio::test]
+    async fn read_only_accounts_cannot_save_patterns_or_presets() {
+        let mut state = AppState::for_tests(Arc::new(mailer::LogMailer)).await;
+        Arc::get_mut(&mut state).unwrap().config.unverified_policy = UnverifiedPolicy::ReadOnly;
+        state
+            .user_store
+            .create_user(
+                "Ada".to_string(),
+                EMAIL.to_string(),
+                PASSWORD.to_string(),
+                "UTC".to_string(),
+            )
+            .await
+            .unwrap();
+        let user = state.user_store.find_by_email(EMAIL).await.unwrap();
+        let pattern = state
+            .user_store
+            .create_breathing_pattern(&user.id, "Mine", [4.0, 0.0, 6.0, 0.0])
+            .await
+            .unwrap();
+        let settings = MeditationSettings::clamped(10, 0, 30);
+        let preset = state
+            .user_store
+            .create_meditation_preset(&user.id, "Mine", &settings)
+            .await
+            .unwrap();
+
+        let mut browser = Browser::new(state.clone());
+        let csrf = browser.csrf_token("/login").await;
+        browser
+            .post(
+                "/login",
+                &[("email", EMAIL), ("password", PASSWORD), ("_csrf", &csrf)],
+            )
+            .await;
+        let csrf = browser.csrf_token("/breathe").await;
+        let csrf = csrf.as_str();
+
+        let pattern_delete = format!(
+            "/breathe/patterns/{}/delete",
+            pattern.trim_start_matches("custom-")
+        );
+        let preset_delete = format!("/meditate/presets/{preset}/delete");
+        for (path, form) in [
+            (
+                "/breathe/patterns",
+                &[
+                    ("name", "Another"),
+                    ("inhale", "4"),
+                    ("hold_in", "0"),
+                    ("exhale", "6"),
+                    ("hold_out", "0"),
+                    ("_csrf", csrf),
+                ][..],
+            ),
+            (pattern_delete.as_str(), &[("_csrf", csrf)][..]),
+            (
+                "/meditate/presets",
+                &[
+                    ("name", "Another"),
+                    ("minutes", "10"),
+                    ("interval", "0"),
+                    ("prep", "30"),
+                    ("_csrf", csrf),
+                ][..],
+            ),
+            (preset_delete.as_str(), &[("_csrf", csrf)][..]),
+        ] {
+            let response = browser.post(path, form).await;
+            assert_eq!(response.status(), StatusCode::FORBIDDEN, "{path}");
+        }
+
+        assert_eq!(
+            state
+                .user_store
+                .get_breathing_patterns(&user.id)
+                .await
+                .len(),
+            1
+        );
+        assert_eq!(
+            state
+                .user_store
+                .get_meditation_presets(&user.id)
+                .await
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_meditation_keeps_the_submitted_settings() {
+        let state = state_with_user().await;
+        let mut browser = Browser::new(state);
+        let csrf = browser.csrf_token("/login").await;
+        browser
+            .post(
+                "/login",
+                &[("email", EMAIL), ("password", PASSWORD), ("_csrf", &csrf)],
+            )
+            .await;
+
+        let csrf = browser.csrf_token("/meditate").await;
+        let response = browser
+            .post(
+                "/meditate/complete",
+                &[
+                    ("start_id", "not-a-session"),
+                    ("minutes", "25"),
+                    ("interval", "5"),
+                    ("prep", "30"),
+                    ("_csrf", &csrf),
+                ],
+            )
+            .await;
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains("find the start of this session"));
+        assert!(body.contains("25 min · bell every 5 min · 30s preparation"));
+    }
+
+    #[tokio::test]
+    async fn read_only_accounts_cannot_edit_or_delete_journal_entries() {
+        let mut state = AppState::for_tests(Arc::new(mailer::LogMailer)).await;
+        Arc::get_mut(&mut state).unwrap().config.unverified_policy = UnverifiedPolicy::ReadOnly;
+        state
+            .user_store
+            .create_user(
+                "Ada".to_string(),
+                EMAIL.to_string(),
+                PASSWORD.to_string(),
+                "UTC".to_string(),
+            )
+            .await
+            .unwrap();
+        let user = state.user_store.find_by_email(EMAIL).await.unwrap();
+        state
+            .user_store
+            .log_journal_entry(&user.id, 3, "before", None, &[])
+            .await
+            .unwrap();
+        let entry = &state
+            .user_store
+            .get_journal_entries(&user.id, &Default::default(), 1)
+            .await
+            .entries[0];
+
+        let mut browser = Browser::new(state.clone());
+        let csrf = browser.csrf_token("/login").await;
+        browser
+            .post(
+                "/login",
+                &[("email", EMAIL), ("password", PASSWORD), ("_csrf", &csrf)],
+            )
+            .await;
+        let csrf = browser.csrf_token("/profile").await;
+
+        let response = browser
+            .post(
+                &format!("/journal/entries/{}/edit", entry.id),
+                &[
+                    ("mood", "5"),
+                    ("note", "after"),
+                    ("tags", ""),
+                    ("_csrf", &csrf),
+                ],
+            )
+            .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(String::from_utf8_lossy(&body).contains("Please confirm your email"));
+        let response = browser
+            .post(
+                &format!("/journal/entries/{}/delete", entry.id),
+                &[("_csrf", &csrf)],
+            )
+            .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(String::from_utf8_lossy(&body).contains("Please confirm your email"));
+
+        let kept = state
+            .user_store
+            .get_journal_entry(&user.id, &entry.id)
+            .await
+            .expect("entry wasn't deleted");
+        assert_eq!((kept.mood, kept.note.as_str()), (3, "before"));
+    }
 }
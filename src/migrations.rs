@@ -0,0 +1,590 @@
+use sqlx::SqlitePool;
+
+/// A single forward-only schema change. Versions must be unique and strictly
+/// increasing; never edit a migration once it has shipped — add a new one.
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub sql: &'static str,
+}
+
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial_schema",
+        // Uses IF NOT EXISTS so databases created by the old `init_schema` adopt
+        // this version without error.
+        sql: "CREATE TABLE IF NOT EXISTS users (
+            id            TEXT PRIMARY KEY,
+            name          TEXT NOT NULL,
+            email         TEXT UNIQUE NOT NULL,
+            password_hash TEXT NOT NULL,
+            created_at    TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        CREATE TABLE IF NOT EXISTS mindful_sessions (
+            id           TEXT PRIMARY KEY,
+            user_id      TEXT NOT NULL,
+            session_type TEXT NOT NULL,
+            duration_min INTEGER NOT NULL DEFAULT 0,
+            completed_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (user_id) REFERENCES users(id)
+        );
+
+        CREATE TABLE IF NOT EXISTS journal_entries (
+            id         TEXT PRIMARY KEY,
+            user_id    TEXT NOT NULL,
+            mood       INTEGER NOT NULL CHECK(mood BETWEEN 1 AND 5),
+            note       TEXT NOT NULL DEFAULT '',
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (user_id) REFERENCES users(id)
+        );
+
+        CREATE TABLE IF NOT EXISTS videos (
+            id            TEXT PRIMARY KEY,
+            user_id       TEXT NOT NULL,
+            title         TEXT NOT NULL,
+            description   TEXT NOT NULL DEFAULT '',
+            video_url     TEXT NOT NULL,
+            thumbnail_url TEXT NOT NULL DEFAULT '',
+            category      TEXT NOT NULL DEFAULT 'general',
+            created_at    TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (user_id) REFERENCES users(id)
+        );
+
+        CREATE TABLE IF NOT EXISTS newsletter_subscribers (
+            id                TEXT PRIMARY KEY,
+            email             TEXT UNIQUE NOT NULL,
+            name              TEXT NOT NULL DEFAULT '',
+            unsubscribe_token TEXT UNIQUE NOT NULL,
+            subscribed_at     TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        CREATE TABLE IF NOT EXISTS newsletter_articles (
+            id           TEXT PRIMARY KEY,
+            title        TEXT NOT NULL,
+            summary      TEXT NOT NULL DEFAULT '',
+            content_html TEXT NOT NULL DEFAULT '',
+            source_urls  TEXT NOT NULL DEFAULT '',
+            published_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );",
+    },
+    Migration {
+        version: 2,
+        name: "sessions",
+        sql: "CREATE TABLE sessions (
+            id          TEXT PRIMARY KEY,
+            data        TEXT NOT NULL,
+            expiry_date INTEGER NOT NULL
+        );
+
+        CREATE INDEX idx_sessions_expiry_date ON sessions(expiry_date);",
+    },
+    Migration {
+        version: 3,
+        name: "password_reset_tokens",
+        sql: "CREATE TABLE password_reset_tokens (
+            token_hash TEXT PRIMARY KEY,
+            user_id    TEXT NOT NULL,
+            expires_at TEXT NOT NULL,
+            used_at    TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (user_id) REFERENCES users(id)
+        );
+
+        CREATE INDEX idx_password_reset_tokens_user ON password_reset_tokens(user_id);",
+    },
+    Migration {
+        version: 4,
+        name: "email_verification",
+        // Accounts from before verification existed keep their access:
+        // they're treated as verified from sign-up. Only new sign-ups and
+        // address changes need the emailed link.
+        sql: "ALTER TABLE users ADD COLUMN email_verified_at TEXT;
+
+        UPDATE users SET email_verified_at = created_at;
+
+        CREATE TABLE email_verification_tokens (
+            token_hash TEXT PRIMARY KEY,
+            user_id    TEXT NOT NULL,
+            email      TEXT NOT NULL,
+            expires_at TEXT NOT NULL,
+            used_at    TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (user_id) REFERENCES users(id)
+        );
+
+        CREATE INDEX idx_email_verification_tokens_user ON email_verification_tokens(user_id);",
+    },
+    Migration {
+        version: 5,
+        name: "totp_two_factor",
+        sql: "ALTER TABLE users ADD COLUMN totp_secret TEXT;
+        ALTER TABLE users ADD COLUMN totp_enabled_at TEXT;
+        ALTER TABLE users ADD COLUMN totp_last_step INTEGER;
+
+        CREATE TABLE totp_recovery_codes (
+            id         TEXT PRIMARY KEY,
+            user_id    TEXT NOT NULL,
+            code_hash  TEXT NOT NULL,
+            used_at    TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (user_id) REFERENCES users(id)
+        );
+
+        CREATE INDEX idx_totp_recovery_codes_user ON totp_recovery_codes(user_id);",
+    },
+    Migration {
+        version: 6,
+        name: "login_attempts",
+        sql: "CREATE TABLE login_attempts (
+            id           TEXT PRIMARY KEY,
+            email        TEXT NOT NULL,
+            ip           TEXT NOT NULL,
+            succeeded    INTEGER NOT NULL DEFAULT 0,
+            reason       TEXT NOT NULL DEFAULT '',
+            attempted_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        CREATE INDEX idx_login_attempts_email ON login_attempts(email, attempted_at);
+        CREATE INDEX idx_login_attempts_ip ON login_attempts(ip, attempted_at);",
+    },
+    Migration {
+        version: 7,
+        name: "breathing_patterns",
+        sql: "CREATE TABLE breathing_patterns (
+            id            TEXT PRIMARY KEY,
+            user_id       TEXT NOT NULL,
+            name          TEXT NOT NULL,
+            inhale_secs   REAL NOT NULL,
+            hold_in_secs  REAL NOT NULL DEFAULT 0,
+            exhale_secs   REAL NOT NULL,
+            hold_out_secs REAL NOT NULL DEFAULT 0,
+            created_at    TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (user_id) REFERENCES users(id)
+        );
+
+        CREATE INDEX idx_breathing_patterns_user ON breathing_patterns(user_id);
+
+        ALTER TABLE mindful_sessions ADD COLUMN pattern TEXT;
+        ALTER TABLE mindful_sessions ADD COLUMN cycles INTEGER;",
+    },
+    Migration {
+        version: 8,
+        name: "session_starts",
+        // `started_at` is when the page was opened; `begun_at` is when the
+        // timer started (after `lead_in_secs` of preparation) and is what
+        // completions are measured from.
+        sql: "CREATE TABLE session_starts (
+            id           TEXT PRIMARY KEY,
+            user_id      TEXT NOT NULL,
+            session_type TEXT NOT NULL,
+            planned_secs INTEGER NOT NULL,
+            lead_in_secs INTEGER NOT NULL DEFAULT 0,
+            pattern      TEXT,
+            cycles       INTEGER,
+            started_at   TEXT NOT NULL DEFAULT (datetime('now')),
+            begun_at     TEXT,
+            completed_at TEXT,
+            FOREIGN KEY (user_id) REFERENCES users(id)
+        );
+
+        CREATE INDEX idx_session_starts_user ON session_starts(user_id);
+
+        ALTER TABLE mindful_sessions ADD COLUMN planned_secs INTEGER;
+        ALTER TABLE mindful_sessions ADD COLUMN actual_secs INTEGER;",
+    },
+    Migration {
+        version: 9,
+        name: "meditation_presets",
+        sql: "CREATE TABLE meditation_presets (
+            id           TEXT PRIMARY KEY,
+            user_id      TEXT NOT NULL,
+            name         TEXT NOT NULL,
+            duration_min INTEGER NOT NULL,
+            interval_min INTEGER NOT NULL DEFAULT 0,
+            prep_secs    INTEGER NOT NULL DEFAULT 0,
+            created_at   TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (user_id) REFERENCES users(id)
+        );
+
+        CREATE INDEX idx_meditation_presets_user ON meditation_presets(user_id);",
+    },
+    Migration {
+        version: 10,
+        name: "journal_entry_sessions",
+        // Link each journal entry to the session it credits. Existing rows were
+        // written in the same request, so pair them up by timestamp.
+        sql: "ALTER TABLE mindful_sessions ADD COLUMN journal_entry_id TEXT;
+        ALTER TABLE journal_entries ADD COLUMN updated_at TEXT;
+
+        CREATE INDEX idx_mindful_sessions_journal_entry ON mindful_sessions(journal_entry_id);
+
+        WITH s AS (
+            SELECT id, user_id, completed_at,
+                   ROW_NUMBER() OVER (PARTITION BY user_id, completed_at ORDER BY id) AS rn
+            FROM mindful_sessions WHERE session_type = 'journal'
+        ),
+        e AS (
+            SELECT id, user_id, created_at,
+                   ROW_NUMBER() OVER (PARTITION BY user_id, created_at ORDER BY id) AS rn
+            FROM journal_entries
+        )
+        UPDATE mindful_sessions SET journal_entry_id = (
+            SELECT e.id FROM s
+            JOIN e ON e.user_id = s.user_id AND e.created_at = s.completed_at AND e.rn = s.rn
+            WHERE s.id = mindful_sessions.id
+        )
+        WHERE session_type = 'journal';",
+    },
+    Migration {
+        version: 11,
+        name: "journal_search",
+        // External-content FTS5 index over journal notes, kept in step with
+        // `journal_entries` by triggers.
+        sql: "CREATE VIRTUAL TABLE journal_fts USING fts5(
+            note,
+            content = 'journal_entries',
+            content_rowid = 'rowid',
+            tokenize = 'porter unicode61'
+        );
+
+        CREATE TRIGGER journal_fts_insert AFTER INSERT ON journal_entries BEGIN
+            INSERT INTO journal_fts (rowid, note) VALUES (new.rowid, new.note);
+        END;
+
+        CREATE TRIGGER journal_fts_delete AFTER DELETE ON journal_entries BEGIN
+            INSERT INTO journal_fts (journal_fts, rowid, note) VALUES ('delete', old.rowid, old.note);
+        END;
+
+        CREATE TRIGGER journal_fts_update AFTER UPDATE OF note ON journal_entries BEGIN
+            INSERT INTO journal_fts (journal_fts, rowid, note) VALUES ('delete', old.rowid, old.note);
+            INSERT INTO journal_fts (rowid, note) VALUES (new.rowid, new.note);
+        END;
+
+        INSERT INTO journal_fts (journal_fts) VALUES ('rebuild');",
+    },
+    Migration {
+        version: 12,
+        name: "journal_tags_and_prompts",
+        sql: "CREATE TABLE journal_tags (
+            id         TEXT PRIMARY KEY,
+            user_id    TEXT NOT NULL,
+            name       TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            UNIQUE (user_id, name),
+            FOREIGN KEY (user_id) REFERENCES users(id)
+        );
+
+        CREATE TABLE journal_entry_tags (
+            entry_id TEXT NOT NULL,
+            tag_id   TEXT NOT NULL,
+            PRIMARY KEY (entry_id, tag_id),
+            FOREIGN KEY (entry_id) REFERENCES journal_entries(id),
+            FOREIGN KEY (tag_id) REFERENCES journal_tags(id)
+        );
+
+        CREATE INDEX idx_journal_entry_tags_tag ON journal_entry_tags(tag_id);
+
+        ALTER TABLE journal_entries ADD COLUMN prompt TEXT;",
+    },
+    Migration {
+        version: 13,
+        name: "journal_note_encryption",
+        // Each user's journal key, wrapped by the master key `master_key_id`.
+        // FTS5 can only index plaintext, so the journal_fts index from
+        // migration 11 goes. journal_search_tokens replaces it with a blind
+        // index: one row per word prefix in a note, hashed under the owner's
+        // data key (`DataKey::search_token`). It matches words and word
+        // beginnings but not phrases; see "Journal search" in the README.
+        // `search_indexed` lets older entries be indexed in the background.
+        sql: "CREATE TABLE user_data_keys (
+            user_id       TEXT PRIMARY KEY,
+            wrapped_key   BLOB NOT NULL,
+            master_key_id TEXT NOT NULL,
+            created_at    TEXT NOT NULL DEFAULT (datetime('now')),
+            rotated_at    TEXT,
+            FOREIGN KEY (user_id) REFERENCES users(id)
+        );
+
+        DROP TRIGGER journal_fts_insert;
+        DROP TRIGGER journal_fts_delete;
+        DROP TRIGGER journal_fts_update;
+        DROP TABLE journal_fts;
+
+        CREATE TABLE journal_search_tokens (
+            token    BLOB NOT NULL,
+            entry_id TEXT NOT NULL,
+            PRIMARY KEY (token, entry_id),
+            FOREIGN KEY (entry_id) REFERENCES journal_entries(id)
+        ) WITHOUT ROWID;
+
+        CREATE INDEX idx_journal_search_tokens_entry ON journal_search_tokens(entry_id);
+
+        ALTER TABLE journal_entries ADD COLUMN search_indexed INTEGER NOT NULL DEFAULT 0;",
+    },
+    Migration {
+        version: 14,
+        name: "user_timezones",
+        // IANA name, e.g. `America/Los_Angeles`, used for day boundaries.
+        sql: "ALTER TABLE users ADD COLUMN timezone TEXT NOT NULL DEFAULT 'UTC';",
+    },
+    Migration {
+        version: 15,
+        name: "mindfulness_goals",
+        // `goal` is a `GoalKind` key. `goal_periods` records each day or week
+        // (by its first local date) with the target that applied then.
+        sql: "CREATE TABLE user_goals (
+            user_id TEXT NOT NULL,
+            goal    TEXT NOT NULL,
+            target  INTEGER NOT NULL,
+            set_at  TEXT NOT NULL DEFAULT (datetime('now')),
+            PRIMARY KEY (user_id, goal),
+            FOREIGN KEY (user_id) REFERENCES users(id)
+        );
+
+        CREATE TABLE goal_periods (
+            user_id      TEXT NOT NULL,
+            goal         TEXT NOT NULL,
+            period_start TEXT NOT NULL,
+            target       INTEGER NOT NULL,
+            achieved     INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY (user_id, goal, period_start),
+            FOREIGN KEY (user_id) REFERENCES users(id)
+        );",
+    },
+    Migration {
+        version: 16,
+        name: "streak_checkpoints",
+        // The streak tally (`StreakTally`) at the end of `day` in `timezone`,
+        // so the dashboard only replays the days since. Deleting activity on
+        // or before `day` drops the row.
+        sql: "CREATE TABLE streak_checkpoints (
+            user_id     TEXT PRIMARY KEY,
+            day         TEXT NOT NULL,
+            timezone    TEXT NOT NULL,
+            current     INTEGER NOT NULL,
+            longest     INTEGER NOT NULL,
+            freezes     INTEGER NOT NULL,
+            frozen_days INTEGER NOT NULL,
+            FOREIGN KEY (user_id) REFERENCES users(id)
+        );",
+    },
+    Migration {
+        version: 17,
+        name: "achievements",
+        // `achievement` is an `Achievement` key. `notified_at` is set once the
+        // dashboard has announced the badge.
+        sql: "CREATE TABLE user_achievements (
+            user_id     TEXT NOT NULL,
+            achievement TEXT NOT NULL,
+            unlocked_at TEXT NOT NULL DEFAULT (datetime('now')),
+            notified_at TEXT,
+            PRIMARY KEY (user_id, achievement),
+            FOREIGN KEY (user_id) REFERENCES users(id)
+        );",
+    },
+];
+
+/// Highest version known to this build.
+pub fn latest_version() -> i64 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+async fn ensure_migrations_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version    INTEGER PRIMARY KEY,
+            name       TEXT NOT NULL,
+            applied_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Version of the most recently applied migration, or 0 for a fresh database.
+pub async fn current_version(pool: &SqlitePool) -> Result<i64, sqlx::Error> {
+    ensure_migrations_table(pool).await?;
+
+    sqlx::query_scalar::<_, i64>("SELECT COALESCE(MAX(version), 0) FROM schema_migrations")
+        .fetch_one(pool)
+        .await
+}
+
+/// Apply every migration newer than the current version, each in its own
+/// transaction together with its `schema_migrations` row. Returns the
+/// versions that were applied.
+pub async fn run_pending(pool: &SqlitePool) -> Result<Vec<i64>, sqlx::Error> {
+    let current = current_version(pool).await?;
+    let mut applied = Vec::new();
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        let mut tx = pool.begin().await?;
+
+        sqlx::raw_sql(migration.sql).execute(&mut *tx).await?;
+
+        sqlx::query("INSERT INTO schema_migrations (version, name) VALUES (?, ?)")
+            .bind(migration.version)
+            .bind(migration.name)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        println!(
+            "🗄️  Applied migration {:03} {}",
+            migration.version, migration.name
+        );
+        applied.push(migration.version);
+    }
+
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    /// The schema `db::init_schema` created before versioned migrations.
+    const INIT_SCHEMA: &str = "CREATE TABLE IF NOT EXISTS users (
+            id            TEXT PRIMARY KEY,
+            name          TEXT NOT NULL,
+            email         TEXT UNIQUE NOT NULL,
+            password_hash TEXT NOT NULL,
+            created_at    TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        CREATE TABLE IF NOT EXISTS mindful_sessions (
+            id           TEXT PRIMARY KEY,
+            user_id      TEXT NOT NULL,
+            session_type TEXT NOT NULL,
+            duration_min INTEGER NOT NULL DEFAULT 0,
+            completed_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (user_id) REFERENCES users(id)
+        );
+        CREATE TABLE IF NOT EXISTS journal_entries (
+            id         TEXT PRIMARY KEY,
+            user_id    TEXT NOT NULL,
+            mood       INTEGER NOT NULL CHECK(mood BETWEEN 1 AND 5),
+            note       TEXT NOT NULL DEFAULT '',
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (user_id) REFERENCES users(id)
+        );
+        CREATE TABLE IF NOT EXISTS videos (
+            id            TEXT PRIMARY KEY,
+            user_id       TEXT NOT NULL,
+            title         TEXT NOT NULL,
+            description   TEXT NOT NULL DEFAULT '',
+            video_url     TEXT NOT NULL,
+            thumbnail_url TEXT NOT NULL DEFAULT '',
+            category      TEXT NOT NULL DEFAULT 'general',
+            created_at    TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (user_id) REFERENCES users(id)
+        );
+        CREATE TABLE IF NOT EXISTS newsletter_subscribers (
+            id                TEXT PRIMARY KEY,
+            email             TEXT UNIQUE NOT NULL,
+            name              TEXT NOT NULL DEFAULT '',
+            unsubscribe_token TEXT UNIQUE NOT NULL,
+            subscribed_at     TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        CREATE TABLE IF NOT EXISTS newsletter_articles (
+            id           TEXT PRIMARY KEY,
+            title        TEXT NOT NULL,
+            summary      TEXT NOT NULL DEFAULT '',
+            content_html TEXT NOT NULL DEFAULT '',
+            source_urls  TEXT NOT NULL DEFAULT '',
+            published_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );";
+
+    /// An empty in-memory database; see `db::test_pool`.
+    async fn empty_pool() -> SqlitePool {
+        SqlitePoolOptions::new()
+            .max_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .connect("sqlite::memory:")
+            .await
+            .expect("in-memory database opens")
+    }
+
+    async fn recorded_versions(pool: &SqlitePool) -> Vec<i64> {
+        sqlx::query_scalar("SELECT version FROM schema_migrations ORDER BY version")
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn versions_strictly_increase() {
+        assert!(MIGRATIONS.windows(2).all(|w| w[0].version < w[1].version));
+        assert_eq!(MIGRATIONS[0].version, 1);
+    }
+
+    #[tokio::test]
+    async fn fresh_database_reaches_the_latest_version() {
+        let pool = empty_pool().await;
+        assert_eq!(current_version(&pool).await.unwrap(), 0);
+
+        let applied = run_pending(&pool).await.unwrap();
+        let all: Vec<i64> = MIGRATIONS.iter().map(|m| m.version).collect();
+        assert_eq!(applied, all);
+        assert_eq!(recorded_versions(&pool).await, all);
+        assert_eq!(current_version(&pool).await.unwrap(), latest_version());
+    }
+
+    #[tokio::test]
+    async fn running_again_is_a_no_op() {
+        let pool = empty_pool().await;
+        run_pending(&pool).await.unwrap();
+        let before = recorded_versions(&pool).await;
+
+        assert!(run_pending(&pool).await.unwrap().is_empty());
+        assert_eq!(recorded_versions(&pool).await, before);
+        assert_eq!(current_version(&pool).await.unwrap(), latest_version());
+    }
+
+    #[tokio::test]
+    async fn database_from_init_schema_is_adopted() {
+        let pool = empty_pool().await;
+        sqlx::raw_sql(INIT_SCHEMA).execute(&pool).await.unwrap();
+        sqlx::raw_sql(
+            "INSERT INTO users (id, name, email, password_hash)
+                 VALUES ('u1', 'Ada', 'ada@example.com', 'hash');
+             INSERT INTO mindful_sessions (id, user_id, session_type, duration_min)
+                 VALUES ('s1', 'u1', 'breathing', 5);
+             INSERT INTO journal_entries (id, user_id, mood, note)
+                 VALUES ('j1', 'u1', 4, 'Slept well');",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        run_pending(&pool).await.unwrap();
+        assert_eq!(current_version(&pool).await.unwrap(), latest_version());
+
+        let user: (String, String, String) =
+            sqlx::query_as("SELECT name, email, password_hash FROM users WHERE id = 'u1'")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(
+            user,
+            ("Ada".into(), "ada@example.com".into(), "hash".into())
+        );
+        let session: (String, i64) =
+            sqlx::query_as("SELECT session_type, duration_min FROM mindful_sessions")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(session, ("breathing".into(), 5));
+        let entry: (i64, String) = sqlx::query_as("SELECT mood, note FROM journal_entries")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(entry, (4, "Slept well".into()));
+    }
+}
@@ -0,0 +1,198 @@
+use crate::models::history::HISTORY_TYPES;
+
+/// Everything the achievement rules look at, gathered in one pass.
+#[derive(Clone, Debug, Default)]
+pub struct AchievementStats {
+    /// Breathing and meditation sessions.
+    pub sessions: i64,
+    pub total_minutes: i64,
+    pub journal_entries: i64,
+    /// Distinct `session_type`s completed, journal included.
+    pub session_types: Vec<String>,
+    pub longest_streak: i64,
+    /// Days a streak freeze was used.
+    pub frozen_days: i64,
+}
+
+impl AchievementStats {
+    /// Every achievement whose rule these stats meet, in catalogue order.
+    pub fn earned(&self) -> impl Iterator<Item = &'static Achievement> + '_ {
+        ACHIEVEMENTS.iter().filter(|a| (a.rule)(self))
+    }
+}
+
+/// A badge and the rule that unlocks it. Once unlocked a badge is kept,
+/// even if the activity behind it is later deleted.
+pub struct Achievement {
+    pub key: &'static str,
+    /// HTML entity.
+    pub icon: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub rule: fn(&AchievementStats) -> bool,
+}
+
+/// Every achievement, in the order they're shown.
+pub const ACHIEVEMENTS: &[Achievement] = &[
+    Achievement {
+        key: "first_session",
+        icon: "&#127793;",
+        name: "First Steps",
+        description: "Complete your first breathing or meditation session.",
+        rule: |s| s.sessions >= 1,
+    },
+    Achievement {
+        key: "first_journal",
+        icon: "&#128221;",
+        name: "Dear Diary",
+        description: "Write your first journal entry.",
+        rule: |s| s.journal_entries >= 1,
+    },
+    Achievement {
+        key: "explorer",
+        icon: "&#129517;",
+        name: "Explorer",
+        description: "Try breathing, meditation and journaling.",
+        rule: |s| {
+            HISTORY_TYPES
+                .iter()
+                .all(|(t, _)| s.session_types.iter().any(|done| done == t))
+        },
+    },
+    Achievement {
+        key: "sessions_10",
+        icon: "&#128170;",
+        name: "Building a Habit",
+        description: "Complete 10 breathing or meditation sessions.",
+        rule: |s| s.sessions >= 10,
+    },
+    Achievement {
+        key: "streak_7",
+        icon: "&#128293;",
+        name: "Week of Calm",
+        description: "Reach a 7-day streak.",
+        rule: |s| s.longest_streak >= 7,
+    },
+    Achievement {
+        key: "streak_30",
+        icon: "&#127775;",
+        name: "Month of Mindfulness",
+        description: "Reach a 30-day streak.",
+        rule: |s| s.longest_streak >= 30,
+    },
+    Achievement {
+        key: "resilient",
+        icon: "&#10052;&#65039;",
+        name: "Resilient",
+        description: "Keep a streak alive with a streak freeze.",
+        rule: |s| s.frozen_days >= 1,
+    },
+    Achievement {
+        key: "minutes_100",
+        icon: "&#9200;",
+        name: "Century",
+        description: "Practise for 100 mindful minutes in total.",
+        rule: |s| s.total_minutes >= 100,
+    },
+    Achievement {
+        key: "minutes_1000",
+        icon: "&#127956;&#65039;",
+        name: "Deep Roots",
+        description: "Practise for 1,000 mindful minutes in total.",
+        rule: |s| s.total_minutes >= 1000,
+    },
+    Achievement {
+        key: "journal_30",
+        icon: "&#128218;",
+        name: "Storyteller",
+        description: "Write 30 journal entries.",
+        rule: |s| s.journal_entries >= 30,
+    },
+];
+
+pub fn find_achievement(key: &str) -> Option<&'static Achievement> {
+    ACHIEVEMENTS.iter().find(|a| a.key == key)
+}
+
+/// A badge a user has earned. `unlocked_at` is in the user's timezone once
+/// loaded through the store.
+#[derive(Clone, Debug, sqlx::FromRow)]
+pub struct UnlockedAchievement {
+    pub achievement: String,
+    pub unlocked_at: String,
+}
+
+impl UnlockedAchievement {
+    pub fn details(&self) -> Option<&'static Achievement> {
+        find_achievement(&self.achievement)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn earned(stats: &AchievementStats) -> Vec<&'static str> {
+        stats.earned().map(|a| a.key).collect()
+    }
+
+    /// Whether `key` is earned just below and exactly at its threshold.
+    fn boundary(key: &str, set: impl Fn(&mut AchievementStats, i64), threshold: i64) {
+        let mut stats = AchievementStats::default();
+        set(&mut stats, threshold - 1);
+        assert!(!earned(&stats).contains(&key), "{key} below {threshold}");
+        set(&mut stats, threshold);
+        assert!(earned(&stats).contains(&key), "{key} at {threshold}");
+    }
+
+    #[test]
+    fn nothing_is_earned_without_activity() {
+        assert!(earned(&AchievementStats::default()).is_empty());
+    }
+
+    #[test]
+    fn thresholds_unlock_exactly_at_their_boundary() {
+        boundary("first_session", |s, n| s.sessions = n, 1);
+        boundary("sessions_10", |s, n| s.sessions = n, 10);
+        boundary("first_journal", |s, n| s.journal_entries = n, 1);
+        boundary("journal_30", |s, n| s.journal_entries = n, 30);
+        boundary("streak_7", |s, n| s.longest_streak = n, 7);
+        boundary("streak_30", |s, n| s.longest_streak = n, 30);
+        boundary("resilient", |s, n| s.frozen_days = n, 1);
+        boundary("minutes_100", |s, n| s.total_minutes = n, 100);
+        boundary("minutes_1000", |s, n| s.total_minutes = n, 1000);
+    }
+
+    #[test]
+    fn explorer_needs_every_activity_type() {
+        let mut stats = AchievementStats {
+            session_types: vec!["breathing".to_string(), "journal".to_string()],
+            ..AchievementStats::default()
+        };
+        assert!(!earned(&stats).contains(&"explorer"));
+
+        stats.session_types.push("meditation".to_string());
+        assert!(earned(&stats).contains(&"explorer"));
+    }
+
+    #[test]
+    fn earned_keeps_catalogue_order() {
+        let stats = AchievementStats {
+            sessions: 10,
+            journal_entries: 1,
+            ..AchievementStats::default()
+        };
+        assert_eq!(
+            earned(&stats),
+            ["first_session", "first_journal", "sessions_10"]
+        );
+    }
+
+    #[test]
+    fn keys_are_unique() {
+        for (i, a) in ACHIEVEMENTS.iter().enumerate() {
+            assert_eq!(find_achievement(a.key).map(|f| f.name), Some(a.name));
+            assert!(ACHIEVEMENTS[i + 1..].iter().all(|b| b.key != a.key));
+        }
+    }
+}
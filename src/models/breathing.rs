@@ -0,0 +1,161 @@
+use serde::Serialize;
+
+/// Fewest and most cycles a breathing session can be set to.
+pub const MIN_CYCLES: i64 = 1;
+pub const MAX_CYCLES: i64 = 60;
+/// Custom patterns a user may keep.
+pub const MAX_CUSTOM_PATTERNS: i64 = 10;
+/// Longest single phase allowed in a custom pattern, in seconds.
+pub const MAX_PHASE_SECS: f64 = 20.0;
+
+/// One step of a breathing cycle. `expand` drives the circle animation.
+#[derive(Clone, Debug, Serialize)]
+pub struct BreathPhase {
+    pub label: &'static str,
+    pub seconds: f64,
+    pub expand: bool,
+}
+
+/// A breathing exercise, either built in or defined by the user. `key` is what
+/// gets stored in `mindful_sessions.pattern`.
+#[derive(Clone, Debug, Serialize)]
+pub struct BreathingPattern {
+    pub key: String,
+    pub name: String,
+    pub phases: Vec<BreathPhase>,
+    pub default_cycles: i64,
+    pub custom: bool,
+}
+
+/// A row of `breathing_patterns`.
+#[derive(Clone, Debug, sqlx::FromRow)]
+pub struct CustomBreathingPattern {
+    pub id: String,
+    pub name: String,
+    pub inhale_secs: f64,
+    pub hold_in_secs: f64,
+    pub exhale_secs: f64,
+    pub hold_out_secs: f64,
+}
+
+fn phase(label: &'static str, seconds: f64, expand: bool) -> BreathPhase {
+    BreathPhase {
+        label,
+        seconds,
+        expand,
+    }
+}
+
+fn builtin(
+    key: &str,
+    name: &str,
+    phases: Vec<BreathPhase>,
+    default_cycles: i64,
+) -> BreathingPattern {
+    BreathingPattern {
+        key: key.to_string(),
+        name: name.to_string(),
+        phases,
+        default_cycles,
+        custom: false,
+    }
+}
+
+/// The patterns everyone can pick, box breathing first as the default.
+pub fn builtin_patterns() -> Vec<BreathingPattern> {
+    vec![
+        builtin(
+            "box",
+            "Box breathing",
+            vec![
+                phase("Inhale", 4.0, true),
+                phase("Hold", 4.0, true),
+                phase("Exhale", 4.0, false),
+                phase("Hold", 4.0, false),
+            ],
+            5,
+        ),
+        builtin(
+            "4-7-8",
+            "4-7-8 relaxing breath",
+            vec![
+                phase("Inhale", 4.0, true),
+                phase("Hold", 7.0, true),
+                phase("Exhale", 8.0, false),
+            ],
+            4,
+        ),
+        builtin(
+            "coherent",
+            "Coherent breathing",
+            vec![phase("Inhale", 5.5, true), phase("Exhale", 5.5, false)],
+            27,
+        ),
+        builtin(
+            "sigh",
+            "Physiological sigh",
+            vec![
+                phase("Inhale", 2.0, true),
+                phase("Inhale again", 1.0, true),
+                phase("Long exhale", 6.0, false),
+            ],
+            10,
+        ),
+    ]
+}
+
+impl CustomBreathingPattern {
+    pub fn to_pattern(&self) -> BreathingPattern {
+        let mut phases = vec![phase("Inhale", self.inhale_secs, true)];
+        if self.hold_in_secs > 0.0 {
+            phases.push(phase("Hold", self.hold_in_secs, true));
+        }
+        phases.push(phase("Exhale", self.exhale_secs, false));
+        if self.hold_out_secs > 0.0 {
+            phases.push(phase("Hold", self.hold_out_secs, false));
+        }
+
+        BreathingPattern {
+            key: format!("custom-{}", self.id),
+            name: self.name.clone(),
+            phases,
+            default_cycles: 5,
+            custom: true,
+        }
+    }
+}
+
+impl BreathingPattern {
+    pub fn cycle_seconds(&self) -> f64 {
+        self.phases.iter().map(|p| p.seconds).sum()
+    }
+
+    /// Seconds `cycles` rounds take, rounded up.
+    pub fn planned_secs(&self, cycles: i64) -> i64 {
+        (self.cycle_seconds() * cycles as f64).ceil() as i64
+    }
+
+    /// Whole minutes credited for `cycles` rounds, never less than one.
+    pub fn planned_minutes(&self, cycles: i64) -> i64 {
+        ((self.cycle_seconds() * cycles as f64) / 60.0)
+            .ceil()
+            .max(1.0) as i64
+    }
+
+    /// e.g. "Inhale 4s · Hold 7s · Exhale 8s"
+    pub fn summary(&self) -> String {
+        self.phases
+            .iter()
+            .map(|p| format!("{} {}s", p.label, p.seconds))
+            .collect::<Vec<_>>()
+            .join(" · ")
+    }
+}
+
+/// Look up a pattern by key among the built-ins and the user's own.
+pub fn find_pattern(key: &str, custom: &[BreathingPattern]) -> Option<BreathingPattern> {
+    builtin_patterns()
+        .into_iter()
+        .chain(custom.iter().cloned())
+        .find(|p| p.key == key)
+}
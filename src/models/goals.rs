@@ -0,0 +1,293 @@
+use chrono::{Datelike, Duration, NaiveDate};
+
+/// The targets a user can set. Daily goals run midnight to midnight, weekly
+/// ones Monday to Sunday, both in the user's timezone.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GoalKind {
+    DailyMinutes,
+    WeeklySessions,
+    WeeklyJournal,
+}
+
+pub const GOAL_KINDS: [GoalKind; 3] = [
+    GoalKind::DailyMinutes,
+    GoalKind::WeeklySessions,
+    GoalKind::WeeklyJournal,
+];
+
+impl GoalKind {
+    /// Stored in the database and used as the form field name.
+    pub fn key(self) -> &'static str {
+        match self {
+            GoalKind::DailyMinutes => "daily_minutes",
+            GoalKind::WeeklySessions => "weekly_sessions",
+            GoalKind::WeeklyJournal => "weekly_journal",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        GOAL_KINDS.into_iter().find(|k| k.key() == key)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            GoalKind::DailyMinutes => "Mindful minutes per day",
+            GoalKind::WeeklySessions => "Sessions per week",
+            GoalKind::WeeklyJournal => "Journal entries per week",
+        }
+    }
+
+    /// What the numbers count, e.g. "12 / 20 min".
+    pub fn unit(self) -> &'static str {
+        match self {
+            GoalKind::DailyMinutes => "min",
+            GoalKind::WeeklySessions => "sessions",
+            GoalKind::WeeklyJournal => "entries",
+        }
+    }
+
+    pub fn max_target(self) -> i64 {
+        match self {
+            GoalKind::DailyMinutes => 240,
+            GoalKind::WeeklySessions => 50,
+            GoalKind::WeeklyJournal => 14,
+        }
+    }
+
+    pub fn is_daily(self) -> bool {
+        self == GoalKind::DailyMinutes
+    }
+
+    /// "today" or "this week".
+    pub fn current_period_name(self) -> &'static str {
+        if self.is_daily() {
+            "today"
+        } else {
+            "this week"
+        }
+    }
+
+    /// First day of the period containing `date`.
+    pub fn period_start(self, date: NaiveDate) -> NaiveDate {
+        if self.is_daily() {
+            date
+        } else {
+            date - Duration::days(date.weekday().num_days_from_monday() as i64)
+        }
+    }
+
+    /// First day of the period after the one starting on `start`.
+    pub fn next_period(self, start: NaiveDate) -> NaiveDate {
+        start + Duration::days(if self.is_daily() { 1 } else { 7 })
+    }
+
+    /// First day of the period before the one starting on `start`.
+    pub fn previous_period(self, start: NaiveDate) -> NaiveDate {
+        start - Duration::days(if self.is_daily() { 1 } else { 7 })
+    }
+
+    /// First days of each period from the one containing `first` through the
+    /// one containing `last`.
+    pub fn period_starts(self, first: NaiveDate, last: NaiveDate) -> Vec<NaiveDate> {
+        let last = self.period_start(last);
+        let mut start = self.period_start(first);
+        let mut starts = Vec::new();
+        while start <= last {
+            starts.push(start);
+            start = self.next_period(start);
+        }
+        starts
+    }
+
+    /// Progress in the period starting on `start`. Activity is given by the
+    /// local day it happened on: `sessions` as (day, is a journal session,
+    /// minutes) and journal `entries` as days.
+    pub fn progress(
+        self,
+        start: NaiveDate,
+        sessions: &[(NaiveDate, bool, i64)],
+        entries: &[NaiveDate],
+    ) -> i64 {
+        let end = self.next_period(start);
+        let in_period = |d: &NaiveDate| *d >= start && *d < end;
+        match self {
+            GoalKind::DailyMinutes => sessions
+                .iter()
+                .filter(|(d, _, _)| in_period(d))
+                .map(|&(_, _, minutes)| minutes)
+                .sum(),
+            GoalKind::WeeklySessions => sessions
+                .iter()
+                .filter(|(d, journal, _)| !journal && in_period(d))
+                .count() as i64,
+            GoalKind::WeeklyJournal => entries.iter().filter(|d| in_period(d)).count() as i64,
+        }
+    }
+}
+
+/// A goal the user has set. `set_at` is when it was first set.
+#[derive(Clone, Debug, sqlx::FromRow)]
+pub struct Goal {
+    pub goal: String,
+    pub target: i64,
+    pub set_at: String,
+}
+
+impl Goal {
+    pub fn kind(&self) -> Option<GoalKind> {
+        GoalKind::from_key(&self.goal)
+    }
+}
+
+/// How far the user got towards a goal in one day or week. `target` is the
+/// goal as it stood then, so changing a goal doesn't rewrite history.
+#[derive(Clone, Debug, sqlx::FromRow)]
+pub struct GoalPeriod {
+    pub goal: String,
+    /// `YYYY-MM-DD`.
+    pub period_start: String,
+    pub target: i64,
+    pub achieved: i64,
+}
+
+impl GoalPeriod {
+    /// A period with nothing recorded in it yet.
+    pub fn empty(kind: GoalKind, start: NaiveDate, target: i64) -> Self {
+        GoalPeriod {
+            goal: kind.key().to_string(),
+            period_start: start.to_string(),
+            target,
+            achieved: 0,
+        }
+    }
+
+    pub fn kind(&self) -> Option<GoalKind> {
+        GoalKind::from_key(&self.goal)
+    }
+
+    pub fn met(&self) -> bool {
+        self.achieved >= self.target
+    }
+
+    /// Progress from 0 to 1.
+    pub fn fraction(&self) -> f64 {
+        if self.target <= 0 {
+            return 1.0;
+        }
+        (self.achieved as f64 / self.target as f64).clamp(0.0, 1.0)
+    }
+
+    pub fn start_date(&self) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(&self.period_start, "%Y-%m-%d").ok()
+    }
+}
+
+/// Progress on each goal this period, and how the last completed period went.
+#[derive(Clone, Debug, Default)]
+pub struct GoalOverview {
+    pub current: Vec<GoalPeriod>,
+    pub previous: Vec<GoalPeriod>,
+}
+
+impl GoalOverview {
+    pub fn has_goals(&self) -> bool {
+        !self.current.is_empty()
+    }
+
+    pub fn all_current_met(&self) -> bool {
+        self.has_goals() && self.current.iter().all(GoalPeriod::met)
+    }
+
+    pub fn all_previous_met(&self) -> bool {
+        !self.previous.is_empty() && self.previous.iter().all(GoalPeriod::met)
+    }
+
+    /// A goal missed last period, weekly ones first as they matter more.
+    pub fn missed_previous(&self) -> Option<&GoalPeriod> {
+        self.previous
+            .iter()
+            .filter(|p| !p.met())
+            .max_by_key(|p| p.kind().is_some_and(|k| !k.is_daily()))
+    }
+}
+
+/// Parse a target from the goals form. Blank means no goal.
+pub fn parse_target(kind: GoalKind, value: &str) -> Result<Option<i64>, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    let max = kind.max_target();
+    match value.parse::<i64>() {
+        Ok(n) if (1..=max).contains(&n) => Ok(Some(n)),
+        _ => Err(format!(
+            "{} must be a whole number from 1 to {max}, or blank for no goal.",
+            kind.label()
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn weeks_run_monday_to_sunday_across_month_and_year_ends() {
+        let weekly = GoalKind::WeeklySessions;
+        assert_eq!(weekly.period_start(day(2026, 3, 2)), day(2026, 3, 2));
+        assert_eq!(weekly.period_start(day(2026, 3, 8)), day(2026, 3, 2));
+        assert_eq!(weekly.period_start(day(2026, 3, 1)), day(2026, 2, 23));
+        assert_eq!(weekly.period_start(day(2026, 1, 1)), day(2025, 12, 29));
+        assert_eq!(weekly.next_period(day(2025, 12, 29)), day(2026, 1, 5));
+        assert_eq!(weekly.previous_period(day(2026, 3, 2)), day(2026, 2, 23));
+    }
+
+    #[test]
+    fn period_starts_cover_both_ends() {
+        assert_eq!(
+            GoalKind::DailyMinutes.period_starts(day(2026, 2, 27), day(2026, 3, 1)),
+            [day(2026, 2, 27), day(2026, 2, 28), day(2026, 3, 1)]
+        );
+        assert_eq!(
+            GoalKind::WeeklyJournal.period_starts(day(2025, 12, 31), day(2026, 1, 5)),
+            [day(2025, 12, 29), day(2026, 1, 5)]
+        );
+        assert!(
+            GoalKind::DailyMinutes
+                .period_starts(day(2026, 3, 2), day(2026, 3, 1))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn progress_counts_only_the_period() {
+        let sessions = [
+            (day(2026, 3, 1), false, 10),
+            (day(2026, 3, 2), false, 5),
+            (day(2026, 3, 2), true, 5),
+            (day(2026, 3, 8), false, 20),
+            (day(2026, 3, 9), false, 30),
+        ];
+        let entries = [day(2026, 3, 2), day(2026, 3, 8), day(2026, 3, 9)];
+
+        let daily = GoalKind::DailyMinutes;
+        assert_eq!(daily.progress(day(2026, 3, 1), &sessions, &entries), 10);
+        assert_eq!(daily.progress(day(2026, 3, 2), &sessions, &entries), 10);
+        assert_eq!(daily.progress(day(2026, 3, 3), &sessions, &entries), 0);
+
+        // Journal sessions count towards minutes but not sessions
+        let week = day(2026, 3, 2);
+        assert_eq!(
+            GoalKind::WeeklySessions.progress(week, &sessions, &entries),
+            2
+        );
+        assert_eq!(
+            GoalKind::WeeklyJournal.progress(week, &sessions, &entries),
+            2
+        );
+    }
+}
@@ -0,0 +1,55 @@
+use chrono::NaiveDate;
+
+/// Entries shown per page of `/history`.
+pub const PAGE_SIZE: i64 = 20;
+
+/// Type filter options for `/history` as (value, label). `journal` matches
+/// journal entries rather than the sessions they credit.
+pub const HISTORY_TYPES: &[(&str, &str)] = &[
+    ("breathing", "Breathing"),
+    ("meditation", "Meditation"),
+    ("journal", "Journal"),
+];
+
+/// One row of the history timeline: a mindful session or a journal entry.
+#[derive(Clone, Debug, sqlx::FromRow)]
+pub struct HistoryItem {
+    pub id: String,
+    /// `breathing`, `meditation` or `journal`.
+    pub item_type: String,
+    pub duration_min: i64,
+    pub pattern: Option<String>,
+    pub cycles: Option<i64>,
+    pub mood: Option<i64>,
+    pub note: Option<String>,
+    pub occurred_at: String,
+}
+
+impl HistoryItem {
+    pub fn is_journal(&self) -> bool {
+        self.item_type == "journal"
+    }
+}
+
+/// Validated `/history` filters. Dates are inclusive.
+#[derive(Clone, Debug, Default)]
+pub struct HistoryFilter {
+    pub item_type: Option<String>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    /// 1-based.
+    pub page: i64,
+}
+
+/// A page of history plus how many items match the filter overall.
+#[derive(Clone, Debug, Default)]
+pub struct HistoryPage {
+    pub items: Vec<HistoryItem>,
+    pub total: i64,
+}
+
+impl HistoryPage {
+    pub fn page_count(&self) -> i64 {
+        ((self.total + PAGE_SIZE - 1) / PAGE_SIZE).max(1)
+    }
+}
@@ -0,0 +1,200 @@
+use chrono::{Datelike, Duration, NaiveDate};
+
+/// Periods offered on `/insights`, in days.
+pub const INSIGHT_PERIODS: &[i64] = &[7, 30, 90];
+pub const DEFAULT_PERIOD: i64 = 30;
+
+/// One day's journal mood, in the user's timezone.
+#[derive(Clone, Debug)]
+pub struct MoodDay {
+    pub day: NaiveDate,
+    /// Average of the day's entries.
+    pub mood: f64,
+    pub entries: i64,
+    /// Breathing and meditation sessions completed that day.
+    pub sessions: i64,
+}
+
+/// Average daily mood over a set of days, and how many days it covers.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MoodAverage {
+    pub mood: Option<f64>,
+    pub days: i64,
+}
+
+impl MoodAverage {
+    fn of(moods: impl Iterator<Item = f64>) -> Self {
+        let (sum, days) = moods.fold((0.0, 0), |(sum, n), m| (sum + m, n + 1));
+        MoodAverage {
+            mood: (days > 0).then(|| sum / days as f64),
+            days,
+        }
+    }
+}
+
+/// Mood trends for `/insights`. Every day counts once however many entries
+/// it has, so one busy day doesn't outweigh the rest.
+#[derive(Clone, Debug)]
+pub struct MoodInsights {
+    /// Length of the charted period in days, ending today.
+    pub days: i64,
+    pub start: NaiveDate,
+    /// Average mood for each day from `start`, `None` where nothing was logged.
+    pub daily: Vec<Option<f64>>,
+    pub entries: i64,
+    /// Average for each of [`INSIGHT_PERIODS`], whichever is charted.
+    pub periods: Vec<(i64, MoodAverage)>,
+    /// Monday first.
+    pub by_weekday: [MoodAverage; 7],
+    /// Days with at least one breathing or meditation session.
+    pub with_practice: MoodAverage,
+    pub without_practice: MoodAverage,
+}
+
+impl MoodInsights {
+    /// Build insights for the `days` up to `today` from mood rows covering at
+    /// least the longest of [`INSIGHT_PERIODS`].
+    pub fn new(days: i64, today: NaiveDate, rows: &[MoodDay]) -> Self {
+        let dated: Vec<(NaiveDate, &MoodDay)> = rows.iter().map(|r| (r.day, r)).collect();
+        let since = |days: i64| today - Duration::days(days - 1);
+
+        let start = since(days);
+        let in_period: Vec<(NaiveDate, &MoodDay)> = dated
+            .iter()
+            .copied()
+            .filter(|(date, _)| *date >= start && *date <= today)
+            .collect();
+
+        let mut daily = vec![None; days as usize];
+        for (date, row) in &in_period {
+            daily[(*date - start).num_days() as usize] = Some(row.mood);
+        }
+
+        let periods = INSIGHT_PERIODS
+            .iter()
+            .map(|&p| {
+                let from = since(p);
+                let moods = dated
+                    .iter()
+                    .filter(|(date, _)| *date >= from && *date <= today)
+                    .map(|(_, r)| r.mood);
+                (p, MoodAverage::of(moods))
+            })
+            .collect();
+
+        let by_weekday = std::array::from_fn(|weekday| {
+            MoodAverage::of(
+                in_period
+                    .iter()
+                    .filter(|(date, _)| date.weekday().num_days_from_monday() as usize == weekday)
+                    .map(|(_, r)| r.mood),
+            )
+        });
+
+        let practiced = |yes: bool| {
+            MoodAverage::of(
+                in_period
+                    .iter()
+                    .filter(|(_, r)| (r.sessions > 0) == yes)
+                    .map(|(_, r)| r.mood),
+            )
+        };
+
+        MoodInsights {
+            days,
+            start,
+            daily,
+            entries: in_period.iter().map(|(_, r)| r.entries).sum(),
+            periods,
+            by_weekday,
+            with_practice: practiced(true),
+            without_practice: practiced(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(date: &str, mood: f64, entries: i64, sessions: i64) -> MoodDay {
+        MoodDay {
+            day: date.parse().unwrap(),
+            mood,
+            entries,
+            sessions,
+        }
+    }
+
+    fn today() -> NaiveDate {
+        // A Sunday
+        NaiveDate::from_ymd_opt(2026, 3, 15).unwrap()
+    }
+
+    #[test]
+    fn each_day_counts_once_whatever_its_entries() {
+        let rows = [day("2026-03-14", 1.0, 5, 0), day("2026-03-15", 5.0, 1, 0)];
+        let insights = MoodInsights::new(7, today(), &rows);
+        assert_eq!(insights.entries, 6);
+        let week = insights.periods.iter().find(|(p, _)| *p == 7).unwrap().1;
+        assert_eq!((week.mood, week.days), (Some(3.0), 2));
+    }
+
+    #[test]
+    fn period_starts_days_minus_one_before_today() {
+        let rows = [
+            day("2026-03-08", 1.0, 1, 0),
+            day("2026-03-09", 2.0, 1, 0),
+            day("2026-03-15", 4.0, 1, 0),
+            day("2026-03-16", 5.0, 1, 0),
+        ];
+        let insights = MoodInsights::new(7, today(), &rows);
+        assert_eq!(insights.start, "2026-03-09".parse::<NaiveDate>().unwrap());
+        assert_eq!(
+            insights.daily,
+            [Some(2.0), None, None, None, None, None, Some(4.0)]
+        );
+        assert_eq!(insights.entries, 2);
+
+        let averages: Vec<(i64, Option<f64>)> =
+            insights.periods.iter().map(|(p, a)| (*p, a.mood)).collect();
+        assert_eq!(
+            averages,
+            [(7, Some(3.0)), (30, Some(7.0 / 3.0)), (90, Some(7.0 / 3.0))]
+        );
+    }
+
+    #[test]
+    fn weekdays_are_bucketed_monday_first() {
+        let rows = [
+            day("2026-03-09", 2.0, 1, 0), // Monday
+            day("2026-03-02", 4.0, 1, 0), // Monday
+            day("2026-03-15", 5.0, 1, 0), // Sunday
+        ];
+        let insights = MoodInsights::new(30, today(), &rows);
+        assert_eq!(insights.by_weekday[0].mood, Some(3.0));
+        assert_eq!(insights.by_weekday[0].days, 2);
+        assert_eq!(insights.by_weekday[6].mood, Some(5.0));
+        assert!(insights.by_weekday[1..6].iter().all(|d| d.mood.is_none()));
+    }
+
+    #[test]
+    fn practice_days_are_compared_with_the_rest() {
+        let rows = [
+            day("2026-03-12", 4.0, 1, 2),
+            day("2026-03-13", 5.0, 1, 1),
+            day("2026-03-14", 2.0, 2, 0),
+            // Outside the charted week
+            day("2026-03-01", 1.0, 1, 3),
+        ];
+        let insights = MoodInsights::new(7, today(), &rows);
+        assert_eq!(insights.with_practice.mood, Some(4.5));
+        assert_eq!(insights.with_practice.days, 2);
+        assert_eq!(insights.without_practice.mood, Some(2.0));
+        assert_eq!(insights.without_practice.days, 1);
+
+        let empty = MoodInsights::new(7, today(), &[]);
+        assert_eq!(empty.with_practice.mood, None);
+        assert_eq!(empty.daily, [None; 7]);
+    }
+}
@@ -0,0 +1,429 @@
+use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+use super::history::PAGE_SIZE;
+
+/// Most tags one entry can carry, and the longest a tag name can be.
+pub const MAX_TAGS_PER_ENTRY: usize = 8;
+pub const MAX_TAG_LEN: usize = 24;
+
+/// Wrap the matched words in search snippets. Private-use characters, so they
+/// survive HTML escaping and can be swapped for `<mark>` afterwards.
+pub const HIGHLIGHT_START: char = '\u{E000}';
+pub const HIGHLIGHT_END: char = '\u{E001}';
+
+/// A row of `journal_entries`.
+#[derive(Clone, Debug, sqlx::FromRow)]
+pub struct JournalEntry {
+    pub id: String,
+    pub mood: i64,
+    pub note: String,
+    pub created_at: String,
+    pub updated_at: Option<String>,
+    /// Text of the reflection prompt the entry answered, if any.
+    pub prompt: Option<String>,
+    /// Comma-separated tag names.
+    #[sqlx(default)]
+    pub tag_list: Option<String>,
+    /// Excerpt around the search match, with matches between
+    /// [`HIGHLIGHT_START`] and [`HIGHLIGHT_END`]. Only set for searches.
+    #[sqlx(default)]
+    pub snippet: Option<String>,
+}
+
+impl JournalEntry {
+    pub fn tags(&self) -> Vec<&str> {
+        self.tag_list
+            .as_deref()
+            .map(|list| list.split(',').collect())
+            .unwrap_or_default()
+    }
+}
+
+/// A page of journal entries plus how many match the filter overall.
+#[derive(Clone, Debug, Default)]
+pub struct JournalPage {
+    pub entries: Vec<JournalEntry>,
+    pub total: i64,
+}
+
+impl JournalPage {
+    pub fn page_count(&self) -> i64 {
+        ((self.total + PAGE_SIZE - 1) / PAGE_SIZE).max(1)
+    }
+}
+
+/// Validated journal search and filters. Moods and dates are inclusive.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct JournalFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub q: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mood_min: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mood_max: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<NaiveDate>,
+}
+
+impl JournalFilter {
+    pub fn is_active(&self) -> bool {
+        self.q.is_some()
+            || self.tag.is_some()
+            || self.mood_min.is_some()
+            || self.mood_max.is_some()
+            || self.from.is_some()
+            || self.to.is_some()
+    }
+
+    /// The search box as lowercase words. An entry matches when every word
+    /// starts one of its words, so "sister" also finds "sisters".
+    pub fn search_terms(&self) -> Vec<String> {
+        self.q
+            .as_deref()
+            .map(|q| {
+                word_spans(q)
+                    .into_iter()
+                    .map(|(start, end)| q[start..end].to_lowercase())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Query string for `page` of these results, for pagination links.
+    pub fn query_string(&self, page: i64) -> String {
+        let filters = serde_urlencoded::to_string(self).unwrap_or_default();
+        if filters.is_empty() {
+            format!("page={page}")
+        } else {
+            format!("{filters}&page={page}")
+        }
+    }
+}
+
+/// Words shown in a search snippet.
+const SNIPPET_WORDS: usize = 16;
+
+/// Longest word prefix kept in the search index. Longer search words are
+/// looked up by this prefix and confirmed against the decrypted note.
+pub const MAX_INDEXED_PREFIX: usize = 20;
+
+/// Byte ranges of the alphanumeric runs in `text`.
+fn word_spans(text: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices() {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                spans.push((s, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        spans.push((s, text.len()));
+    }
+    spans
+}
+
+/// Match a note against [`JournalFilter::search_terms`]. Returns how many
+/// words matched, for ranking, and an excerpt around the first match with
+/// matches between [`HIGHLIGHT_START`] and [`HIGHLIGHT_END`]. `None` if any
+/// term is missing.
+pub fn search_snippet(note: &str, terms: &[String]) -> Option<(usize, String)> {
+    let words = word_spans(note);
+    let lowered: Vec<String> = words
+        .iter()
+        .map(|&(start, end)| note[start..end].to_lowercase())
+        .collect();
+    let matches = |word: &str| terms.iter().any(|t| word.starts_with(t.as_str()));
+
+    if !terms
+        .iter()
+        .all(|t| lowered.iter().any(|w| w.starts_with(t.as_str())))
+    {
+        return None;
+    }
+
+    let hits: Vec<bool> = lowered.iter().map(|w| matches(w)).collect();
+    let score = hits.iter().filter(|&&h| h).count();
+    let first = hits.iter().position(|&h| h)?;
+
+    // A little context before the first match, then fill the window
+    let end = (first.saturating_sub(SNIPPET_WORDS / 4) + SNIPPET_WORDS).min(words.len());
+    let start = end.saturating_sub(SNIPPET_WORDS);
+
+    let mut snippet = String::new();
+    if start > 0 {
+        snippet.push('…');
+    }
+    let mut cursor = words[start].0;
+    for (&(word_start, word_end), &hit) in words[start..end].iter().zip(&hits[start..end]) {
+        snippet.push_str(&note[cursor..word_start]);
+        if hit {
+            snippet.push(HIGHLIGHT_START);
+            snippet.push_str(&note[word_start..word_end]);
+            snippet.push(HIGHLIGHT_END);
+        } else {
+            snippet.push_str(&note[word_start..word_end]);
+        }
+        cursor = word_end;
+    }
+    if end < words.len() {
+        snippet.push('…');
+    } else {
+        snippet.push_str(note[cursor..].trim_end());
+    }
+    Some((score, snippet))
+}
+
+/// Everything a search word could be for `note` to match: each distinct
+/// lowercase prefix of its words, up to [`MAX_INDEXED_PREFIX`] characters.
+pub fn index_words(note: &str) -> BTreeSet<String> {
+    let mut words = BTreeSet::new();
+    for (start, end) in word_spans(note) {
+        let word = note[start..end].to_lowercase();
+        for (i, _) in word.char_indices().skip(1).take(MAX_INDEXED_PREFIX - 1) {
+            words.insert(word[..i].to_string());
+        }
+        words.insert(indexed_prefix(&word).to_string());
+    }
+    words
+}
+
+/// The part of a search word the index knows about.
+pub fn indexed_prefix(word: &str) -> &str {
+    match word.char_indices().nth(MAX_INDEXED_PREFIX) {
+        Some((i, _)) => &word[..i],
+        None => word,
+    }
+}
+
+/// Normalise a comma-separated tag field: lowercase, single spaces, no
+/// duplicates. Returns a message if any tag is unusable.
+pub fn parse_tags(input: &str) -> Result<Vec<String>, String> {
+    let mut tags: Vec<String> = Vec::new();
+    for raw in input.split(',') {
+        let tag = raw
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+        if tag.is_empty() || tags.contains(&tag) {
+            continue;
+        }
+        if tag.chars().count() > MAX_TAG_LEN {
+            return Err(format!("Tags can be at most {MAX_TAG_LEN} characters."));
+        }
+        if !tag
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_'))
+        {
+            return Err("Tags can only contain letters, numbers, spaces, - and _.".to_string());
+        }
+        tags.push(tag);
+    }
+    if tags.len() > MAX_TAGS_PER_ENTRY {
+        return Err(format!(
+            "An entry can have at most {MAX_TAGS_PER_ENTRY} tags."
+        ));
+    }
+    Ok(tags)
+}
+
+// ── Reflection prompts ─────────────────────────────────────────────────────────
+
+/// A question offered above the journal note. `key` is what the form sends;
+/// the text itself is stored with the entry so rewording a prompt later
+/// doesn't change old entries.
+#[derive(Clone, Copy, Debug)]
+pub struct JournalPrompt {
+    pub key: &'static str,
+    pub category: &'static str,
+    pub text: &'static str,
+}
+
+const fn prompt(key: &'static str, category: &'static str, text: &'static str) -> JournalPrompt {
+    JournalPrompt {
+        key,
+        category,
+        text,
+    }
+}
+
+/// Interleaved by category so consecutive days get some variety.
+pub const PROMPTS: &[JournalPrompt] = &[
+    prompt(
+        "gratitude-small",
+        "Gratitude",
+        "What is one small thing that went right today?",
+    ),
+    prompt(
+        "reframe-thought",
+        "Reframing",
+        "What thought has been weighing on you? What would you tell a friend who had it?",
+    ),
+    prompt(
+        "body-checkin",
+        "Check-in",
+        "Where do you notice tension in your body right now, and what might it be telling you?",
+    ),
+    prompt(
+        "gratitude-person",
+        "Gratitude",
+        "Who made your life a little easier recently, and how?",
+    ),
+    prompt(
+        "reframe-evidence",
+        "Reframing",
+        "Pick a worry from today. What evidence supports it, and what evidence doesn't?",
+    ),
+    prompt(
+        "compassion",
+        "Self-compassion",
+        "What would you say to yourself if you were being as kind as you are to others?",
+    ),
+    prompt(
+        "gratitude-comfort",
+        "Gratitude",
+        "What is something you often take for granted that you're glad to have?",
+    ),
+    prompt(
+        "reframe-alternative",
+        "Reframing",
+        "Think of something that upset you. What is another way to see what happened?",
+    ),
+    prompt(
+        "energy",
+        "Check-in",
+        "What gave you energy today, and what drained it?",
+    ),
+    prompt(
+        "gratitude-growth",
+        "Gratitude",
+        "What challenge from the past are you now grateful to have been through?",
+    ),
+    prompt(
+        "reframe-control",
+        "Reframing",
+        "What part of today's problem is in your control, and what part isn't?",
+    ),
+    prompt(
+        "tomorrow",
+        "Intention",
+        "What is one kind thing you can do for yourself tomorrow?",
+    ),
+];
+
+pub fn find_prompt(key: &str) -> Option<&'static JournalPrompt> {
+    PROMPTS.iter().find(|p| p.key == key)
+}
+
+/// The prompt for a given day; everyone sees the same one.
+pub fn prompt_for_day(date: NaiveDate) -> &'static JournalPrompt {
+    let day = date.num_days_from_ce() as usize;
+    &PROMPTS[day % PROMPTS.len()]
+}
+
+/// The prompt after `current`, wrapping round, for "show me another".
+pub fn next_prompt(current: &JournalPrompt) -> &'static JournalPrompt {
+    let i = PROMPTS
+        .iter()
+        .position(|p| p.key == current.key)
+        .unwrap_or(0);
+    &PROMPTS[(i + 1) % PROMPTS.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terms(q: &str) -> Vec<String> {
+        JournalFilter {
+            q: Some(q.to_string()),
+            ..Default::default()
+        }
+        .search_terms()
+    }
+
+    #[test]
+    fn index_holds_every_prefix_a_search_can_use() {
+        let words = index_words("Called my Sister.");
+        for prefix in ["c", "cal", "called", "my", "s", "sis", "sister"] {
+            assert!(words.contains(prefix), "missing {prefix}");
+        }
+        assert!(!words.contains("sisters"));
+        assert!(!words.contains("Sister"));
+    }
+
+    #[test]
+    fn long_words_are_indexed_by_their_leading_characters() {
+        let word = "supercalifragilisticexpialidocious";
+        let words = index_words(word);
+        assert_eq!(
+            words.iter().map(|w| w.chars().count()).max(),
+            Some(MAX_INDEXED_PREFIX)
+        );
+        for term in terms(word) {
+            assert!(words.contains(indexed_prefix(&term)));
+        }
+    }
+
+    #[test]
+    fn indexed_prefix_counts_characters_not_bytes() {
+        let word = "é".repeat(MAX_INDEXED_PREFIX + 5);
+        assert_eq!(indexed_prefix(&word).chars().count(), MAX_INDEXED_PREFIX);
+        assert_eq!(indexed_prefix("calm"), "calm");
+    }
+
+    #[test]
+    fn every_term_that_matches_a_note_is_in_its_index() {
+        let note = "Long day at work. My sisters visited in the evening.";
+        let words = index_words(note);
+        for q in ["sister", "work", "even", "Visit"] {
+            let terms = terms(q);
+            assert!(search_snippet(note, &terms).is_some());
+            assert!(terms.iter().all(|t| words.contains(indexed_prefix(t))));
+        }
+    }
+
+    #[test]
+    fn tags_are_lowercased_and_deduplicated() {
+        assert_eq!(
+            parse_tags(" Work ,work,  Family   Time ,, WORK, family time").unwrap(),
+            ["work", "family time"]
+        );
+        assert!(parse_tags("").unwrap().is_empty());
+        assert_eq!(
+            parse_tags("Ünïcode_ok, self-care").unwrap(),
+            ["ünïcode_ok", "self-care"]
+        );
+    }
+
+    #[test]
+    fn tags_are_limited_in_length_and_number() {
+        let longest = "é".repeat(MAX_TAG_LEN);
+        assert_eq!(parse_tags(&longest).unwrap(), [longest.as_str()]);
+        assert!(parse_tags(&format!("{longest}e")).is_err());
+
+        let tags: Vec<String> = (0..MAX_TAGS_PER_ENTRY).map(|i| format!("t{i}")).collect();
+        assert_eq!(parse_tags(&tags.join(",")).unwrap(), tags);
+        assert!(parse_tags(&format!("{},extra", tags.join(","))).is_err());
+        // Repeats don't count towards the limit
+        assert!(parse_tags(&format!("{},t0", tags.join(","))).is_ok());
+    }
+
+    #[test]
+    fn tags_reject_punctuation() {
+        for input in ["#work", "a/b", "<b>", "work;play", "tag."] {
+            assert!(parse_tags(input).is_err(), "{input}");
+        }
+    }
+}
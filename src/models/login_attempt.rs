@@ -0,0 +1,99 @@
+use chrono::{NaiveDateTime, Utc};
+
+/// Why a login attempt was recorded. Stored as text in `login_attempts.reason`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttemptOutcome {
+    Success,
+    BadPassword,
+    BadCode,
+    /// A completed password reset, which also lifts an account lockout.
+    PasswordReset,
+    /// Rejected without checking credentials because of a lockout; these
+    /// don't count towards further lockouts.
+    Locked,
+}
+
+impl AttemptOutcome {
+    pub fn succeeded(self) -> bool {
+        matches!(
+            self,
+            AttemptOutcome::Success | AttemptOutcome::PasswordReset
+        )
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AttemptOutcome::Success => "success",
+            AttemptOutcome::BadPassword => "bad_password",
+            AttemptOutcome::BadCode => "bad_code",
+            AttemptOutcome::PasswordReset => "password_reset",
+            AttemptOutcome::Locked => "locked",
+        }
+    }
+}
+
+/// Recent failed logins for one account or one IP address.
+#[derive(Debug, Clone, Default)]
+pub struct FailureStreak {
+    pub failures: i64,
+    pub last_failed_at: Option<NaiveDateTime>,
+}
+
+impl FailureStreak {
+    /// Seconds until another attempt is allowed, if currently locked out.
+    /// The first lockout lasts `base_secs` once `threshold` failures pile up,
+    /// doubling with every further failure up to `max_secs`.
+    pub fn lockout_remaining(&self, threshold: i64, base_secs: i64, max_secs: i64) -> Option<i64> {
+        if threshold <= 0 || self.failures < threshold {
+            return None;
+        }
+        let last = self.last_failed_at?;
+
+        let doublings = (self.failures - threshold).min(20) as u32;
+        let lockout = base_secs.saturating_mul(1i64 << doublings).min(max_secs);
+        let elapsed = (Utc::now().naive_utc() - last).num_seconds();
+
+        (elapsed < lockout).then_some(lockout - elapsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn streak(failures: i64, secs_ago: i64) -> FailureStreak {
+        FailureStreak {
+            failures,
+            last_failed_at: Some(Utc::now().naive_utc() - Duration::seconds(secs_ago)),
+        }
+    }
+
+    #[test]
+    fn locks_once_the_threshold_is_reached() {
+        assert_eq!(streak(4, 0).lockout_remaining(5, 60, 3600), None);
+        assert_eq!(streak(5, 0).lockout_remaining(5, 60, 3600), Some(60));
+    }
+
+    #[test]
+    fn lockout_doubles_up_to_the_cap() {
+        assert_eq!(streak(6, 0).lockout_remaining(5, 60, 3600), Some(120));
+        assert_eq!(streak(8, 0).lockout_remaining(5, 60, 3600), Some(480));
+        assert_eq!(streak(50, 0).lockout_remaining(5, 60, 3600), Some(3600));
+    }
+
+    #[test]
+    fn lockout_expires() {
+        assert_eq!(streak(5, 45).lockout_remaining(5, 60, 3600), Some(15));
+        assert_eq!(streak(5, 60).lockout_remaining(5, 60, 3600), None);
+    }
+
+    #[test]
+    fn zero_threshold_disables_lockout() {
+        assert_eq!(streak(100, 0).lockout_remaining(0, 60, 3600), None);
+        assert_eq!(
+            FailureStreak::default().lockout_remaining(5, 60, 3600),
+            None
+        );
+    }
+}
@@ -0,0 +1,95 @@
+use serde::Serialize;
+
+/// Shortest and longest meditation a user can set, in minutes.
+pub const MIN_MINUTES: i64 = 3;
+pub const MAX_MINUTES: i64 = 60;
+/// Longest preparation countdown before the timer starts, in seconds.
+pub const MAX_PREP_SECS: i64 = 60;
+/// Presets a user may keep.
+pub const MAX_PRESETS: i64 = 10;
+
+/// How a meditation timer runs. An `interval_min` of 0 means no interval
+/// bells, only the closing one.
+#[derive(Clone, Debug, Serialize)]
+pub struct MeditationSettings {
+    pub minutes: i64,
+    pub interval_min: i64,
+    pub prep_secs: i64,
+}
+
+impl Default for MeditationSettings {
+    fn default() -> Self {
+        MeditationSettings {
+            minutes: 10,
+            interval_min: 0,
+            prep_secs: 10,
+        }
+    }
+}
+
+impl MeditationSettings {
+    /// Pull out-of-range values back to the nearest allowed setting.
+    pub fn clamped(minutes: i64, interval_min: i64, prep_secs: i64) -> Self {
+        let minutes = minutes.clamp(MIN_MINUTES, MAX_MINUTES);
+        MeditationSettings {
+            minutes,
+            interval_min: interval_min.clamp(0, minutes - 1),
+            prep_secs: prep_secs.clamp(0, MAX_PREP_SECS),
+        }
+    }
+
+    /// Like [`clamped`](Self::clamped) but reports what was out of range, for
+    /// settings the user is about to save.
+    pub fn validated(minutes: i64, interval_min: i64, prep_secs: i64) -> Result<Self, String> {
+        if !(MIN_MINUTES..=MAX_MINUTES).contains(&minutes) {
+            return Err(format!(
+                "Length must be between {MIN_MINUTES} and {MAX_MINUTES} minutes."
+            ));
+        }
+        if !(0..minutes).contains(&interval_min) {
+            return Err("Interval bells must be shorter than the session.".to_string());
+        }
+        if !(0..=MAX_PREP_SECS).contains(&prep_secs) {
+            return Err(format!(
+                "Preparation must be between 0 and {MAX_PREP_SECS} seconds."
+            ));
+        }
+        Ok(MeditationSettings {
+            minutes,
+            interval_min,
+            prep_secs,
+        })
+    }
+
+    pub fn total_secs(&self) -> i64 {
+        self.minutes * 60
+    }
+
+    /// e.g. "20 min · bell every 5 min · 10s preparation"
+    pub fn summary(&self) -> String {
+        let mut parts = vec![format!("{} min", self.minutes)];
+        if self.interval_min > 0 {
+            parts.push(format!("bell every {} min", self.interval_min));
+        }
+        if self.prep_secs > 0 {
+            parts.push(format!("{}s preparation", self.prep_secs));
+        }
+        parts.join(" · ")
+    }
+}
+
+/// A row of `meditation_presets`.
+#[derive(Clone, Debug, sqlx::FromRow)]
+pub struct MeditationPreset {
+    pub id: String,
+    pub name: String,
+    pub duration_min: i64,
+    pub interval_min: i64,
+    pub prep_secs: i64,
+}
+
+impl MeditationPreset {
+    pub fn settings(&self) -> MeditationSettings {
+        MeditationSettings::clamped(self.duration_min, self.interval_min, self.prep_secs)
+    }
+}
@@ -1,4 +1,13 @@
+pub mod achievements;
+pub mod breathing;
+pub mod goals;
+pub mod history;
+pub mod insights;
+pub mod journal;
+pub mod login_attempt;
+pub mod meditation;
 pub mod newsletter;
 pub mod session;
+pub mod streak;
 pub mod user;
 pub mod video;
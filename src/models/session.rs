@@ -1,9 +1,27 @@
+use chrono::NaiveDate;
+
+use crate::models::history::HISTORY_TYPES;
+
 #[derive(Debug, Clone, Default)]
 pub struct DashboardStats {
     pub sessions_today: i64,
     pub streak: i64,
+    pub longest_streak: i64,
+    /// Streak freezes banked for missed days.
+    pub freezes: i64,
     pub total_minutes: i64,
 }
 
-/// Minutes of activity for each of the last 7 days, index 0 = 6 days ago, index 6 = today.
-pub type WeeklyMinutes = [i64; 7];
+/// One day's practice in the user's timezone.
+#[derive(Debug, Clone)]
+pub struct DayActivity {
+    pub day: NaiveDate,
+    /// All sessions, including the minutes journal entries credit.
+    pub minutes: i64,
+    /// `minutes` split by session type, in [`HISTORY_TYPES`] order.
+    pub type_minutes: [i64; HISTORY_TYPES.len()],
+    /// Breathing and meditation sessions.
+    pub sessions: i64,
+    /// Average journal mood, if anything was logged.
+    pub mood: Option<f64>,
+}
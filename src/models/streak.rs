@@ -0,0 +1,315 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use chrono_tz::Tz;
+
+/// Consecutive active days that earn a streak freeze.
+pub const FREEZE_EARN_DAYS: i64 = 7;
+/// Most freezes that can be banked at once.
+pub const MAX_FREEZES: i64 = 2;
+
+/// What happened on a day, as far as the streak is concerned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StreakDay {
+    /// At least one session.
+    Active,
+    /// No session, but a banked freeze kept the streak alive.
+    Frozen,
+    /// No session and no freeze; any streak ended here.
+    Missed,
+    /// Today, with no session yet. Doesn't break the streak until it's over.
+    Pending,
+}
+
+/// Running totals of a streak replay at the end of some day.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StreakTally {
+    /// Active days in the current run; frozen days bridge it but don't count.
+    pub current: i64,
+    pub longest: i64,
+    pub freezes: i64,
+    /// Freezes spent so far.
+    pub frozen_days: i64,
+}
+
+impl StreakTally {
+    /// Move on to `date`, which had a session if `active`.
+    fn advance(&mut self, active: bool, date: NaiveDate, today: NaiveDate) -> StreakDay {
+        let day = if active {
+            self.current += 1;
+            if self.current % FREEZE_EARN_DAYS == 0 {
+                self.freezes = (self.freezes + 1).min(MAX_FREEZES);
+            }
+            StreakDay::Active
+        } else if date == today {
+            StreakDay::Pending
+        } else if self.current > 0 && self.freezes > 0 {
+            self.freezes -= 1;
+            self.frozen_days += 1;
+            StreakDay::Frozen
+        } else {
+            self.current = 0;
+            StreakDay::Missed
+        };
+        self.longest = self.longest.max(self.current);
+        day
+    }
+}
+
+/// The streak replayed day by day from the first session to today. Freezes
+/// are earned and spent as the days go by, so the result only depends on
+/// which days had sessions.
+#[derive(Clone, Debug)]
+pub struct StreakHistory {
+    /// First day replayed: the first day with a session, or today if there
+    /// are none.
+    pub first: NaiveDate,
+    /// One entry per day from `first` to today.
+    pub days: Vec<StreakDay>,
+    /// Active days in the current run; frozen days bridge it but don't count.
+    pub current: i64,
+    pub longest: i64,
+    pub freezes: i64,
+    /// The tally at the end of yesterday. Only sessions added to past days
+    /// can change it, so a later replay can start from here.
+    pub settled: StreakTally,
+}
+
+impl StreakHistory {
+    /// `dates` are the days with sessions in the user's timezone, in any order.
+    pub fn new(dates: &[NaiveDate], today: NaiveDate) -> Self {
+        let first = dates
+            .iter()
+            .copied()
+            .filter(|d| *d <= today)
+            .min()
+            .unwrap_or(today);
+        Self::resume(StreakTally::default(), first, dates, today)
+    }
+
+    /// Carry on a replay that had reached `tally` by the day before `first`.
+    /// `dates` before `first` are ignored.
+    pub fn resume(
+        mut tally: StreakTally,
+        first: NaiveDate,
+        dates: &[NaiveDate],
+        today: NaiveDate,
+    ) -> Self {
+        let mut active: Vec<NaiveDate> = dates
+            .iter()
+            .copied()
+            .filter(|d| (first..=today).contains(d))
+            .collect();
+        active.sort_unstable();
+        active.dedup();
+
+        let mut days = Vec::with_capacity(((today - first).num_days() + 1).max(0) as usize);
+        let mut settled = tally;
+        let mut next_active = active.iter().peekable();
+
+        let mut date = first;
+        while date <= today {
+            if date == today {
+                settled = tally;
+            }
+            let active = next_active.next_if_eq(&&date).is_some();
+            days.push(tally.advance(active, date, today));
+            date += Duration::days(1);
+        }
+
+        StreakHistory {
+            first,
+            days,
+            current: tally.current,
+            longest: tally.longest,
+            freezes: tally.freezes,
+            settled,
+        }
+    }
+
+    /// The tally including today. Today is never frozen, so the frozen days
+    /// are yesterday's.
+    pub fn tally(&self) -> StreakTally {
+        StreakTally {
+            current: self.current,
+            longest: self.longest,
+            freezes: self.freezes,
+            ..self.settled
+        }
+    }
+
+    /// [`StreakHistory::new`] for sessions completed at `times`, each counted
+    /// on its calendar day in `tz`.
+    pub fn from_times(times: &[DateTime<Utc>], tz: Tz, today: NaiveDate) -> Self {
+        let dates: Vec<NaiveDate> = times
+            .iter()
+            .map(|t| t.with_timezone(&tz).date_naive())
+            .collect();
+        Self::new(&dates, today)
+    }
+
+    /// The state of `date`, `None` before the first session or after today.
+    pub fn day(&self, date: NaiveDate) -> Option<StreakDay> {
+        let index = usize::try_from((date - self.first).num_days()).ok()?;
+        self.days.get(index).copied()
+    }
+
+    /// Active days until the next freeze is earned, `None` with a full bank.
+    pub fn days_to_next_freeze(&self) -> Option<i64> {
+        (self.freezes < MAX_FREEZES).then(|| FREEZE_EARN_DAYS - self.current % FREEZE_EARN_DAYS)
+    }
+
+    /// Freezes spent over the whole history.
+    pub fn frozen_days(&self) -> usize {
+        self.days
+            .iter()
+            .filter(|d| **d == StreakDay::Frozen)
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use StreakDay::*;
+    use chrono::TimeZone;
+
+    fn day(m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, m, d).unwrap()
+    }
+
+    /// Every day from `from` to `to` inclusive.
+    fn run(from: NaiveDate, to: NaiveDate) -> Vec<NaiveDate> {
+        from.iter_days().take_while(|d| *d <= to).collect()
+    }
+
+    #[test]
+    fn a_freeze_bridges_a_missed_day() {
+        let mut dates = run(day(3, 1), day(3, 7));
+        dates.push(day(3, 9));
+        let history = StreakHistory::new(&dates, day(3, 9));
+
+        assert_eq!(history.day(day(3, 7)), Some(Active));
+        assert_eq!(history.day(day(3, 8)), Some(Frozen));
+        assert_eq!(history.day(day(3, 9)), Some(Active));
+        // The frozen day keeps the run going without adding to it
+        assert_eq!(history.current, 8);
+        assert_eq!(history.longest, 8);
+        assert_eq!(history.freezes, 0);
+        assert_eq!(history.frozen_days(), 1);
+    }
+
+    #[test]
+    fn the_streak_ends_when_no_freeze_is_left() {
+        let mut dates = run(day(3, 1), day(3, 7));
+        dates.push(day(3, 10));
+        let history = StreakHistory::new(&dates, day(3, 10));
+
+        assert_eq!(history.day(day(3, 8)), Some(Frozen));
+        assert_eq!(history.day(day(3, 9)), Some(Missed));
+        assert_eq!(history.current, 1);
+        assert_eq!(history.longest, 7);
+        assert_eq!(history.freezes, 0);
+    }
+
+    #[test]
+    fn freezes_are_earned_every_seventh_day_up_to_the_cap() {
+        let history = StreakHistory::new(&run(day(3, 1), day(3, 6)), day(3, 6));
+        assert_eq!(history.freezes, 0);
+        assert_eq!(history.days_to_next_freeze(), Some(1));
+
+        let history = StreakHistory::new(&run(day(3, 1), day(3, 7)), day(3, 7));
+        assert_eq!(history.freezes, 1);
+        assert_eq!(history.days_to_next_freeze(), Some(7));
+
+        let history = StreakHistory::new(&run(day(3, 1), day(3, 21)), day(3, 21));
+        assert_eq!(history.freezes, MAX_FREEZES);
+        assert_eq!(history.days_to_next_freeze(), None);
+
+        // Without a banked freeze a single gap ends the run
+        let history = StreakHistory::new(&[day(3, 1), day(3, 2), day(3, 4)], day(3, 4));
+        assert_eq!(history.day(day(3, 3)), Some(Missed));
+        assert_eq!(history.current, 1);
+        assert_eq!(history.longest, 2);
+    }
+
+    #[test]
+    fn freezes_never_bridge_more_than_the_banked_days() {
+        // Three weeks earn three freezes, but only two can be banked
+        let mut dates = run(day(3, 1), day(3, 21));
+        dates.push(day(3, 25));
+        let history = StreakHistory::new(&dates, day(3, 25));
+
+        assert_eq!(history.day(day(3, 22)), Some(Frozen));
+        assert_eq!(history.day(day(3, 23)), Some(Frozen));
+        assert_eq!(history.day(day(3, 24)), Some(Missed));
+        assert_eq!(history.current, 1);
+        assert_eq!(history.longest, 21);
+        assert_eq!(history.tally().frozen_days, 2);
+    }
+
+    #[test]
+    fn resuming_from_a_settled_tally_matches_a_full_replay() {
+        let mut dates = run(day(3, 1), day(3, 9));
+        dates.extend(run(day(3, 11), day(3, 18)));
+        dates.extend([day(3, 21), day(3, 22), day(3, 25)]);
+        let today = day(3, 26);
+        let full = StreakHistory::new(&dates, today);
+
+        for resume_on in run(day(3, 2), today) {
+            let earlier = StreakHistory::new(&dates, resume_on);
+            let resumed = StreakHistory::resume(earlier.settled, resume_on, &dates, today);
+            assert_eq!(resumed.tally(), full.tally(), "resumed on {resume_on}");
+            assert_eq!(
+                resumed.days[..],
+                full.days[full.days.len() - resumed.days.len()..]
+            );
+        }
+        assert_eq!(full.tally().frozen_days, full.frozen_days() as i64);
+    }
+
+    #[test]
+    fn today_without_a_session_is_pending() {
+        let dates = run(day(3, 1), day(3, 9));
+        let history = StreakHistory::new(&dates, day(3, 10));
+
+        assert_eq!(history.day(day(3, 10)), Some(Pending));
+        assert_eq!(history.current, 9);
+        assert_eq!(history.longest, 9);
+        // Nothing is spent on a day that isn't over yet
+        assert_eq!(history.freezes, 1);
+        assert_eq!(history.frozen_days(), 0);
+        assert_eq!(history.day(day(3, 11)), None);
+        assert_eq!(history.day(day(2, 28)), None);
+    }
+
+    #[test]
+    fn no_sessions_means_no_streak() {
+        let history = StreakHistory::new(&[], day(3, 10));
+        assert_eq!(history.first, day(3, 10));
+        assert_eq!(history.days, [Pending]);
+        assert_eq!(
+            (history.current, history.longest, history.freezes),
+            (0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn days_follow_the_timezone_across_daylight_saving_changes() {
+        let tz: Tz = "America/New_York".parse().unwrap();
+        let utc = |m, d, h, min| Utc.with_ymd_and_hms(2026, m, d, h, min, 0).unwrap();
+
+        // Clocks go forward on 8 March: local midnight moves from 05:00 to
+        // 04:00 UTC, so 04:30 UTC on the 9th is already the 9th
+        let times = [utc(3, 7, 5, 0), utc(3, 8, 5, 0), utc(3, 9, 4, 30)];
+        let history = StreakHistory::from_times(&times, tz, day(3, 9));
+        assert_eq!(history.days, [Active, Active, Active]);
+        assert_eq!(history.current, 3);
+
+        // Clocks go back on 1 November: 04:30 UTC on the 2nd is still 23:30
+        // on the 1st
+        let times = [utc(10, 31, 4, 30), utc(11, 2, 4, 30)];
+        let history = StreakHistory::from_times(&times, tz, day(11, 2));
+        assert_eq!(history.first, day(10, 31));
+        assert_eq!(history.days, [Active, Active, Pending]);
+        assert_eq!(history.current, 2);
+    }
+}
@@ -1,4 +1,6 @@
 use bcrypt::{DEFAULT_COST, hash, verify};
+use chrono::{NaiveDate, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Serialize, Deserialize, sqlx::FromRow)]
@@ -7,20 +9,86 @@ pub struct User {
     pub name: String,
     pub email: String,
     pub password_hash: String,
+    pub email_verified_at: Option<String>,
+    pub totp_enabled_at: Option<String>,
+    /// IANA timezone name; days, streaks and daily stats follow it.
+    pub timezone: String,
 }
 
 impl User {
-    pub fn new(id: String, name: String, email: String, password: String) -> Result<Self, String> {
-        let password_hash = hash(&password, DEFAULT_COST).map_err(|e| e.to_string())?;
+    pub fn new(
+        id: String,
+        name: String,
+        email: String,
+        password: String,
+        timezone: String,
+    ) -> Result<Self, String> {
+        let password_hash = Self::hash_password(&password)?;
         Ok(User {
             id,
             name,
             email,
             password_hash,
+            email_verified_at: None,
+            totp_enabled_at: None,
+            timezone,
         })
     }
 
+    pub fn hash_password(password: &str) -> Result<String, String> {
+        hash(password, DEFAULT_COST).map_err(|e| e.to_string())
+    }
+
     pub fn verify_password(&self, password: &str) -> bool {
         verify(password, &self.password_hash).unwrap_or(false)
     }
+
+    pub fn is_verified(&self) -> bool {
+        self.email_verified_at.is_some()
+    }
+
+    pub fn has_two_factor(&self) -> bool {
+        self.totp_enabled_at.is_some()
+    }
+
+    /// The user's timezone, or UTC if the stored name isn't recognised.
+    pub fn tz(&self) -> Tz {
+        parse_timezone(&self.timezone).unwrap_or(Tz::UTC)
+    }
+
+    /// Today's date where the user is.
+    pub fn today(&self) -> NaiveDate {
+        Utc::now().with_timezone(&self.tz()).date_naive()
+    }
+}
+
+/// Look up an IANA timezone name such as `Europe/Berlin`.
+pub fn parse_timezone(name: &str) -> Option<Tz> {
+    name.trim().parse().ok()
+}
+
+/// Timezone names offered on the profile page: UTC and the `Area/City`
+/// zones, leaving out legacy aliases like `US/Pacific`.
+pub fn timezone_names() -> impl Iterator<Item = &'static str> {
+    const AREAS: [&str; 10] = [
+        "Africa",
+        "America",
+        "Antarctica",
+        "Arctic",
+        "Asia",
+        "Atlantic",
+        "Australia",
+        "Europe",
+        "Indian",
+        "Pacific",
+    ];
+    chrono_tz::TZ_VARIANTS
+        .iter()
+        .map(|tz| tz.name())
+        .filter(|name| {
+            *name == "UTC"
+                || name
+                    .split_once('/')
+                    .is_some_and(|(area, _)| AREAS.contains(&area))
+        })
 }
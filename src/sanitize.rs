@@ -0,0 +1,382 @@
+//! Allowlist sanitizer for newsletter article HTML. Articles are cleaned when
+//! they arrive through the API and again when rendered, so rows written before
+//! sanitizing existed (or edited by hand) can't reach readers unfiltered.
+
+use std::{cell::RefCell, collections::BTreeMap};
+
+use html5ever::{
+    LocalName, local_name,
+    tendril::StrTendril,
+    tokenizer::{
+        BufferQueue, StartTag, Tag, TagToken, Token, TokenSink, TokenSinkResult, Tokenizer,
+        TokenizerOpts, states::RawKind,
+    },
+};
+use serde::Serialize;
+
+const TAGS: &[&str] = &[
+    "a",
+    "abbr",
+    "b",
+    "blockquote",
+    "br",
+    "code",
+    "em",
+    "figcaption",
+    "figure",
+    "h2",
+    "h3",
+    "h4",
+    "hr",
+    "i",
+    "img",
+    "li",
+    "ol",
+    "p",
+    "pre",
+    "strong",
+    "sub",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "th",
+    "thead",
+    "tr",
+    "ul",
+];
+
+const TAG_ATTRIBUTES: &[(&str, &[&str])] = &[
+    ("a", &["href", "title"]),
+    ("abbr", &["title"]),
+    ("img", &["src", "alt", "title", "width", "height"]),
+    ("td", &["colspan", "rowspan"]),
+    ("th", &["colspan", "rowspan", "scope"]),
+];
+
+/// Elements dropped together with everything inside them.
+const DROP_WITH_CONTENT: &[&str] = &[
+    "script", "style", "iframe", "object", "embed", "template", "noscript", "svg", "math",
+];
+
+const URL_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+const EXTERNAL_LINK_REL: &str = "noopener nofollow";
+
+/// What the sanitizer removed from a document, for the API response.
+#[derive(Debug, Default, Serialize)]
+pub struct Stripped {
+    /// Disallowed elements, e.g. `script`.
+    pub tags: Vec<String>,
+    /// Disallowed attributes as `tag[attribute]`, including URLs with a
+    /// disallowed scheme, e.g. `img[onerror]` or `a[href]`.
+    pub attributes: Vec<String>,
+}
+
+fn builder() -> ammonia::Builder<'static> {
+    let mut builder = ammonia::Builder::empty();
+    builder
+        .add_tags(TAGS)
+        .clean_content_tags(DROP_WITH_CONTENT.iter().copied().collect())
+        .url_schemes(URL_SCHEMES.iter().copied().collect())
+        .link_rel(None)
+        .strip_comments(true);
+    for (tag, attributes) in TAG_ATTRIBUTES {
+        builder.add_tag_attributes(tag, attributes.iter());
+    }
+    builder
+}
+
+/// Clean `html` against the allowlist and mark links leaving the site.
+pub fn clean(html: &str) -> String {
+    mark_external_links(&builder().clean(html).to_string())
+}
+
+/// Clean `html`, also reporting what was removed.
+pub fn clean_with_report(html: &str) -> (String, Stripped) {
+    let sanitized = builder().clean(html).to_string();
+    let stripped = report(html, &sanitized);
+    (mark_external_links(&sanitized), stripped)
+}
+
+// ── External links ─────────────────────────────────────────────────────────────
+
+/// Give every `<a>` whose `href` leaves the site [`EXTERNAL_LINK_REL`].
+/// `html` must be ammonia's output: html5ever serializes every attribute as
+/// `name="value"` with `"` escaped and escapes `<` in text, so each `<`
+/// starts a tag and only a quoted value can hide a `>`.
+fn mark_external_links(html: &str) -> String {
+    let mut marked = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(open) = rest.find('<') {
+        let (text, tag) = rest.split_at(open);
+        marked.push_str(text);
+        let (tag, after) = tag.split_at(tag_len(tag));
+        match tag.strip_suffix('>') {
+            Some(start) if anchor_href(start).is_some_and(is_external_url) => {
+                marked.push_str(start);
+                marked.push_str(&format!(r#" rel="{EXTERNAL_LINK_REL}">"#));
+            }
+            _ => marked.push_str(tag),
+        }
+        rest = after;
+    }
+    marked.push_str(rest);
+    marked
+}
+
+/// Length of the tag `html` starts with, up to and including its `>`.
+fn tag_len(html: &str) -> usize {
+    let mut quoted = false;
+    for (i, c) in html.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '>' if !quoted => return i + 1,
+            _ => {}
+        }
+    }
+    html.len()
+}
+
+/// The `href` of an `<a …` start tag given without its closing `>`.
+fn anchor_href(tag: &str) -> Option<&str> {
+    let mut attributes = tag.strip_prefix("<a ")?;
+    loop {
+        let (name, rest) = attributes.trim_start().split_once("=\"")?;
+        let (value, rest) = rest.split_once('"')?;
+        if name == "href" {
+            return Some(value);
+        }
+        attributes = rest;
+    }
+}
+
+fn is_external_url(href: &str) -> bool {
+    let href = href.trim().to_ascii_lowercase();
+    href.starts_with("http://") || href.starts_with("https://") || href.starts_with("//")
+}
+
+// ── Reporting ──────────────────────────────────────────────────────────────────
+
+/// Work out what sanitizing `html` into `sanitized` removed: elements not on
+/// the allowlist, and attributes that occur fewer times in the output than in
+/// the input. Nothing inside a [`DROP_WITH_CONTENT`] element is counted, so
+/// the children of a dropped `<svg>` aren't listed separately.
+fn report(html: &str, sanitized: &str) -> Stripped {
+    let input = Census::of(html);
+    let output = Census::of(sanitized);
+
+    let tags = input
+        .tags
+        .into_keys()
+        .filter(|tag| !TAGS.contains(&tag.as_str()))
+        .collect();
+    let attributes = input
+        .attributes
+        .into_iter()
+        .filter(|(key, count)| output.attributes.get(key).is_none_or(|kept| kept < count))
+        .map(|((tag, attribute), _)| format!("{tag}[{attribute}]"))
+        .collect();
+
+    Stripped { tags, attributes }
+}
+
+/// How often each element, and each attribute of an allowed element, starts
+/// a tag in a document.
+#[derive(Default)]
+struct Census {
+    tags: BTreeMap<String, usize>,
+    attributes: BTreeMap<(String, String), usize>,
+}
+
+impl Census {
+    fn of(html: &str) -> Census {
+        let input = BufferQueue::default();
+        input.push_back(StrTendril::from_slice(html));
+        let tokenizer = Tokenizer::new(CensusSink::default(), TokenizerOpts::default());
+        let _ = tokenizer.feed(&input);
+        tokenizer.end();
+        tokenizer.sink.census.into_inner()
+    }
+}
+
+#[derive(Default)]
+struct CensusSink {
+    census: RefCell<Census>,
+    /// The dropped element being skipped, and how deeply it is nested.
+    skipping: RefCell<Option<(LocalName, usize)>>,
+}
+
+impl CensusSink {
+    fn count(&self, tag: &Tag) {
+        let mut census = self.census.borrow_mut();
+        let name = tag.name.to_string();
+        if TAGS.contains(&name.as_str()) {
+            for attribute in &tag.attrs {
+                let key = (name.clone(), attribute.name.local.to_string());
+                *census.attributes.entry(key).or_default() += 1;
+            }
+        }
+        *census.tags.entry(name).or_default() += 1;
+    }
+}
+
+impl TokenSink for CensusSink {
+    type Handle = ();
+
+    fn process_token(&self, token: Token, _line_number: u64) -> TokenSinkResult<()> {
+        let TagToken(tag) = token else {
+            return TokenSinkResult::Continue;
+        };
+        let mut skipping = self.skipping.borrow_mut();
+        if let Some((name, depth)) = skipping.as_mut() {
+            if tag.name == *name && !tag.self_closing {
+                if tag.kind == StartTag {
+                    *depth += 1;
+                } else {
+                    *depth -= 1;
+                }
+            }
+            if *depth == 0 {
+                *skipping = None;
+            }
+            return TokenSinkResult::Continue;
+        }
+        if tag.kind != StartTag {
+            return TokenSinkResult::Continue;
+        }
+
+        self.count(&tag);
+        let raw = raw_text_kind(&tag.name);
+        if DROP_WITH_CONTENT.contains(&&*tag.name) && (raw.is_some() || !tag.self_closing) {
+            *skipping = Some((tag.name, 1));
+        }
+        // Read the contents of raw text elements as text, like the parser does.
+        raw.map_or(TokenSinkResult::Continue, TokenSinkResult::RawData)
+    }
+}
+
+/// How the parser tokenizes the contents of `name`, if not as markup.
+fn raw_text_kind(name: &LocalName) -> Option<RawKind> {
+    match *name {
+        local_name!("title") | local_name!("textarea") => Some(RawKind::Rcdata),
+        local_name!("style")
+        | local_name!("xmp")
+        | local_name!("iframe")
+        | local_name!("noembed")
+        | local_name!("noframes")
+        | local_name!("noscript") => Some(RawKind::Rawtext),
+        local_name!("script") => Some(RawKind::ScriptData),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_scripts_and_iframes_with_their_content() {
+        let (html, stripped) = clean_with_report(
+            r#"<p>Hi<script>alert(1)</script></p><iframe src="https://x.io"><p>inner</p></iframe>"#,
+        );
+        assert_eq!(html, "<p>Hi</p>");
+        assert_eq!(stripped.tags, ["iframe", "script"]);
+        assert!(stripped.attributes.is_empty());
+    }
+
+    #[test]
+    fn strips_event_handler_attributes() {
+        let (html, stripped) =
+            clean_with_report(r#"<img src="/a.png" onerror="alert(1)"><p onclick="x()">t</p>"#);
+        assert_eq!(html, r#"<img src="/a.png"><p>t</p>"#);
+        assert!(stripped.tags.is_empty());
+        assert_eq!(stripped.attributes, ["img[onerror]", "p[onclick]"]);
+    }
+
+    #[test]
+    fn strips_javascript_hrefs_however_they_are_spelt() {
+        for href in [
+            "javascript:alert(1)",
+            " JaVaScRiPt:alert(1)",
+            "java\tscript:alert(1)",
+        ] {
+            let (html, stripped) = clean_with_report(&format!(r#"<a href="{href}">x</a>"#));
+            assert_eq!(html, "<a>x</a>", "{href:?}");
+            assert_eq!(stripped.attributes, ["a[href]"], "{href:?}");
+        }
+    }
+
+    #[test]
+    fn only_external_links_get_rel() {
+        assert_eq!(
+            clean(r#"<a href="/journal">j</a><a href="notes">n</a><a href="mailto:a@x.io">m</a>"#),
+            r#"<a href="/journal">j</a><a href="notes">n</a><a href="mailto:a@x.io">m</a>"#
+        );
+        assert_eq!(
+            clean(
+                r#"<a href="https://x.io">x</a><a href="//x.io">y</a><a href="HTTP://x.io">z</a>"#
+            ),
+            concat!(
+                r#"<a href="https://x.io" rel="noopener nofollow">x</a>"#,
+                r#"<a href="//x.io" rel="noopener nofollow">y</a>"#,
+                r#"<a href="HTTP://x.io" rel="noopener nofollow">z</a>"#,
+            )
+        );
+    }
+
+    #[test]
+    fn rel_follows_each_links_own_href() {
+        assert_eq!(
+            clean(r#"<a href="https://x.io" rel="opener">x</a><a>y</a><a href="/z">z</a>"#),
+            r#"<a href="https://x.io" rel="noopener nofollow">x</a><a>y</a><a href="/z">z</a>"#
+        );
+    }
+
+    #[test]
+    fn rel_ignores_lookalikes_in_text_and_attributes() {
+        assert_eq!(
+            clean(
+                r#"&lt;a href="https://x.io"&gt;<a title='> href="https://y.io"' href="/z">z</a>"#
+            ),
+            r#"&lt;a href="https://x.io"&gt;<a title="&gt; href=&quot;https://y.io&quot;" href="/z">z</a>"#
+        );
+    }
+
+    #[test]
+    fn dropped_subtrees_are_reported_once() {
+        let (html, stripped) =
+            clean_with_report(r#"<p>a</p><svg><circle r="1"/><a href="/x">x</a></svg><p>b</p>"#);
+        assert_eq!(html, "<p>a</p><p>b</p>");
+        assert_eq!(stripped.tags, ["svg"]);
+        assert!(stripped.attributes.is_empty());
+    }
+
+    #[test]
+    fn report_matches_what_was_removed() {
+        let input = concat!(
+            r#"<div class="x"><span style="color:red">s</span>"#,
+            r#"<a href="https://x.io" target="_blank" title="t">x</a>"#,
+            r#"<img src="data:image/png;base64,AA" alt="a"><style>p{}</style></div>"#,
+        );
+        let (html, stripped) = clean_with_report(input);
+        assert_eq!(
+            html,
+            r#"s<a href="https://x.io" title="t" rel="noopener nofollow">x</a><img alt="a">"#
+        );
+        assert_eq!(stripped.tags, ["div", "span", "style"]);
+        assert_eq!(stripped.attributes, ["a[target]", "img[src]"]);
+
+        for tag in &stripped.tags {
+            assert!(!html.contains(&format!("<{tag}")), "{tag}");
+        }
+    }
+
+    #[test]
+    fn clean_input_reports_nothing() {
+        let input = r#"<p>Plain <em>text</em> and <a href="/x">a link</a>.</p>"#;
+        let (html, stripped) = clean_with_report(input);
+        assert_eq!(html, input);
+        assert!(stripped.tags.is_empty() && stripped.attributes.is_empty());
+    }
+}
@@ -0,0 +1,229 @@
+use async_trait::async_trait;
+use sqlx::SqlitePool;
+use tower_sessions::{
+    ExpiredDeletion, SessionStore,
+    cookie::time::OffsetDateTime,
+    session::{Id, Record},
+    session_store,
+};
+
+use crate::handlers::auth::SESSION_USER_KEY;
+
+/// `tower_sessions` store backed by the `sessions` table, sharing the same
+/// SQLite pool as `UserStore` so logins survive restarts and redeploys.
+#[derive(Clone, Debug)]
+pub struct SqliteSessionStore {
+    pool: SqlitePool,
+}
+
+impl SqliteSessionStore {
+    pub fn new(pool: SqlitePool) -> Self {
+        SqliteSessionStore { pool }
+    }
+
+    /// Log a user out everywhere, optionally sparing the session `keep`
+    /// (the one making the request).
+    pub async fn delete_user_sessions(
+        &self,
+        user_id: &str,
+        keep: Option<Id>,
+    ) -> Result<(), String> {
+        sqlx::query(
+            "DELETE FROM sessions
+             WHERE json_extract(data, '$.' || ?) = ? AND id != ?",
+        )
+        .bind(SESSION_USER_KEY)
+        .bind(user_id)
+        .bind(keep.map(|id| id.to_string()).unwrap_or_default())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+
+    /// Rows in the `sessions` table, expired or not.
+    #[cfg(test)]
+    pub async fn count(&self) -> i64 {
+        sqlx::query_scalar("SELECT COUNT(*) FROM sessions")
+            .fetch_one(&self.pool)
+            .await
+            .expect("sessions table is readable")
+    }
+}
+
+fn backend(e: sqlx::Error) -> session_store::Error {
+    session_store::Error::Backend(e.to_string())
+}
+
+fn encode(record: &Record) -> session_store::Result<String> {
+    serde_json::to_string(&record.data).map_err(|e| session_store::Error::Encode(e.to_string()))
+}
+
+#[async_trait]
+impl SessionStore for SqliteSessionStore {
+    async fn create(&self, record: &mut Record) -> session_store::Result<()> {
+        let data = encode(record)?;
+
+        // Session ids are random, but regenerate on the off chance of a clash
+        // rather than overwriting somebody else's session.
+        loop {
+            let inserted = sqlx::query(
+                "INSERT INTO sessions (id, data, expiry_date) VALUES (?, ?, ?)
+                 ON CONFLICT(id) DO NOTHING",
+            )
+            .bind(record.id.to_string())
+            .bind(&data)
+            .bind(record.expiry_date.unix_timestamp())
+            .execute(&self.pool)
+            .await
+            .map_err(backend)?;
+
+            if inserted.rows_affected() > 0 {
+                return Ok(());
+            }
+            record.id = Id::default();
+        }
+    }
+
+    async fn save(&self, record: &Record) -> session_store::Result<()> {
+        sqlx::query(
+            "INSERT INTO sessions (id, data, expiry_date) VALUES (?, ?, ?)
+             ON CONFLICT(id) DO UPDATE SET data = excluded.data, expiry_date = excluded.expiry_date",
+        )
+        .bind(record.id.to_string())
+        .bind(encode(record)?)
+        .bind(record.expiry_date.unix_timestamp())
+        .execute(&self.pool)
+        .await
+        .map_err(backend)?;
+
+        Ok(())
+    }
+
+    async fn load(&self, session_id: &Id) -> session_store::Result<Option<Record>> {
+        let row = sqlx::query_as::<_, (String, i64)>(
+            "SELECT data, expiry_date FROM sessions WHERE id = ? AND expiry_date > ?",
+        )
+        .bind(session_id.to_string())
+        .bind(OffsetDateTime::now_utc().unix_timestamp())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(backend)?;
+
+        let Some((data, expiry)) = row else {
+            return Ok(None);
+        };
+
+        Ok(Some(Record {
+            id: *session_id,
+            data: serde_json::from_str(&data)
+                .map_err(|e| session_store::Error::Decode(e.to_string()))?,
+            expiry_date: OffsetDateTime::from_unix_timestamp(expiry)
+                .map_err(|e| session_store::Error::Decode(e.to_string()))?,
+        }))
+    }
+
+    async fn delete(&self, session_id: &Id) -> session_store::Result<()> {
+        sqlx::query("DELETE FROM sessions WHERE id = ?")
+            .bind(session_id.to_string())
+            .execute(&self.pool)
+            .await
+            .map_err(backend)?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ExpiredDeletion for SqliteSessionStore {
+    async fn delete_expired(&self) -> session_store::Result<()> {
+        sqlx::query("DELETE FROM sessions WHERE expiry_date <= ?")
+            .bind(OffsetDateTime::now_utc().unix_timestamp())
+            .execute(&self.pool)
+            .await
+            .map_err(backend)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tower_sessions::cookie::time::Duration;
+
+    fn record(user_id: Option<&str>, expires_in: Duration) -> Record {
+        let mut data = HashMap::new();
+        if let Some(user_id) = user_id {
+            data.insert(SESSION_USER_KEY.to_string(), user_id.into());
+        }
+        data.insert("_csrf".to_string(), "token".into());
+        Record {
+            id: Id::default(),
+            data,
+            expiry_date: OffsetDateTime::now_utc() + expires_in,
+        }
+    }
+
+    #[tokio::test]
+    async fn saved_sessions_load_until_they_expire() {
+        let store = SqliteSessionStore::new(crate::db::test_pool().await);
+        let mut live = record(Some("ada"), Duration::hours(1));
+        store.create(&mut live).await.unwrap();
+
+        let loaded = store.load(&live.id).await.unwrap().expect("session loads");
+        assert_eq!(loaded.data, live.data);
+        assert_eq!(
+            loaded.expiry_date.unix_timestamp(),
+            live.expiry_date.unix_timestamp()
+        );
+
+        live.data.insert("_csrf".to_string(), "rotated".into());
+        store.save(&live).await.unwrap();
+        let loaded = store.load(&live.id).await.unwrap().unwrap();
+        assert_eq!(loaded.data["_csrf"], "rotated");
+
+        let mut expired = record(Some("ada"), Duration::seconds(-1));
+        store.create(&mut expired).await.unwrap();
+        assert!(store.load(&expired.id).await.unwrap().is_none());
+
+        store.delete_expired().await.unwrap();
+        assert_eq!(store.count().await, 1);
+
+        store.delete(&live.id).await.unwrap();
+        assert!(store.load(&live.id).await.unwrap().is_none());
+        assert_eq!(store.count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn deleting_a_users_sessions_spares_everyone_else() {
+        let store = SqliteSessionStore::new(crate::db::test_pool().await);
+        let mut current = record(Some("ada"), Duration::hours(1));
+        let mut other_device = record(Some("ada"), Duration::hours(1));
+        let mut someone_else = record(Some("grace"), Duration::hours(1));
+        let mut anonymous = record(None, Duration::hours(1));
+        for record in [
+            &mut current,
+            &mut other_device,
+            &mut someone_else,
+            &mut anonymous,
+        ] {
+            store.create(record).await.unwrap();
+        }
+
+        store
+            .delete_user_sessions("ada", Some(current.id))
+            .await
+            .unwrap();
+        assert!(store.load(&current.id).await.unwrap().is_some());
+        assert!(store.load(&other_device.id).await.unwrap().is_none());
+        assert!(store.load(&someone_else.id).await.unwrap().is_some());
+        assert!(store.load(&anonymous.id).await.unwrap().is_some());
+
+        store.delete_user_sessions("ada", None).await.unwrap();
+        assert!(store.load(&current.id).await.unwrap().is_none());
+        assert_eq!(store.count().await, 2);
+    }
+}
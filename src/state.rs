@@ -1,6 +1,26 @@
-use crate::store::UserStore;
+use std::sync::Arc;
+
+use crate::{config::Config, mailer::Mailer, session_store::SqliteSessionStore, store::UserStore};
 
 #[derive(Clone, Debug)]
 pub struct AppState {
     pub user_store: UserStore,
+    pub session_store: SqliteSessionStore,
+    pub config: Config,
+    pub mailer: Arc<dyn Mailer>,
+}
+
+#[cfg(test)]
+impl AppState {
+    /// Default settings over a fresh in-memory database, with mail going to
+    /// `mailer`.
+    pub async fn for_tests(mailer: Arc<dyn Mailer>) -> Arc<AppState> {
+        let pool = crate::db::test_pool().await;
+        Arc::new(AppState {
+            user_store: UserStore::new(pool.clone(), crate::crypto::Keyring::for_tests()),
+            session_store: SqliteSessionStore::new(pool),
+            config: Config::from_env(),
+            mailer,
+        })
+    }
 }
@@ -1,22 +1,52 @@
-use chrono::{Local, NaiveDate};
-use sqlx::SqlitePool;
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use sqlx::{Sqlite, SqlitePool, Transaction};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use uuid::Uuid;
 
+use crate::crypto::{self, DataKey, ENCRYPTED_PREFIX, Keyring};
 use crate::models::{
+    achievements::{ACHIEVEMENTS, Achievement, AchievementStats, UnlockedAchievement},
+    breathing::{BreathingPattern, CustomBreathingPattern, MAX_CUSTOM_PATTERNS},
+    goals::{Goal, GoalKind, GoalOverview, GoalPeriod},
+    history::{HISTORY_TYPES, HistoryFilter, HistoryItem, HistoryPage, PAGE_SIZE},
+    insights::MoodDay,
+    journal::{
+        JournalEntry, JournalFilter, JournalPage, index_words, indexed_prefix, search_snippet,
+    },
+    login_attempt::{AttemptOutcome, FailureStreak},
+    meditation::{MAX_PRESETS, MeditationPreset, MeditationSettings},
     newsletter::{NewsletterArticle, NewsletterSubscriber},
-    session::{DashboardStats, WeeklyMinutes},
-    user::User,
+    session::{DashboardStats, DayActivity},
+    streak::{StreakHistory, StreakTally},
+    user::{User, parse_timezone},
     video::VideoWithUploader,
 };
 
+/// Correlated subquery giving a journal entry's (`je`) tags as "a,b,c".
+const TAG_LIST: &str = "SELECT group_concat(t.name, ',') FROM journal_entry_tags et
+     JOIN journal_tags t ON t.id = et.tag_id WHERE et.entry_id = je.id";
+
+/// Shown in lists in place of a note whose key isn't available.
+const UNREADABLE_NOTE: &str = "(This note couldn't be decrypted.)";
+
+/// How far back goal history is filled in after a long absence.
+const MAX_GOAL_BACKFILL_DAYS: i64 = 366;
+
+/// Shortest timed session worth logging; anything longer is rounded to the
+/// nearest minute, so this is where a session first counts as one.
+const MIN_LOGGED_SECS: i64 = 30;
+
 #[derive(Clone, Debug)]
 pub struct UserStore {
     pool: SqlitePool,
+    /// Master keys for the per-user keys that encrypt journal notes.
+    keyring: Keyring,
 }
 
 impl UserStore {
-    pub fn new(pool: SqlitePool) -> Self {
-        UserStore { pool }
+    pub fn new(pool: SqlitePool, keyring: Keyring) -> Self {
+        UserStore { pool, keyring }
     }
 
     // ── Users ──────────────────────────────────────────────────────────────────
@@ -26,6 +56,7 @@ impl UserStore {
         name: String,
         email: String,
         password: String,
+        timezone: String,
     ) -> Result<User, String> {
         let existing = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM users WHERE email = ?")
             .bind(&email)
@@ -37,23 +68,27 @@ impl UserStore {
             return Err("An account with this email already exists.".to_string());
         }
 
-        let user = User::new(Uuid::new_v4().to_string(), name, email, password)?;
+        let user = User::new(Uuid::new_v4().to_string(), name, email, password, timezone)?;
 
-        sqlx::query("INSERT INTO users (id, name, email, password_hash) VALUES (?, ?, ?, ?)")
-            .bind(&user.id)
-            .bind(&user.name)
-            .bind(&user.email)
-            .bind(&user.password_hash)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| e.to_string())?;
+        sqlx::query(
+            "INSERT INTO users (id, name, email, password_hash, timezone) VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(&user.id)
+        .bind(&user.name)
+        .bind(&user.email)
+        .bind(&user.password_hash)
+        .bind(&user.timezone)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| e.to_string())?;
 
         Ok(user)
     }
 
     pub async fn find_by_email(&self, email: &str) -> Option<User> {
         sqlx::query_as::<_, User>(
-            "SELECT id, name, email, password_hash FROM users WHERE email = ?",
+            "SELECT id, name, email, password_hash, email_verified_at, totp_enabled_at, timezone
+             FROM users WHERE email = ?",
         )
         .bind(email)
         .fetch_optional(&self.pool)
@@ -62,22 +97,97 @@ impl UserStore {
         .flatten()
     }
 
-    // ── Sessions ───────────────────────────────────────────────────────────────
+    pub async fn find_by_id(&self, id: &str) -> Option<User> {
+        sqlx::query_as::<_, User>(
+            "SELECT id, name, email, password_hash, email_verified_at, totp_enabled_at, timezone
+             FROM users WHERE id = ?",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .ok()
+        .flatten()
+    }
+
+    pub async fn update_name(&self, user_id: &str, name: &str) -> Result<(), String> {
+        sqlx::query("UPDATE users SET name = ? WHERE id = ?")
+            .bind(name)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+
+    /// Change the user's timezone. Goal periods from here on follow the new
+    /// day boundaries.
+    pub async fn update_timezone(&self, user_id: &str, timezone: &str) -> Result<(), String> {
+        let tz = parse_timezone(timezone).ok_or_else(|| "Unknown timezone.".to_string())?;
+        let mut tx = self.pool.begin().await.map_err(|e| e.to_string())?;
+
+        sqlx::query("UPDATE users SET timezone = ? WHERE id = ?")
+            .bind(timezone)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let today = today_in(tz);
+        Self::sync_goal_periods(&mut tx, user_id, tz, today, today).await?;
+        tx.commit().await.map_err(|e| e.to_string())
+    }
+
+    /// The user's timezone, for working out which day a timestamp falls on.
+    async fn user_timezone(&self, user_id: &str) -> Tz {
+        sqlx::query_scalar::<_, String>("SELECT timezone FROM users WHERE id = ?")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await
+            .ok()
+            .flatten()
+            .and_then(|name| parse_timezone(&name))
+            .unwrap_or(Tz::UTC)
+    }
+
+    pub async fn update_password(&self, user_id: &str, password: &str) -> Result<(), String> {
+        let password_hash = User::hash_password(password)?;
+
+        sqlx::query("UPDATE users SET password_hash = ? WHERE id = ?")
+            .bind(&password_hash)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+
+    // ── Password reset tokens ──────────────────────────────────────────────────
 
-    pub async fn log_session(
+    /// Store a new reset token, revoking any earlier unused ones for the user.
+    pub async fn create_password_reset_token(
         &self,
         user_id: &str,
-        session_type: &str,
-        duration_min: i64,
+        token_hash: &str,
+        ttl_minutes: i64,
     ) -> Result<(), String> {
         sqlx::query(
-            "INSERT INTO mindful_sessions (id, user_id, session_type, duration_min)
-             VALUES (?, ?, ?, ?)",
+            "UPDATE password_reset_tokens SET used_at = datetime('now')
+             WHERE user_id = ? AND used_at IS NULL",
         )
-        .bind(Uuid::new_v4().to_string())
         .bind(user_id)
-        .bind(session_type)
-        .bind(duration_min)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        sqlx::query(
+            "INSERT INTO password_reset_tokens (token_hash, user_id, expires_at)
+             VALUES (?, ?, datetime('now', ?))",
+        )
+        .bind(token_hash)
+        .bind(user_id)
+        .bind(format!("+{ttl_minutes} minutes"))
         .execute(&self.pool)
         .await
         .map_err(|e| e.to_string())?;
@@ -85,93 +195,1877 @@ impl UserStore {
         Ok(())
     }
 
-    // ── Journal ────────────────────────────────────────────────────────────────
+    /// The user a still-valid reset token belongs to, without consuming it.
+    pub async fn find_password_reset_user(&self, token_hash: &str) -> Option<String> {
+        sqlx::query_scalar::<_, String>(
+            "SELECT user_id FROM password_reset_tokens
+             WHERE token_hash = ? AND used_at IS NULL AND expires_at > datetime('now')",
+        )
+        .bind(token_hash)
+        .fetch_optional(&self.pool)
+        .await
+        .ok()
+        .flatten()
+    }
 
-    pub async fn log_journal_entry(
+    /// Atomically mark a reset token used and set the new password.
+    /// Returns `(user_id, email)` so their sessions can be revoked and their
+    /// lockout lifted.
+    pub async fn reset_password(
+        &self,
+        token_hash: &str,
+        password: &str,
+    ) -> Result<(String, String), String> {
+        let password_hash = User::hash_password(password)?;
+        let mut tx = self.pool.begin().await.map_err(|e| e.to_string())?;
+
+        let user_id = sqlx::query_scalar::<_, String>(
+            "UPDATE password_reset_tokens SET used_at = datetime('now')
+             WHERE token_hash = ? AND used_at IS NULL AND expires_at > datetime('now')
+             RETURNING user_id",
+        )
+        .bind(token_hash)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "This reset link is invalid or has expired.".to_string())?;
+
+        let email = sqlx::query_scalar::<_, String>(
+            "UPDATE users SET password_hash = ? WHERE id = ? RETURNING email",
+        )
+        .bind(&password_hash)
+        .bind(&user_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        tx.commit().await.map_err(|e| e.to_string())?;
+        Ok((user_id, email))
+    }
+
+    // ── Email verification tokens ──────────────────────────────────────────────
+
+    /// Store a verification token for `email`, revoking earlier unused ones.
+    pub async fn create_email_verification_token(
         &self,
         user_id: &str,
-        mood: i64,
-        note: &str,
+        email: &str,
+        token_hash: &str,
+        ttl_hours: i64,
+    ) -> Result<(), String> {
+        sqlx::query(
+            "UPDATE email_verification_tokens SET used_at = datetime('now')
+             WHERE user_id = ? AND used_at IS NULL",
+        )
+        .bind(user_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        sqlx::query(
+            "INSERT INTO email_verification_tokens (token_hash, user_id, email, expires_at)
+             VALUES (?, ?, ?, datetime('now', ?))",
+        )
+        .bind(token_hash)
+        .bind(user_id)
+        .bind(email)
+        .bind(format!("+{ttl_hours} hours"))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+
+    /// Consume a verification token and mark the address it was issued for
+    /// as verified. A token issued for a new address (an email change) also
+    /// moves the account to that address. Returns the user's id and whether
+    /// the address changed, so their other sessions can be signed out.
+    pub async fn verify_email(&self, token_hash: &str) -> Result<(String, bool), String> {
+        let mut tx = self.pool.begin().await.map_err(|e| e.to_string())?;
+
+        let (user_id, email) = sqlx::query_as::<_, (String, String)>(
+            "UPDATE email_verification_tokens SET used_at = datetime('now')
+             WHERE token_hash = ? AND used_at IS NULL AND expires_at > datetime('now')
+             RETURNING user_id, email",
+        )
+        .bind(token_hash)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "This verification link is invalid or has expired.".to_string())?;
+
+        let taken =
+            sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM users WHERE email = ? AND id != ?")
+                .bind(&email)
+                .bind(&user_id)
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+
+        if taken > 0 {
+            return Err("Another account is already using this email address.".to_string());
+        }
+
+        let old_email = sqlx::query_scalar::<_, String>("SELECT email FROM users WHERE id = ?")
+            .bind(&user_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        sqlx::query(
+            "UPDATE users
+             SET email_verified_at = CASE
+                     WHEN email = ? THEN COALESCE(email_verified_at, datetime('now'))
+                     ELSE datetime('now')
+                 END,
+                 email = ?
+             WHERE id = ?",
+        )
+        .bind(&email)
+        .bind(&email)
+        .bind(&user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        tx.commit().await.map_err(|e| e.to_string())?;
+        Ok((user_id, old_email != email))
+    }
+
+    pub async fn email_in_use(&self, email: &str) -> bool {
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM users WHERE email = ?")
+            .bind(email)
+            .fetch_one(&self.pool)
+            .await
+            .map(|n| n > 0)
+            .unwrap_or(true)
+    }
+
+    // ── Two-factor authentication ──────────────────────────────────────────────
+
+    /// Turn on TOTP for a user, replacing any previous recovery codes.
+    pub async fn enable_totp(
+        &self,
+        user_id: &str,
+        secret: &str,
+        verified_step: u64,
+        recovery_code_hashes: &[String],
     ) -> Result<(), String> {
-        sqlx::query("INSERT INTO journal_entries (id, user_id, mood, note) VALUES (?, ?, ?, ?)")
+        let mut tx = self.pool.begin().await.map_err(|e| e.to_string())?;
+
+        sqlx::query(
+            "UPDATE users
+             SET totp_secret = ?, totp_enabled_at = datetime('now'), totp_last_step = ?
+             WHERE id = ?",
+        )
+        .bind(secret)
+        .bind(verified_step as i64)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        sqlx::query("DELETE FROM totp_recovery_codes WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        for hash in recovery_code_hashes {
+            sqlx::query(
+                "INSERT INTO totp_recovery_codes (id, user_id, code_hash) VALUES (?, ?, ?)",
+            )
             .bind(Uuid::new_v4().to_string())
             .bind(user_id)
-            .bind(mood)
-            .bind(note)
-            .execute(&self.pool)
+            .bind(hash)
+            .execute(&mut *tx)
             .await
             .map_err(|e| e.to_string())?;
+        }
 
-        // Also count a journal entry as a 5-minute mindful session
-        self.log_session(user_id, "journal", 5).await
+        tx.commit().await.map_err(|e| e.to_string())
     }
 
-    // ── Stats ──────────────────────────────────────────────────────────────────
+    pub async fn disable_totp(&self, user_id: &str) -> Result<(), String> {
+        let mut tx = self.pool.begin().await.map_err(|e| e.to_string())?;
 
-    pub async fn get_stats(&self, user_id: &str) -> DashboardStats {
-        let sessions_today = sqlx::query_scalar::<_, i64>(
-            "SELECT COUNT(*) FROM mindful_sessions
-             WHERE user_id = ? AND date(completed_at) = date('now')",
+        sqlx::query(
+            "UPDATE users SET totp_secret = NULL, totp_enabled_at = NULL, totp_last_step = NULL
+             WHERE id = ?",
         )
         .bind(user_id)
-        .fetch_one(&self.pool)
+        .execute(&mut *tx)
         .await
-        .unwrap_or(0);
+        .map_err(|e| e.to_string())?;
+
+        sqlx::query("DELETE FROM totp_recovery_codes WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        tx.commit().await.map_err(|e| e.to_string())
+    }
+
+    /// The enabled TOTP secret and the last time step accepted for it.
+    pub async fn get_totp_secret(&self, user_id: &str) -> Option<(String, Option<i64>)> {
+        sqlx::query_as::<_, (String, Option<i64>)>(
+            "SELECT totp_secret, totp_last_step FROM users
+             WHERE id = ? AND totp_enabled_at IS NOT NULL AND totp_secret IS NOT NULL",
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await
+        .ok()
+        .flatten()
+    }
+
+    /// Record that a time step was used. Fails if an equal or later step was
+    /// already accepted, so each code works only once.
+    pub async fn claim_totp_step(&self, user_id: &str, step: u64) -> bool {
+        sqlx::query(
+            "UPDATE users SET totp_last_step = ?
+             WHERE id = ? AND (totp_last_step IS NULL OR totp_last_step < ?)",
+        )
+        .bind(step as i64)
+        .bind(user_id)
+        .bind(step as i64)
+        .execute(&self.pool)
+        .await
+        .map(|r| r.rows_affected() > 0)
+        .unwrap_or(false)
+    }
+
+    /// Spend a recovery code. Returns false if it doesn't exist or was used.
+    pub async fn use_recovery_code(&self, user_id: &str, code_hash: &str) -> bool {
+        sqlx::query(
+            "UPDATE totp_recovery_codes SET used_at = datetime('now')
+             WHERE user_id = ? AND code_hash = ? AND used_at IS NULL",
+        )
+        .bind(user_id)
+        .bind(code_hash)
+        .execute(&self.pool)
+        .await
+        .map(|r| r.rows_affected() > 0)
+        .unwrap_or(false)
+    }
+
+    // ── Login attempts ─────────────────────────────────────────────────────────
+
+    /// Append to the login audit trail.
+    pub async fn record_login_attempt(
+        &self,
+        email: &str,
+        ip: &str,
+        outcome: AttemptOutcome,
+    ) -> Result<(), String> {
+        sqlx::query(
+            "INSERT INTO login_attempts (id, email, ip, succeeded, reason) VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(email)
+        .bind(ip)
+        .bind(outcome.succeeded())
+        .bind(outcome.as_str())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+
+    /// Wrong passwords or codes for an account since its last successful
+    /// sign-in (or password reset), looking back at most a day.
+    pub async fn account_failures(&self, email: &str) -> FailureStreak {
+        self.failure_streak(
+            "SELECT COUNT(*), MAX(attempted_at) FROM login_attempts
+             WHERE email = ?1 AND reason IN ('bad_password', 'bad_code')
+               AND attempted_at > datetime('now', '-1 day')
+               AND attempted_at > COALESCE(
+                   (SELECT MAX(attempted_at) FROM login_attempts
+                    WHERE email = ?1 AND succeeded = 1), '')",
+            email,
+        )
+        .await
+    }
+
+    /// Wrong passwords or codes from an IP address in the last hour, across
+    /// all accounts. Successes don't reset this, so an attacker can't clear it
+    /// by signing in to their own account.
+    pub async fn ip_failures(&self, ip: &str) -> FailureStreak {
+        self.failure_streak(
+            "SELECT COUNT(*), MAX(attempted_at) FROM login_attempts
+             WHERE ip = ?1 AND reason IN ('bad_password', 'bad_code')
+               AND attempted_at > datetime('now', '-1 hour')",
+            ip,
+        )
+        .await
+    }
+
+    async fn failure_streak(&self, sql: &str, key: &str) -> FailureStreak {
+        let row = sqlx::query_as::<_, (i64, Option<String>)>(sql)
+            .bind(key)
+            .fetch_one(&self.pool)
+            .await;
+
+        match row {
+            Ok((failures, last)) => FailureStreak {
+                failures,
+                last_failed_at: last
+                    .and_then(|t| NaiveDateTime::parse_from_str(&t, "%Y-%m-%d %H:%M:%S").ok()),
+            },
+            Err(_) => FailureStreak::default(),
+        }
+    }
+
+    // ── Sessions ───────────────────────────────────────────────────────────────
+
+    /// Start the clock on a timed activity, when the user presses Begin.
+    /// Returns the id that `finish_session` takes. Time spent in a
+    /// `lead_in_secs` countdown before the timer runs isn't credited.
+    pub async fn start_session(
+        &self,
+        user_id: &str,
+        session_type: &str,
+        planned_secs: i64,
+        lead_in_secs: i64,
+        pattern: Option<&str>,
+        cycles: Option<i64>,
+    ) -> Result<String, String> {
+        let id = Uuid::new_v4().to_string();
+
+        sqlx::query(
+            "INSERT INTO session_starts
+                 (id, user_id, session_type, planned_secs, pattern, cycles, lead_in_secs, begun_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, datetime('now', '+' || ?7 || ' seconds'))",
+        )
+        .bind(&id)
+        .bind(user_id)
+        .bind(session_type)
+        .bind(planned_secs)
+        .bind(pattern)
+        .bind(cycles)
+        .bind(lead_in_secs)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        Ok(id)
+    }
+
+    /// Close a started activity and log it with the time actually spent
+    /// since Begin, capped at the planned length and rounded to the nearest
+    /// minute. Sessions shorter than `MIN_LOGGED_SECS` aren't logged. Each
+    /// start can be completed once.
+    pub async fn finish_session(
+        &self,
+        user_id: &str,
+        start_id: &str,
+        session_type: &str,
+    ) -> Result<(), String> {
+        let tz = self.user_timezone(user_id).await;
+        let mut tx = self.pool.begin().await.map_err(|e| e.to_string())?;
+
+        let start = sqlx::query_as::<_, (i64, Option<String>, Option<i64>, Option<i64>)>(
+            "UPDATE session_starts SET completed_at = datetime('now')
+             WHERE id = ? AND user_id = ? AND session_type = ? AND completed_at IS NULL
+             RETURNING planned_secs, pattern, cycles,
+                       CAST(strftime('%s', 'now') - strftime('%s', begun_at) AS INTEGER)",
+        )
+        .bind(start_id)
+        .bind(user_id)
+        .bind(session_type)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        let Some((planned_secs, pattern, cycles, elapsed_secs)) = start else {
+            return Err(
+                "We couldn't find the start of this session. Please start it again.".to_string(),
+            );
+        };
+
+        // Still in the lead-in countdown
+        let Some(elapsed_secs) = elapsed_secs.filter(|&secs| secs >= 0) else {
+            return Err("This session hasn't started yet, so there's nothing to save.".to_string());
+        };
+        let actual_secs = elapsed_secs.min(planned_secs);
+        if actual_secs < MIN_LOGGED_SECS {
+            return Err(format!(
+                "Sessions shorter than {MIN_LOGGED_SECS} seconds aren't saved."
+            ));
+        }
+        // Whole cycles that fit in the time spent
+        let cycles = cycles.map(|c| c * actual_secs / planned_secs.max(1));
+        let duration_min = (actual_secs + 30) / 60;
+
+        sqlx::query(
+            "INSERT INTO mindful_sessions
+                 (id, user_id, session_type, duration_min, pattern, cycles, planned_secs, actual_secs)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(user_id)
+        .bind(session_type)
+        .bind(duration_min)
+        .bind(pattern)
+        .bind(cycles)
+        .bind(planned_secs)
+        .bind(actual_secs)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        let today = today_in(tz);
+        Self::sync_goal_periods(&mut tx, user_id, tz, today, today).await?;
+        tx.commit().await.map_err(|e| e.to_string())
+    }
+
+    /// Rows in `session_starts`, finished or not.
+    #[cfg(test)]
+    pub async fn session_start_count(&self) -> i64 {
+        sqlx::query_scalar("SELECT COUNT(*) FROM session_starts")
+            .fetch_one(&self.pool)
+            .await
+            .expect("session_starts table is readable")
+    }
+
+    /// Drop starts that were never completed, e.g. the user closed the tab.
+    pub async fn delete_stale_session_starts(&self) -> Result<(), String> {
+        sqlx::query("DELETE FROM session_starts WHERE started_at < datetime('now', '-1 day')")
+            .execute(&self.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+
+    /// Pattern key of the user's most recent breathing session, if any.
+    pub async fn last_breathing_pattern(&self, user_id: &str) -> Option<String> {
+        sqlx::query_scalar::<_, String>(
+            "SELECT pattern FROM mindful_sessions
+             WHERE user_id = ? AND session_type = 'breathing' AND pattern IS NOT NULL
+             ORDER BY completed_at DESC LIMIT 1",
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await
+        .ok()
+        .flatten()
+    }
+
+    // ── Breathing patterns ─────────────────────────────────────────────────────
+
+    pub async fn get_breathing_patterns(&self, user_id: &str) -> Vec<BreathingPattern> {
+        sqlx::query_as::<_, CustomBreathingPattern>(
+            "SELECT id, name, inhale_secs, hold_in_secs, exhale_secs, hold_out_secs
+             FROM breathing_patterns WHERE user_id = ? ORDER BY created_at",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default()
+        .iter()
+        .map(CustomBreathingPattern::to_pattern)
+        .collect()
+    }
+
+    pub async fn create_breathing_pattern(
+        &self,
+        user_id: &str,
+        name: &str,
+        phases: [f64; 4],
+    ) -> Result<String, String> {
+        let count = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM breathing_patterns WHERE user_id = ?",
+        )
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        if count >= MAX_CUSTOM_PATTERNS {
+            return Err(format!(
+                "You can save up to {MAX_CUSTOM_PATTERNS} patterns. Delete one to add another."
+            ));
+        }
+
+        let id = Uuid::new_v4().to_string();
+        let [inhale, hold_in, exhale, hold_out] = phases;
+
+        sqlx::query(
+            "INSERT INTO breathing_patterns
+                 (id, user_id, name, inhale_secs, hold_in_secs, exhale_secs, hold_out_secs)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&id)
+        .bind(user_id)
+        .bind(name)
+        .bind(inhale)
+        .bind(hold_in)
+        .bind(exhale)
+        .bind(hold_out)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        Ok(format!("custom-{id}"))
+    }
+
+    pub async fn delete_breathing_pattern(&self, user_id: &str, id: &str) -> Result<(), String> {
+        sqlx::query("DELETE FROM breathing_patterns WHERE id = ? AND user_id = ?")
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+
+    // ── Meditation presets ─────────────────────────────────────────────────────
+
+    pub async fn get_meditation_presets(&self, user_id: &str) -> Vec<MeditationPreset> {
+        sqlx::query_as::<_, MeditationPreset>(
+            "SELECT id, name, duration_min, interval_min, prep_secs
+             FROM meditation_presets WHERE user_id = ? ORDER BY created_at",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default()
+    }
+
+    pub async fn create_meditation_preset(
+        &self,
+        user_id: &str,
+        name: &str,
+        settings: &MeditationSettings,
+    ) -> Result<String, String> {
+        let count = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM meditation_presets WHERE user_id = ?",
+        )
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        if count >= MAX_PRESETS {
+            return Err(format!(
+                "You can save up to {MAX_PRESETS} presets. Delete one to add another."
+            ));
+        }
+
+        let id = Uuid::new_v4().to_string();
+
+        sqlx::query(
+            "INSERT INTO meditation_presets (id, user_id, name, duration_min, interval_min, prep_secs)
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&id)
+        .bind(user_id)
+        .bind(name)
+        .bind(settings.minutes)
+        .bind(settings.interval_min)
+        .bind(settings.prep_secs)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        Ok(id)
+    }
+
+    pub async fn delete_meditation_preset(&self, user_id: &str, id: &str) -> Result<(), String> {
+        sqlx::query("DELETE FROM meditation_presets WHERE id = ? AND user_id = ?")
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+
+    // ── Journal ────────────────────────────────────────────────────────────────
+
+    /// Save an entry along with its tags and the 5-minute "journal" session
+    /// it credits. `prompt` is the text of the prompt it answers, if any.
+    pub async fn log_journal_entry(
+        &self,
+        user_id: &str,
+        mood: i64,
+        note: &str,
+        prompt: Option<&str>,
+        tags: &[String],
+    ) -> Result<(), String> {
+        let entry_id = Uuid::new_v4().to_string();
+        let key = self.data_key(user_id).await?;
+        let encrypted = key.encrypt(note, &entry_id)?;
+        let tz = self.user_timezone(user_id).await;
+        let mut tx = self.pool.begin().await.map_err(|e| e.to_string())?;
+
+        sqlx::query(
+            "INSERT INTO journal_entries (id, user_id, mood, note, prompt) VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(&entry_id)
+        .bind(user_id)
+        .bind(mood)
+        .bind(&encrypted)
+        .bind(prompt)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        Self::set_entry_tags(&mut tx, user_id, &entry_id, tags).await?;
+        Self::set_search_tokens(&mut tx, &key, &entry_id, note).await?;
+
+        sqlx::query(
+            "INSERT INTO mindful_sessions (id, user_id, session_type, duration_min, journal_entry_id)
+             VALUES (?, ?, 'journal', 5, ?)",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(user_id)
+        .bind(&entry_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        let today = today_in(tz);
+        Self::sync_goal_periods(&mut tx, user_id, tz, today, today).await?;
+        tx.commit().await.map_err(|e| e.to_string())
+    }
+
+    /// Replace an entry's tags, creating new tag names as needed and dropping
+    /// names no entry uses any more.
+    async fn set_entry_tags(
+        tx: &mut Transaction<'_, Sqlite>,
+        user_id: &str,
+        entry_id: &str,
+        tags: &[String],
+    ) -> Result<(), String> {
+        sqlx::query("DELETE FROM journal_entry_tags WHERE entry_id = ?")
+            .bind(entry_id)
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        for name in tags {
+            sqlx::query("INSERT OR IGNORE INTO journal_tags (id, user_id, name) VALUES (?, ?, ?)")
+                .bind(Uuid::new_v4().to_string())
+                .bind(user_id)
+                .bind(name)
+                .execute(&mut **tx)
+                .await
+                .map_err(|e| e.to_string())?;
+
+            sqlx::query(
+                "INSERT INTO journal_entry_tags (entry_id, tag_id)
+                 SELECT ?, id FROM journal_tags WHERE user_id = ? AND name = ?",
+            )
+            .bind(entry_id)
+            .bind(user_id)
+            .bind(name)
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| e.to_string())?;
+        }
+
+        sqlx::query(
+            "DELETE FROM journal_tags
+             WHERE user_id = ? AND id NOT IN (SELECT tag_id FROM journal_entry_tags)",
+        )
+        .bind(user_id)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+
+    /// Replace an entry's blind search index with the words of `note` (its
+    /// plaintext) and mark the entry indexed.
+    async fn set_search_tokens(
+        tx: &mut Transaction<'_, Sqlite>,
+        key: &DataKey,
+        entry_id: &str,
+        note: &str,
+    ) -> Result<(), String> {
+        sqlx::query("DELETE FROM journal_search_tokens WHERE entry_id = ?")
+            .bind(entry_id)
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        for word in index_words(note) {
+            sqlx::query(
+                "INSERT OR IGNORE INTO journal_search_tokens (token, entry_id) VALUES (?, ?)",
+            )
+            .bind(key.search_token(&word))
+            .bind(entry_id)
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| e.to_string())?;
+        }
+
+        sqlx::query("UPDATE journal_entries SET search_indexed = 1 WHERE id = ?")
+            .bind(entry_id)
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+
+    /// The user's tag names with how many entries use each, most used first.
+    pub async fn get_journal_tags(&self, user_id: &str) -> Vec<(String, i64)> {
+        sqlx::query_as::<_, (String, i64)>(
+            "SELECT t.name, COUNT(et.entry_id) AS uses
+             FROM journal_tags t
+             JOIN journal_entry_tags et ON et.tag_id = t.id
+             WHERE t.user_id = ?
+             GROUP BY t.id
+             ORDER BY uses DESC, t.name",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default()
+    }
+
+    /// One page of the user's journal matching `filter`. Notes are encrypted,
+    /// so searches find candidates through the blind index, then decrypt just
+    /// those to confirm the match, rank it and cut a snippet; everything else
+    /// is paged by the database, newest first.
+    pub async fn get_journal_entries(
+        &self,
+        user_id: &str,
+        filter: &JournalFilter,
+        page: i64,
+    ) -> JournalPage {
+        const FILTERS: &str = "FROM journal_entries je
+             WHERE je.user_id = ?1
+              AND (?2 IS NULL OR je.mood >= ?2)
+              AND (?3 IS NULL OR je.mood <= ?3)
+              AND (?4 IS NULL OR je.created_at >= ?4)
+              AND (?5 IS NULL OR je.created_at < ?5)
+              AND (?6 IS NULL OR EXISTS (
+                  SELECT 1 FROM journal_entry_tags et
+                  JOIN journal_tags t ON t.id = et.tag_id
+                  WHERE et.entry_id = je.id AND t.name = ?6))";
+
+        let tz = self.user_timezone(user_id).await;
+        let from_date = filter.from.map(|d| day_start_utc(d, tz));
+        let to_date = filter.to.map(|d| day_start_utc(d + Duration::days(1), tz));
+        let offset = (page.max(1) - 1) * PAGE_SIZE;
+        let terms = filter.search_terms();
+
+        // Without a search the database can page; with one it returns every
+        // entry holding a token for each term (LIMIT -1) for matching below.
+        let (limit, skip) = if terms.is_empty() {
+            (PAGE_SIZE, offset)
+        } else {
+            (-1, 0)
+        };
+        let tokens: BTreeSet<Vec<u8>> = if terms.is_empty() {
+            BTreeSet::new()
+        } else {
+            // No key means no notes were ever written
+            let Ok(Some(key)) = self.existing_data_key(user_id).await else {
+                return JournalPage::default();
+            };
+            terms
+                .iter()
+                .map(|t| key.search_token(indexed_prefix(t)))
+                .collect()
+        };
+        let token_filter = if tokens.is_empty() {
+            String::new()
+        } else {
+            let placeholders: Vec<String> =
+                (0..tokens.len()).map(|i| format!("?{}", i + 9)).collect();
+            format!(
+                "AND je.id IN (
+                    SELECT entry_id FROM journal_search_tokens
+                    WHERE token IN ({})
+                    GROUP BY entry_id HAVING COUNT(*) = {})",
+                placeholders.join(", "),
+                tokens.len()
+            )
+        };
+
+        let sql = format!(
+            "SELECT je.id, je.mood, je.note, je.created_at, je.updated_at, je.prompt,
+                    ({TAG_LIST}) AS tag_list
+             {FILTERS}
+              {token_filter}
+             ORDER BY je.created_at DESC, je.id
+             LIMIT ?7 OFFSET ?8"
+        );
+        let mut query = sqlx::query_as::<_, JournalEntry>(&sql)
+            .bind(user_id)
+            .bind(filter.mood_min)
+            .bind(filter.mood_max)
+            .bind(&from_date)
+            .bind(&to_date)
+            .bind(&filter.tag)
+            .bind(limit)
+            .bind(skip);
+        for token in &tokens {
+            query = query.bind(token);
+        }
+        let mut entries = query.fetch_all(&self.pool).await.unwrap_or_default();
+
+        self.decrypt_notes(user_id, &mut entries).await;
+        for entry in entries.iter_mut() {
+            localize_entry(entry, tz);
+        }
+
+        if terms.is_empty() {
+            let total = sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) {FILTERS}"))
+                .bind(user_id)
+                .bind(filter.mood_min)
+                .bind(filter.mood_max)
+                .bind(&from_date)
+                .bind(&to_date)
+                .bind(&filter.tag)
+                .fetch_one(&self.pool)
+                .await
+                .unwrap_or(0);
+            return JournalPage { entries, total };
+        }
+
+        let mut matches: Vec<(usize, JournalEntry)> = entries
+            .into_iter()
+            .filter_map(|mut entry| {
+                let (score, snippet) = search_snippet(&entry.note, &terms)?;
+                entry.snippet = Some(snippet);
+                Some((score, entry))
+            })
+            .collect();
+        // Stable, so equally good matches stay newest first
+        matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+
+        JournalPage {
+            total: matches.len() as i64,
+            entries: matches
+                .into_iter()
+                .skip(offset as usize)
+                .take(PAGE_SIZE as usize)
+                .map(|(_, entry)| entry)
+                .collect(),
+        }
+    }
+
+    pub async fn get_journal_entry(&self, user_id: &str, id: &str) -> Option<JournalEntry> {
+        let mut entry = sqlx::query_as::<_, JournalEntry>(&format!(
+            "SELECT je.id, je.mood, je.note, je.created_at, je.updated_at, je.prompt,
+                    ({TAG_LIST}) AS tag_list
+             FROM journal_entries je
+             WHERE je.id = ? AND je.user_id = ?"
+        ))
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await
+        .ok()
+        .flatten()?;
+
+        // Refuse rather than show a placeholder that could be saved over the note
+        let key = self.existing_data_key(user_id).await;
+        match key.and_then(|key| decrypt_note(key.as_ref(), &entry.note, &entry.id)) {
+            Ok(note) => entry.note = note,
+            Err(e) => {
+                eprintln!("Couldn't decrypt journal entry {}: {e}", entry.id);
+                return None;
+            }
+        }
+        localize_entry(&mut entry, self.user_timezone(user_id).await);
+        Some(entry)
+    }
+
+    pub async fn update_journal_entry(
+        &self,
+        user_id: &str,
+        id: &str,
+        mood: i64,
+        note: &str,
+        tags: &[String],
+    ) -> Result<(), String> {
+        let key = self.data_key(user_id).await?;
+        let encrypted = key.encrypt(note, id)?;
+        let mut tx = self.pool.begin().await.map_err(|e| e.to_string())?;
+
+        let rows = sqlx::query(
+            "UPDATE journal_entries SET mood = ?, note = ?, updated_at = datetime('now')
+             WHERE id = ? AND user_id = ?",
+        )
+        .bind(mood)
+        .bind(&encrypted)
+        .bind(id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        if rows.rows_affected() == 0 {
+            return Err("That journal entry couldn't be found.".to_string());
+        }
+
+        Self::set_entry_tags(&mut tx, user_id, id, tags).await?;
+        Self::set_search_tokens(&mut tx, &key, id, note).await?;
+
+        tx.commit().await.map_err(|e| e.to_string())
+    }
+
+    /// Delete an entry, its tags and the session it credited, so streaks and
+    /// totals no longer count it.
+    pub async fn delete_journal_entry(&self, user_id: &str, id: &str) -> Result<(), String> {
+        let tz = self.user_timezone(user_id).await;
+        let mut tx = self.pool.begin().await.map_err(|e| e.to_string())?;
+
+        let created_at = sqlx::query_scalar::<_, String>(
+            "SELECT created_at FROM journal_entries WHERE id = ? AND user_id = ?",
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "That journal entry couldn't be found.".to_string())?;
+
+        Self::set_entry_tags(&mut tx, user_id, id, &[]).await?;
+
+        sqlx::query("DELETE FROM journal_search_tokens WHERE entry_id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        sqlx::query("DELETE FROM journal_entries WHERE id = ? AND user_id = ?")
+            .bind(id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        sqlx::query("DELETE FROM mindful_sessions WHERE journal_entry_id = ? AND user_id = ?")
+            .bind(id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let today = today_in(tz);
+        let changed = local_date(&created_at, tz).unwrap_or(today);
+        Self::forget_streak_from(&mut tx, user_id, changed).await?;
+        Self::sync_goal_periods(&mut tx, user_id, tz, today, changed).await?;
+        tx.commit().await.map_err(|e| e.to_string())
+    }
+
+    // ── Note encryption ────────────────────────────────────────────────────────
+
+    /// The user's data key, if one has been created.
+    async fn existing_data_key(&self, user_id: &str) -> Result<Option<DataKey>, String> {
+        let row = sqlx::query_as::<_, (Vec<u8>, String)>(
+            "SELECT wrapped_key, master_key_id FROM user_data_keys WHERE user_id = ?",
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        row.map(|(wrapped, master_key_id)| self.keyring.unwrap(&wrapped, &master_key_id))
+            .transpose()
+    }
+
+    /// The user's data key, created on first use.
+    async fn data_key(&self, user_id: &str) -> Result<DataKey, String> {
+        if let Some(key) = self.existing_data_key(user_id).await? {
+            return Ok(key);
+        }
+
+        let (wrapped, master_key_id) = self.keyring.wrap(&DataKey::generate())?;
+        // If a concurrent request got there first, keep its key
+        sqlx::query(
+            "INSERT OR IGNORE INTO user_data_keys (user_id, wrapped_key, master_key_id)
+             VALUES (?, ?, ?)",
+        )
+        .bind(user_id)
+        .bind(&wrapped)
+        .bind(&master_key_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        self.existing_data_key(user_id)
+            .await?
+            .ok_or_else(|| "Couldn't create an encryption key for your journal.".to_string())
+    }
+
+    /// Decrypt notes for display. Any that can't be are logged and shown as
+    /// [`UNREADABLE_NOTE`].
+    async fn decrypt_notes(&self, user_id: &str, entries: &mut [JournalEntry]) {
+        if !entries.iter().any(|e| crypto::is_encrypted(&e.note)) {
+            return;
+        }
+        let key = self.existing_data_key(user_id).await;
+        for entry in entries.iter_mut() {
+            let note = key
+                .as_ref()
+                .map_err(String::clone)
+                .and_then(|key| decrypt_note(key.as_ref(), &entry.note, &entry.id));
+            entry.note = note.unwrap_or_else(|e| {
+                eprintln!("Couldn't decrypt journal entry {}: {e}", entry.id);
+                UNREADABLE_NOTE.to_string()
+            });
+        }
+    }
+
+    /// Encrypt notes written before encryption was introduced. Returns how
+    /// many were encrypted. The pool runs with `secure_delete`, so the
+    /// plaintext they replace is zeroed rather than left in free pages.
+    pub async fn encrypt_plaintext_notes(&self) -> Result<usize, String> {
+        let rows = sqlx::query_as::<_, (String, String, String)>(
+            "SELECT id, user_id, note FROM journal_entries WHERE note NOT LIKE ? || '%'",
+        )
+        .bind(ENCRYPTED_PREFIX)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        let mut keys: HashMap<String, DataKey> = HashMap::new();
+        for (id, user_id, note) in &rows {
+            if !keys.contains_key(user_id) {
+                keys.insert(user_id.clone(), self.data_key(user_id).await?);
+            }
+            let encrypted = keys[user_id].encrypt(note, id)?;
+
+            // Only if untouched since we read it
+            sqlx::query("UPDATE journal_entries SET note = ? WHERE id = ? AND note = ?")
+                .bind(&encrypted)
+                .bind(id)
+                .bind(note)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+
+        Ok(rows.len())
+    }
+
+    /// Build the blind search index for entries written before it existed.
+    /// Returns how many entries were indexed.
+    pub async fn index_journal_notes(&self) -> Result<usize, String> {
+        let rows = sqlx::query_as::<_, (String, String, String)>(
+            "SELECT id, user_id, note FROM journal_entries WHERE search_indexed = 0",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        let mut keys: HashMap<String, DataKey> = HashMap::new();
+        let mut indexed = 0;
+        for (id, user_id, stored) in &rows {
+            if !keys.contains_key(user_id) {
+                keys.insert(user_id.clone(), self.data_key(user_id).await?);
+            }
+            let key = &keys[user_id];
+            let note = match key.decrypt(stored, id) {
+                Ok(note) => note,
+                Err(e) => {
+                    eprintln!("Couldn't index journal entry {id}: {e}");
+                    continue;
+                }
+            };
+
+            let mut tx = self.pool.begin().await.map_err(|e| e.to_string())?;
+            // Only if untouched since we read it; an edit indexes itself
+            let unchanged = sqlx::query(
+                "SELECT 1 FROM journal_entries WHERE id = ? AND note = ? AND search_indexed = 0",
+            )
+            .bind(id)
+            .bind(stored)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+            if unchanged.is_none() {
+                continue;
+            }
+            Self::set_search_tokens(&mut tx, key, id, &note).await?;
+            tx.commit().await.map_err(|e| e.to_string())?;
+            indexed += 1;
+        }
+
+        Ok(indexed)
+    }
+
+    /// Re-wrap every data key that isn't under the current master key, so the
+    /// previous master keys can be retired. Returns how many keys were
+    /// re-wrapped and the errors for any that couldn't be unwrapped.
+    pub async fn rewrap_data_keys(&self) -> Result<(usize, Vec<String>), String> {
+        let rows = sqlx::query_as::<_, (String, Vec<u8>, String)>(
+            "SELECT user_id, wrapped_key, master_key_id FROM user_data_keys
+             WHERE master_key_id != ?",
+        )
+        .bind(self.keyring.current_id())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        let mut rewrapped = 0;
+        let mut failures = Vec::new();
+        for (user_id, wrapped, master_key_id) in rows {
+            let key = match self.keyring.unwrap(&wrapped, &master_key_id) {
+                Ok(key) => key,
+                Err(e) => {
+                    failures.push(format!("user {user_id}: {e}"));
+                    continue;
+                }
+            };
+            let (new_wrapped, new_id) = self.keyring.wrap(&key)?;
+
+            sqlx::query(
+                "UPDATE user_data_keys
+                 SET wrapped_key = ?, master_key_id = ?, rotated_at = datetime('now')
+                 WHERE user_id = ? AND master_key_id = ?",
+            )
+            .bind(&new_wrapped)
+            .bind(&new_id)
+            .bind(&user_id)
+            .bind(&master_key_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+            rewrapped += 1;
+        }
+
+        Ok((rewrapped, failures))
+    }
+
+    // ── Stats ──────────────────────────────────────────────────────────────────
+
+    /// Today's count, streak, freezes and total. Days follow the user's timezone.
+    pub async fn get_stats(&self, user_id: &str) -> DashboardStats {
+        let tz = self.user_timezone(user_id).await;
+        let today = today_in(tz);
+
+        let sessions_today = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM mindful_sessions
+             WHERE user_id = ? AND completed_at >= ? AND completed_at < ?",
+        )
+        .bind(user_id)
+        .bind(day_start_utc(today, tz))
+        .bind(day_start_utc(today + Duration::days(1), tz))
+        .fetch_one(&self.pool)
+        .await
+        .unwrap_or(0);
+
+        let total_minutes = sqlx::query_scalar::<_, i64>(
+            "SELECT COALESCE(SUM(duration_min), 0) FROM mindful_sessions WHERE user_id = ?",
+        )
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await
+        .unwrap_or(0);
+
+        let streak = self.streak_tally(user_id, tz, today).await;
+
+        DashboardStats {
+            sessions_today,
+            streak: streak.current,
+            longest_streak: streak.longest,
+            freezes: streak.freezes,
+            total_minutes,
+        }
+    }
+
+    /// The streak replayed over every day with a session, in the user's
+    /// timezone.
+    pub async fn get_streak_history(&self, user_id: &str) -> StreakHistory {
+        let tz = self.user_timezone(user_id).await;
+        self.streak_history(user_id, tz, today_in(tz)).await
+    }
+
+    async fn streak_history(&self, user_id: &str, tz: Tz, today: NaiveDate) -> StreakHistory {
+        let times = self.session_times(user_id, None).await;
+        StreakHistory::from_times(&times, tz, today)
+    }
+
+    /// Today's streak, replayed from the user's checkpoint when there is one
+    /// for their timezone instead of from their first session. The
+    /// checkpoint then moves up to yesterday.
+    async fn streak_tally(&self, user_id: &str, tz: Tz, today: NaiveDate) -> StreakTally {
+        let checkpoint = sqlx::query_as::<_, (String, i64, i64, i64, i64)>(
+            "SELECT day, current, longest, freezes, frozen_days FROM streak_checkpoints
+             WHERE user_id = ? AND timezone = ?",
+        )
+        .bind(user_id)
+        .bind(tz.name())
+        .fetch_optional(&self.pool)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|(day, current, longest, freezes, frozen_days)| {
+            let day = NaiveDate::parse_from_str(&day, "%Y-%m-%d").ok()?;
+            let tally = StreakTally {
+                current,
+                longest,
+                freezes,
+                frozen_days,
+            };
+            (day < today).then_some((day + Duration::days(1), tally))
+        });
+
+        let history = match checkpoint {
+            Some((from, tally)) => {
+                let dates: Vec<NaiveDate> = self
+                    .session_times(user_id, Some(day_start_utc(from, tz)))
+                    .await
+                    .iter()
+                    .map(|t| t.with_timezone(&tz).date_naive())
+                    .collect();
+                StreakHistory::resume(tally, from, &dates, today)
+            }
+            None => self.streak_history(user_id, tz, today).await,
+        };
+
+        let yesterday = today - Duration::days(1);
+        if checkpoint.is_none_or(|(from, _)| from <= yesterday) {
+            let settled = history.settled;
+            if let Err(e) = sqlx::query(
+                "INSERT INTO streak_checkpoints
+                     (user_id, day, timezone, current, longest, freezes, frozen_days)
+                 VALUES (?, ?, ?, ?, ?, ?, ?)
+                 ON CONFLICT(user_id) DO UPDATE SET
+                     day = excluded.day, timezone = excluded.timezone,
+                     current = excluded.current, longest = excluded.longest,
+                     freezes = excluded.freezes, frozen_days = excluded.frozen_days",
+            )
+            .bind(user_id)
+            .bind(yesterday.to_string())
+            .bind(tz.name())
+            .bind(settled.current)
+            .bind(settled.longest)
+            .bind(settled.freezes)
+            .bind(settled.frozen_days)
+            .execute(&self.pool)
+            .await
+            {
+                eprintln!("Failed to save streak checkpoint for {user_id}: {e}");
+            }
+        }
+
+        history.tally()
+    }
+
+    /// Drop the streak checkpoint if it covers `changed`, after activity on
+    /// that day was removed.
+    async fn forget_streak_from(
+        tx: &mut Transaction<'_, Sqlite>,
+        user_id: &str,
+        changed: NaiveDate,
+    ) -> Result<(), String> {
+        sqlx::query("DELETE FROM streak_checkpoints WHERE user_id = ? AND day >= ?")
+            .bind(user_id)
+            .bind(changed.to_string())
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+
+    /// When the user's sessions were completed, from `since` (UTC) if given.
+    async fn session_times(&self, user_id: &str, since: Option<String>) -> Vec<DateTime<Utc>> {
+        let timestamps = sqlx::query_scalar::<_, String>(
+            "SELECT completed_at FROM mindful_sessions
+             WHERE user_id = ?1 AND (?2 IS NULL OR completed_at >= ?2)",
+        )
+        .bind(user_id)
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default();
+
+        timestamps
+            .iter()
+            .filter_map(|ts| parse_timestamp(ts))
+            .collect()
+    }
+
+    /// Minutes, sessions and average mood for every day from `from` to `to`
+    /// inclusive, in the user's timezone. Days without activity are included.
+    pub async fn get_daily_activity(
+        &self,
+        user_id: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Vec<DayActivity> {
+        let tz = self.user_timezone(user_id).await;
+        let start = day_start_utc(from, tz);
+        let end = day_start_utc(to + Duration::days(1), tz);
+
+        let sessions = sqlx::query_as::<_, (String, String, i64)>(
+            "SELECT completed_at, session_type, duration_min FROM mindful_sessions
+             WHERE user_id = ? AND completed_at >= ? AND completed_at < ?",
+        )
+        .bind(user_id)
+        .bind(&start)
+        .bind(&end)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default();
+
+        let moods = sqlx::query_as::<_, (String, i64)>(
+            "SELECT created_at, mood FROM journal_entries
+             WHERE user_id = ? AND created_at >= ? AND created_at < ?",
+        )
+        .bind(user_id)
+        .bind(&start)
+        .bind(&end)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default();
+
+        let mut days: Vec<DayActivity> = from
+            .iter_days()
+            .take_while(|d| *d <= to)
+            .map(|day| DayActivity {
+                day,
+                minutes: 0,
+                type_minutes: [0; HISTORY_TYPES.len()],
+                sessions: 0,
+                mood: None,
+            })
+            .collect();
+        let index = |ts: &str| {
+            let date = local_date(ts, tz)?;
+            usize::try_from((date - from).num_days()).ok()
+        };
+
+        for (completed_at, session_type, minutes) in &sessions {
+            if let Some(day) = index(completed_at).and_then(|i| days.get_mut(i)) {
+                day.minutes += minutes;
+                if let Some(t) = HISTORY_TYPES.iter().position(|(t, _)| t == session_type) {
+                    day.type_minutes[t] += minutes;
+                }
+                if session_type != "journal" {
+                    day.sessions += 1;
+                }
+            }
+        }
+
+        let mut entries = vec![0i64; days.len()];
+        for (created_at, mood) in &moods {
+            if let Some(i) = index(created_at).filter(|&i| i < days.len()) {
+                // Running mean
+                entries[i] += 1;
+                let mean = days[i].mood.unwrap_or(0.0);
+                days[i].mood = Some(mean + (*mood as f64 - mean) / entries[i] as f64);
+            }
+        }
+
+        days
+    }
+
+    // ── Goals ──────────────────────────────────────────────────────────────────
+
+    pub async fn get_goals(&self, user_id: &str) -> Vec<Goal> {
+        sqlx::query_as::<_, Goal>(
+            "SELECT goal, target, set_at FROM user_goals WHERE user_id = ? ORDER BY goal",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default()
+    }
+
+    /// Set or clear (`None`) each goal. A goal keeps its original `set_at`
+    /// when only its target changes.
+    pub async fn set_goals(
+        &self,
+        user_id: &str,
+        targets: &[(GoalKind, Option<i64>)],
+    ) -> Result<(), String> {
+        let tz = self.user_timezone(user_id).await;
+        let mut tx = self.pool.begin().await.map_err(|e| e.to_string())?;
+
+        for &(kind, target) in targets {
+            match target {
+                Some(target) => {
+                    sqlx::query(
+                        "INSERT INTO user_goals (user_id, goal, target) VALUES (?, ?, ?)
+                         ON CONFLICT(user_id, goal) DO UPDATE SET target = excluded.target",
+                    )
+                    .bind(user_id)
+                    .bind(kind.key())
+                    .bind(target)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                }
+                None => {
+                    sqlx::query("DELETE FROM user_goals WHERE user_id = ? AND goal = ?")
+                        .bind(user_id)
+                        .bind(kind.key())
+                        .execute(&mut *tx)
+                        .await
+                        .map_err(|e| e.to_string())?;
+                }
+            }
+        }
+
+        let today = today_in(tz);
+        Self::sync_goal_periods(&mut tx, user_id, tz, today, today).await?;
+        tx.commit().await.map_err(|e| e.to_string())
+    }
+
+    /// Bring `goal_periods` up to date, as part of a write that changes
+    /// goals or activity: every period from the later of when each goal was
+    /// set and its last recorded period, through the current one. `changed`
+    /// is the earliest day whose activity the write touched, so removing
+    /// something from a past period recomputes that period too. Past
+    /// periods keep the target they were recorded with; the current one
+    /// follows the goal as it is now.
+    async fn sync_goal_periods(
+        tx: &mut Transaction<'_, Sqlite>,
+        user_id: &str,
+        tz: Tz,
+        today: NaiveDate,
+        changed: NaiveDate,
+    ) -> Result<(), String> {
+        let goals = sqlx::query_as::<_, Goal>(
+            "SELECT goal, target, set_at FROM user_goals WHERE user_id = ? ORDER BY goal",
+        )
+        .bind(user_id)
+        .fetch_all(&mut **tx)
+        .await
+        .map_err(|e| e.to_string())?;
+        let mut plans = Vec::new();
+
+        for goal in &goals {
+            let Some(kind) = goal.kind() else { continue };
+            let latest = sqlx::query_scalar::<_, Option<String>>(
+                "SELECT MAX(period_start) FROM goal_periods WHERE user_id = ? AND goal = ?",
+            )
+            .bind(user_id)
+            .bind(kind.key())
+            .fetch_one(&mut **tx)
+            .await
+            .map_err(|e| e.to_string())?
+            .and_then(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok());
+
+            let set = local_date(&goal.set_at, tz).unwrap_or(today);
+            let first = latest
+                .map_or(set, |l| l.min(changed).max(set))
+                .max(today - Duration::days(MAX_GOAL_BACKFILL_DAYS));
+            plans.push((kind, goal.target, kind.period_start(first)));
+        }
+
+        let Some(earliest) = plans.iter().map(|&(_, _, start)| start).min() else {
+            return Ok(());
+        };
+        let from = day_start_utc(earliest, tz);
+
+        let sessions = sqlx::query_as::<_, (String, String, i64)>(
+            "SELECT completed_at, session_type, duration_min FROM mindful_sessions
+             WHERE user_id = ? AND completed_at >= ?",
+        )
+        .bind(user_id)
+        .bind(&from)
+        .fetch_all(&mut **tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        let entries = sqlx::query_scalar::<_, String>(
+            "SELECT created_at FROM journal_entries WHERE user_id = ? AND created_at >= ?",
+        )
+        .bind(user_id)
+        .bind(&from)
+        .fetch_all(&mut **tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        let sessions: Vec<(NaiveDate, bool, i64)> = sessions
+            .iter()
+            .filter_map(|(at, kind, minutes)| {
+                Some((local_date(at, tz)?, kind == "journal", *minutes))
+            })
+            .collect();
+        let entries: Vec<NaiveDate> = entries.iter().filter_map(|at| local_date(at, tz)).collect();
+
+        for (kind, target, first) in plans {
+            let current = kind.period_start(today);
+            for start in kind.period_starts(first, today) {
+                sqlx::query(
+                    "INSERT INTO goal_periods (user_id, goal, period_start, target, achieved)
+                     VALUES (?1, ?2, ?3, ?4, ?5)
+                     ON CONFLICT(user_id, goal, period_start) DO UPDATE SET
+                         achieved = excluded.achieved,
+                         target = CASE WHEN ?6 THEN excluded.target ELSE goal_periods.target END",
+                )
+                .bind(user_id)
+                .bind(kind.key())
+                .bind(start.to_string())
+                .bind(target)
+                .bind(kind.progress(start, &sessions, &entries))
+                .bind(start == current)
+                .execute(&mut **tx)
+                .await
+                .map_err(|e| e.to_string())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Progress on each goal this period and how the previous period went.
+    /// Periods are recorded when activity or goals change, so one nothing
+    /// has happened in yet is shown as empty rather than written here.
+    pub async fn get_goal_overview(&self, user_id: &str) -> GoalOverview {
+        let tz = self.user_timezone(user_id).await;
+        let today = today_in(tz);
+
+        let mut overview = GoalOverview::default();
+        for goal in self.get_goals(user_id).await {
+            let Some(kind) = goal.kind() else { continue };
+            let current = kind.period_start(today);
+            let previous = kind.previous_period(current);
+            let first = kind.period_start(local_date(&goal.set_at, tz).unwrap_or(today));
+
+            let periods = sqlx::query_as::<_, GoalPeriod>(
+                "SELECT goal, period_start, target, achieved FROM goal_periods
+                 WHERE user_id = ? AND goal = ? AND period_start IN (?, ?)",
+            )
+            .bind(user_id)
+            .bind(kind.key())
+            .bind(current.to_string())
+            .bind(previous.to_string())
+            .fetch_all(&self.pool)
+            .await
+            .unwrap_or_default();
+            let recorded = |start: NaiveDate| {
+                periods
+                    .iter()
+                    .find(|p| p.period_start == start.to_string())
+                    .cloned()
+                    .unwrap_or_else(|| GoalPeriod::empty(kind, start, goal.target))
+            };
+
+            overview.current.push(recorded(current));
+            if previous >= first {
+                overview.previous.push(recorded(previous));
+            }
+        }
+
+        overview
+    }
+
+    /// The latest `per_goal` periods of each goal ever set, newest first,
+    /// including the one in progress. Periods since the last recorded
+    /// activity are shown as empty.
+    pub async fn get_goal_history(&self, user_id: &str, per_goal: i64) -> Vec<GoalPeriod> {
+        let tz = self.user_timezone(user_id).await;
+        let today = today_in(tz);
+
+        let mut periods = sqlx::query_as::<_, GoalPeriod>(
+            "SELECT goal, period_start, target, achieved FROM (
+                 SELECT goal, period_start, target, achieved,
+                        ROW_NUMBER() OVER (PARTITION BY goal ORDER BY period_start DESC) AS n
+                 FROM goal_periods WHERE user_id = ?
+             )
+             WHERE n <= ?
+             ORDER BY goal, period_start DESC",
+        )
+        .bind(user_id)
+        .bind(per_goal)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default();
+
+        for goal in self.get_goals(user_id).await {
+            let Some(kind) = goal.kind() else { continue };
+            let latest = periods
+                .iter()
+                .find(|p| p.goal == goal.goal)
+                .and_then(GoalPeriod::start_date);
+            let first = match latest {
+                Some(latest) => kind.next_period(latest),
+                None => local_date(&goal.set_at, tz).unwrap_or(today),
+            }
+            .max(today - Duration::days(MAX_GOAL_BACKFILL_DAYS));
+            periods.extend(
+                kind.period_starts(first, today)
+                    .into_iter()
+                    .map(|start| GoalPeriod::empty(kind, start, goal.target)),
+            );
+        }
+
+        periods.sort_by(|a, b| {
+            a.goal
+                .cmp(&b.goal)
+                .then_with(|| b.period_start.cmp(&a.period_start))
+        });
+        let mut shown: HashMap<String, i64> = HashMap::new();
+        periods.retain(|p| {
+            let count = shown.entry(p.goal.clone()).or_default();
+            *count += 1;
+            *count <= per_goal
+        });
+        periods
+    }
+
+    // ── Achievements ───────────────────────────────────────────────────────────
+
+    async fn achievement_stats(&self, user_id: &str) -> Result<AchievementStats, String> {
+        let (sessions, total_minutes) = sqlx::query_as::<_, (i64, i64)>(
+            "SELECT COALESCE(SUM(session_type != 'journal'), 0), COALESCE(SUM(duration_min), 0)
+             FROM mindful_sessions WHERE user_id = ?",
+        )
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        let journal_entries =
+            sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM journal_entries WHERE user_id = ?")
+                .bind(user_id)
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| e.to_string())?;
+
+        let session_types = sqlx::query_scalar::<_, String>(
+            "SELECT DISTINCT session_type FROM mindful_sessions WHERE user_id = ?",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        let tz = self.user_timezone(user_id).await;
+        let streak = self.streak_tally(user_id, tz, today_in(tz)).await;
+
+        Ok(AchievementStats {
+            sessions,
+            total_minutes,
+            journal_entries,
+            session_types,
+            longest_streak: streak.longest,
+            frozen_days: streak.frozen_days,
+        })
+    }
+
+    /// Run every achievement rule and record any newly met. Returns the
+    /// achievements unlocked by this call.
+    pub async fn evaluate_achievements(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<&'static Achievement>, String> {
+        let stats = self.achievement_stats(user_id).await?;
+        let mut unlocked = Vec::new();
+
+        for achievement in stats.earned() {
+            let inserted = sqlx::query(
+                "INSERT OR IGNORE INTO user_achievements (user_id, achievement) VALUES (?, ?)",
+            )
+            .bind(user_id)
+            .bind(achievement.key)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| e.to_string())?
+            .rows_affected();
+
+            if inserted > 0 {
+                unlocked.push(achievement);
+            }
+        }
+
+        Ok(unlocked)
+    }
+
+    /// Earned badges, oldest first, with unlock times in the user's timezone.
+    pub async fn get_achievements(&self, user_id: &str) -> Vec<UnlockedAchievement> {
+        let tz = self.user_timezone(user_id).await;
+        let mut unlocked = sqlx::query_as::<_, UnlockedAchievement>(
+            "SELECT achievement, unlocked_at FROM user_achievements
+             WHERE user_id = ? ORDER BY unlocked_at, achievement",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default();
+
+        for a in &mut unlocked {
+            a.unlocked_at = to_local(&a.unlocked_at, tz);
+        }
+        unlocked
+    }
+
+    /// Badges not yet announced, marking them announced.
+    pub async fn take_new_achievements(&self, user_id: &str) -> Vec<&'static Achievement> {
+        let keys = sqlx::query_scalar::<_, String>(
+            "UPDATE user_achievements SET notified_at = datetime('now')
+             WHERE user_id = ? AND notified_at IS NULL
+             RETURNING achievement",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default();
 
-        let total_minutes = sqlx::query_scalar::<_, i64>(
-            "SELECT COALESCE(SUM(duration_min), 0) FROM mindful_sessions WHERE user_id = ?",
+        // Keep the catalogue's order rather than whatever RETURNING gives
+        ACHIEVEMENTS
+            .iter()
+            .filter(|a| keys.iter().any(|k| k == a.key))
+            .collect()
+    }
+
+    // ── Insights ───────────────────────────────────────────────────────────────
+
+    /// Average journal mood for each day since `since` that has entries, with
+    /// how many breathing or meditation sessions were completed that day.
+    /// Days follow the user's timezone.
+    pub async fn get_mood_days(&self, user_id: &str, since: NaiveDate) -> Vec<MoodDay> {
+        let tz = self.user_timezone(user_id).await;
+        let from = day_start_utc(since, tz);
+
+        let moods = sqlx::query_as::<_, (String, i64)>(
+            "SELECT created_at, mood FROM journal_entries WHERE user_id = ? AND created_at >= ?",
         )
         .bind(user_id)
-        .fetch_one(&self.pool)
+        .bind(&from)
+        .fetch_all(&self.pool)
         .await
-        .unwrap_or(0);
+        .unwrap_or_default();
 
-        // Fetch distinct session dates newest-first to calculate streak
-        let dates = sqlx::query_scalar::<_, String>(
-            "SELECT DISTINCT date(completed_at) FROM mindful_sessions
-             WHERE user_id = ?
-             ORDER BY 1 DESC",
+        let sessions = sqlx::query_scalar::<_, String>(
+            "SELECT completed_at FROM mindful_sessions
+             WHERE user_id = ? AND session_type != 'journal' AND completed_at >= ?",
         )
         .bind(user_id)
+        .bind(&from)
         .fetch_all(&self.pool)
         .await
         .unwrap_or_default();
 
-        let streak = calculate_streak(&dates);
-
-        DashboardStats {
-            sessions_today,
-            streak,
-            total_minutes,
+        let mut days: BTreeMap<NaiveDate, MoodDay> = BTreeMap::new();
+        for (created_at, mood) in moods {
+            if let Some(day) = local_date(&created_at, tz) {
+                let entry = days.entry(day).or_insert(MoodDay {
+                    day,
+                    mood: 0.0,
+                    entries: 0,
+                    sessions: 0,
+                });
+                // Running mean
+                entry.entries += 1;
+                entry.mood += (mood as f64 - entry.mood) / entry.entries as f64;
+            }
+        }
+        for completed_at in sessions {
+            if let Some(day) = local_date(&completed_at, tz).and_then(|d| days.get_mut(&d)) {
+                day.sessions += 1;
+            }
         }
+
+        days.into_values().collect()
     }
 
-    pub async fn get_weekly_minutes(&self, user_id: &str) -> WeeklyMinutes {
-        let rows = sqlx::query_as::<_, (String, i64)>(
-            "SELECT date(completed_at) as d, COALESCE(SUM(duration_min), 0) as total_min
-             FROM mindful_sessions
-             WHERE user_id = ? AND date(completed_at) >= date('now', '-6 days')
-             GROUP BY d
-             ORDER BY d ASC",
-        )
+    // ── History ────────────────────────────────────────────────────────────────
+
+    /// Sessions and journal entries newest first. The 5-minute sessions that
+    /// journal entries credit are left out in favour of the entries themselves.
+    pub async fn get_history(&self, user_id: &str, filter: &HistoryFilter) -> HistoryPage {
+        const TIMELINE: &str = "
+            WITH timeline AS (
+                SELECT id, session_type AS item_type, duration_min, pattern, cycles,
+                       NULL AS mood, NULL AS note, completed_at AS occurred_at
+                FROM mindful_sessions
+                WHERE user_id = ?1 AND session_type != 'journal'
+                UNION ALL
+                SELECT id, 'journal', 5, NULL, NULL, mood, note, created_at
+                FROM journal_entries
+                WHERE user_id = ?1
+            )
+            SELECT {columns} FROM timeline
+            WHERE (?2 IS NULL OR item_type = ?2)
+              AND (?3 IS NULL OR occurred_at >= ?3)
+              AND (?4 IS NULL OR occurred_at < ?4)";
+
+        let tz = self.user_timezone(user_id).await;
+        let from = filter.from.map(|d| day_start_utc(d, tz));
+        let to = filter.to.map(|d| day_start_utc(d + Duration::days(1), tz));
+
+        let total = sqlx::query_scalar::<_, i64>(&TIMELINE.replace("{columns}", "COUNT(*)"))
+            .bind(user_id)
+            .bind(&filter.item_type)
+            .bind(&from)
+            .bind(&to)
+            .fetch_one(&self.pool)
+            .await
+            .unwrap_or(0);
+
+        let mut items = sqlx::query_as::<_, HistoryItem>(&format!(
+            "{} ORDER BY occurred_at DESC, id LIMIT ?5 OFFSET ?6",
+            TIMELINE.replace("{columns}", "*")
+        ))
         .bind(user_id)
+        .bind(&filter.item_type)
+        .bind(&from)
+        .bind(&to)
+        .bind(PAGE_SIZE)
+        .bind((filter.page.max(1) - 1) * PAGE_SIZE)
         .fetch_all(&self.pool)
         .await
         .unwrap_or_default();
 
-        let mut result: WeeklyMinutes = [0; 7];
-        let today = Local::now().date_naive();
-
-        for (date_str, minutes) in rows {
-            if let Ok(date) = NaiveDate::parse_from_str(&date_str, "%Y-%m-%d") {
-                let days_ago = (today - date).num_days();
-                if (0..7).contains(&days_ago) {
-                    result[(6 - days_ago) as usize] = minutes;
+        if items
+            .iter()
+            .any(|i| i.note.as_deref().is_some_and(crypto::is_encrypted))
+        {
+            let key = self.existing_data_key(user_id).await;
+            for item in items.iter_mut() {
+                if let Some(note) = &item.note {
+                    let note = key
+                        .as_ref()
+                        .map_err(String::clone)
+                        .and_then(|key| decrypt_note(key.as_ref(), note, &item.id));
+                    item.note = Some(note.unwrap_or_else(|_| UNREADABLE_NOTE.to_string()));
                 }
             }
         }
 
-        result
+        for item in items.iter_mut() {
+            item.occurred_at = to_local(&item.occurred_at, tz);
+        }
+
+        HistoryPage { items, total }
+    }
+
+    /// Remove a logged session. Streaks and totals are computed from
+    /// `mindful_sessions`, so they update straight away.
+    pub async fn delete_session(&self, user_id: &str, id: &str) -> Result<(), String> {
+        let tz = self.user_timezone(user_id).await;
+        let mut tx = self.pool.begin().await.map_err(|e| e.to_string())?;
+
+        let completed_at = sqlx::query_scalar::<_, String>(
+            "DELETE FROM mindful_sessions
+             WHERE id = ? AND user_id = ? AND session_type != 'journal'
+             RETURNING completed_at",
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "That session couldn't be found.".to_string())?;
+
+        let today = today_in(tz);
+        let changed = local_date(&completed_at, tz).unwrap_or(today);
+        Self::forget_streak_from(&mut tx, user_id, changed).await?;
+        Self::sync_goal_periods(&mut tx, user_id, tz, today, changed).await?;
+        tx.commit().await.map_err(|e| e.to_string())
     }
 
     // ── Videos ─────────────────────────────────────────────────────────────────
@@ -370,23 +2264,977 @@ impl UserStore {
 
 // ── Helpers ────────────────────────────────────────────────────────────────────
 
-/// Given a list of ISO date strings ordered newest-first, count how many
-/// consecutive days ending today (or yesterday) have at least one session.
-fn calculate_streak(dates: &[String]) -> i64 {
-    let today = Local::now().date_naive();
-    let mut streak = 0i64;
-    let mut expected = today;
-
-    for date_str in dates {
-        if let Ok(date) = NaiveDate::parse_from_str(date_str, "%Y-%m-%d") {
-            if date == expected {
-                streak += 1;
-                expected = expected.pred_opt().unwrap_or(expected);
+/// Timestamps are stored as UTC in SQLite's `datetime()` format.
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+fn today_in(tz: Tz) -> NaiveDate {
+    Utc::now().with_timezone(&tz).date_naive()
+}
+
+fn parse_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
+    let utc = NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).ok()?;
+    Some(Utc.from_utc_datetime(&utc))
+}
+
+/// The calendar day a stored timestamp falls on in `tz`.
+fn local_date(timestamp: &str, tz: Tz) -> Option<NaiveDate> {
+    Some(parse_timestamp(timestamp)?.with_timezone(&tz).date_naive())
+}
+
+/// A stored timestamp as wall-clock time in `tz`, in the same format.
+fn to_local(timestamp: &str, tz: Tz) -> String {
+    NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT)
+        .map(|utc| {
+            Utc.from_utc_datetime(&utc)
+                .with_timezone(&tz)
+                .format(TIMESTAMP_FORMAT)
+                .to_string()
+        })
+        .unwrap_or_else(|_| timestamp.to_string())
+}
+
+fn localize_entry(entry: &mut JournalEntry, tz: Tz) {
+    entry.created_at = to_local(&entry.created_at, tz);
+    entry.updated_at = entry.updated_at.as_deref().map(|t| to_local(t, tz));
+}
+
+/// When `date` begins in `tz`, as a stored UTC timestamp for range queries.
+/// Where DST skips midnight the day starts at the first hour that exists.
+fn day_start_utc(date: NaiveDate, tz: Tz) -> String {
+    (0..3)
+        .find_map(|hour| {
+            tz.from_local_datetime(&date.and_hms_opt(hour, 0, 0)?)
+                .earliest()
+        })
+        .map(|start| {
+            start
+                .with_timezone(&Utc)
+                .format(TIMESTAMP_FORMAT)
+                .to_string()
+        })
+        .unwrap_or_else(|| format!("{date} 00:00:00"))
+}
+
+/// Decrypt a stored note with the owner's key. Plaintext notes from before
+/// encryption pass through unchanged.
+fn decrypt_note(key: Option<&DataKey>, stored: &str, entry_id: &str) -> Result<String, String> {
+    if !crypto::is_encrypted(stored) {
+        return Ok(stored.to_string());
+    }
+    key.ok_or_else(|| "no data key for this user".to_string())?
+        .decrypt(stored, entry_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A store over a fresh database holding one user in `timezone`. The
+    /// user is inserted directly to skip bcrypt.
+    async fn store_with_user(timezone: &str) -> (UserStore, String) {
+        let store = UserStore::new(crate::db::test_pool().await, Keyring::for_tests());
+        let user_id = Uuid::new_v4().to_string();
+        sqlx::query(
+            "INSERT INTO users (id, name, email, password_hash, timezone)
+             VALUES (?, 'Ada', 'ada@example.com', '', ?)",
+        )
+        .bind(&user_id)
+        .bind(timezone)
+        .execute(&store.pool)
+        .await
+        .unwrap();
+        (store, user_id)
+    }
+
+    /// Add another user to `store`'s database, returning their id.
+    async fn add_user(store: &UserStore, email: &str) -> String {
+        let user_id = Uuid::new_v4().to_string();
+        sqlx::query(
+            "INSERT INTO users (id, name, email, password_hash, timezone)
+             VALUES (?, 'Grace', ?, '', 'UTC')",
+        )
+        .bind(&user_id)
+        .bind(email)
+        .execute(&store.pool)
+        .await
+        .unwrap();
+        user_id
+    }
+
+    /// Write a journal entry as if it were saved at `created_at` (UTC).
+    async fn journal_at(store: &UserStore, user_id: &str, mood: i64, note: &str, created_at: &str) {
+        store
+            .log_journal_entry(user_id, mood, note, None, &[])
+            .await
+            .unwrap();
+        sqlx::query(
+            "UPDATE journal_entries SET created_at = ?
+             WHERE id = (SELECT id FROM journal_entries ORDER BY rowid DESC LIMIT 1)",
+        )
+        .bind(created_at)
+        .execute(&store.pool)
+        .await
+        .unwrap();
+    }
+
+    /// Log a finished session as if it completed at `completed_at` (UTC),
+    /// without touching goal progress.
+    async fn session_at(
+        store: &UserStore,
+        user_id: &str,
+        session_type: &str,
+        minutes: i64,
+        completed_at: &str,
+    ) {
+        sqlx::query(
+            "INSERT INTO mindful_sessions (id, user_id, session_type, duration_min, completed_at)
+             VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(user_id)
+        .bind(session_type)
+        .bind(minutes)
+        .bind(completed_at)
+        .execute(&store.pool)
+        .await
+        .unwrap();
+    }
+
+    /// Recorded periods as (goal, period start, achieved), oldest first.
+    async fn goal_periods(store: &UserStore, user_id: &str) -> Vec<(String, String, i64)> {
+        sqlx::query_as(
+            "SELECT goal, period_start, achieved FROM goal_periods
+             WHERE user_id = ? ORDER BY goal, period_start",
+        )
+        .bind(user_id)
+        .fetch_all(&store.pool)
+        .await
+        .unwrap()
+    }
+
+    fn search(q: &str) -> JournalFilter {
+        JournalFilter {
+            q: Some(q.to_string()),
+            ..JournalFilter::default()
+        }
+    }
+
+    fn notes(page: &JournalPage) -> Vec<&str> {
+        page.entries.iter().map(|e| e.note.as_str()).collect()
+    }
+
+    /// Record a login attempt as if it happened `secs_ago` seconds ago.
+    async fn attempt_ago(store: &UserStore, ip: &str, outcome: AttemptOutcome, secs_ago: i64) {
+        store
+            .record_login_attempt("ada@example.com", ip, outcome)
+            .await
+            .unwrap();
+        sqlx::query(
+            "UPDATE login_attempts SET attempted_at = datetime('now', ?)
+             WHERE id = (SELECT id FROM login_attempts ORDER BY rowid DESC LIMIT 1)",
+        )
+        .bind(format!("-{secs_ago} seconds"))
+        .execute(&store.pool)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn account_failures_count_since_the_last_success() {
+        let (store, _) = store_with_user("UTC").await;
+        let failures = || async { store.account_failures("ada@example.com").await.failures };
+
+        attempt_ago(&store, "198.51.100.1", AttemptOutcome::BadPassword, 600).await;
+        attempt_ago(&store, "198.51.100.2", AttemptOutcome::BadCode, 500).await;
+        // Rejections during a lockout don't extend it
+        attempt_ago(&store, "198.51.100.2", AttemptOutcome::Locked, 450).await;
+        assert_eq!(failures().await, 2);
+
+        attempt_ago(&store, "198.51.100.1", AttemptOutcome::Success, 400).await;
+        assert_eq!(failures().await, 0);
+
+        attempt_ago(&store, "198.51.100.1", AttemptOutcome::BadPassword, 300).await;
+        assert_eq!(failures().await, 1);
+        attempt_ago(&store, "198.51.100.1", AttemptOutcome::PasswordReset, 200).await;
+        assert_eq!(failures().await, 0);
+
+        // Nothing older than a day counts, success or not
+        let (store, _) = store_with_user("UTC").await;
+        attempt_ago(
+            &store,
+            "198.51.100.1",
+            AttemptOutcome::BadPassword,
+            86_400 + 60,
+        )
+        .await;
+        attempt_ago(&store, "198.51.100.1", AttemptOutcome::BadPassword, 60).await;
+        let streak = store.account_failures("ada@example.com").await;
+        assert_eq!(streak.failures, 1);
+        assert!(streak.last_failed_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn ip_failures_cover_the_last_hour() {
+        let (store, _) = store_with_user("UTC").await;
+        let ip = "198.51.100.9";
+
+        attempt_ago(&store, ip, AttemptOutcome::BadPassword, 3_600 + 60).await;
+        attempt_ago(&store, ip, AttemptOutcome::BadPassword, 3_500).await;
+        attempt_ago(&store, ip, AttemptOutcome::BadCode, 60).await;
+        attempt_ago(&store, "198.51.100.10", AttemptOutcome::BadPassword, 60).await;
+        assert_eq!(store.ip_failures(ip).await.failures, 2);
+
+        // Signing in from the same address doesn't clear it
+        attempt_ago(&store, ip, AttemptOutcome::Success, 30).await;
+        assert_eq!(store.ip_failures(ip).await.failures, 2);
+    }
+
+    /// Start a session whose clock started `secs_ago` seconds ago.
+    async fn begun_session(
+        store: &UserStore,
+        user_id: &str,
+        planned_secs: i64,
+        secs_ago: i64,
+    ) -> String {
+        let id = store
+            .start_session(user_id, "meditation", planned_secs, 0, None, None)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE session_starts SET begun_at = datetime('now', ?) WHERE id = ?")
+            .bind(format!("-{secs_ago} seconds"))
+            .bind(&id)
+            .execute(&store.pool)
+            .await
+            .unwrap();
+        id
+    }
+
+    async fn logged_sessions(store: &UserStore, user_id: &str) -> Vec<(i64, i64)> {
+        sqlx::query_as(
+            "SELECT duration_min, actual_secs FROM mindful_sessions
+             WHERE user_id = ? ORDER BY rowid",
+        )
+        .bind(user_id)
+        .fetch_all(&store.pool)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn finished_sessions_are_capped_at_the_planned_length() {
+        let (store, user) = store_with_user("UTC").await;
+        let id = begun_session(&store, &user, 300, 3_600).await;
+        store
+            .finish_session(&user, &id, "meditation")
+            .await
+            .unwrap();
+
+        assert_eq!(logged_sessions(&store, &user).await, [(5, 300)]);
+        // Each start is saved once
+        assert!(
+            store
+                .finish_session(&user, &id, "meditation")
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn durations_round_to_the_nearest_minute() {
+        let (store, user) = store_with_user("UTC").await;
+        for secs_ago in [30, 89, 90] {
+            let id = begun_session(&store, &user, 600, secs_ago).await;
+            store
+                .finish_session(&user, &id, "meditation")
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            logged_sessions(&store, &user).await,
+            [(1, 30), (1, 89), (2, 90)]
+        );
+
+        // Under half a minute isn't logged at all
+        let id = begun_session(&store, &user, 600, 29).await;
+        let err = store
+            .finish_session(&user, &id, "meditation")
+            .await
+            .unwrap_err();
+        assert!(err.contains("shorter than 30 seconds"), "{err}");
+        assert_eq!(logged_sessions(&store, &user).await.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn sessions_in_their_lead_in_are_not_saved() {
+        let (store, user) = store_with_user("UTC").await;
+
+        // Begin puts the clock 10 s in the future, past the countdown
+        let id = store
+            .start_session(&user, "meditation", 600, 10, None, None)
+            .await
+            .unwrap();
+        let err = store
+            .finish_session(&user, &id, "meditation")
+            .await
+            .unwrap_err();
+        assert!(err.contains("hasn't started yet"), "{err}");
+        assert!(logged_sessions(&store, &user).await.is_empty());
+
+        // The rejected attempt leaves the start open for a later save
+        sqlx::query("UPDATE session_starts SET begun_at = datetime('now', '-90 seconds')")
+            .execute(&store.pool)
+            .await
+            .unwrap();
+        store
+            .finish_session(&user, &id, "meditation")
+            .await
+            .unwrap();
+        assert_eq!(logged_sessions(&store, &user).await, [(2, 90)]);
+    }
+
+    #[tokio::test]
+    async fn sessions_can_only_be_deleted_by_their_owner() {
+        let (store, user) = store_with_user("UTC").await;
+        let other = add_user(&store, "grace@example.com").await;
+        session_at(&store, &user, "breathing", 5, "2026-03-02 09:00:00").await;
+        let history = store.get_history(&user, &HistoryFilter::default()).await;
+        let id = history.items[0].id.clone();
+
+        let err = store.delete_session(&other, &id).await.unwrap_err();
+        assert_eq!(err, "That session couldn't be found.");
+        assert_eq!(
+            store
+                .get_history(&user, &HistoryFilter::default())
+                .await
+                .total,
+            1
+        );
+
+        // Journal credits go with their entry, not through history
+        store
+            .log_journal_entry(&user, 4, "fine", None, &[])
+            .await
+            .unwrap();
+        let credit = sqlx::query_scalar::<_, String>(
+            "SELECT id FROM mindful_sessions WHERE session_type = 'journal'",
+        )
+        .fetch_one(&store.pool)
+        .await
+        .unwrap();
+        assert!(store.delete_session(&user, &credit).await.is_err());
+
+        store.delete_session(&user, &id).await.unwrap();
+        assert!(store.delete_session(&user, &id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn history_pages_and_filters_by_type() {
+        let (store, user) = store_with_user("UTC").await;
+        for day in 1..=PAGE_SIZE + 5 {
+            let kind = if day % 5 == 0 {
+                "meditation"
             } else {
-                break;
-            }
+                "breathing"
+            };
+            session_at(
+                &store,
+                &user,
+                kind,
+                5,
+                &format!("2026-01-{day:02} 09:00:00"),
+            )
+            .await;
+        }
+        let page = |page, item_type: Option<&str>| HistoryFilter {
+            item_type: item_type.map(str::to_string),
+            page,
+            ..HistoryFilter::default()
+        };
+
+        let first = store.get_history(&user, &page(1, None)).await;
+        assert_eq!((first.items.len() as i64, first.total), (PAGE_SIZE, 25));
+        assert_eq!(first.items[0].occurred_at, "2026-01-25 09:00:00");
+        let second = store.get_history(&user, &page(2, None)).await;
+        assert_eq!(second.items.len(), 5);
+        assert_eq!(second.items[4].occurred_at, "2026-01-01 09:00:00");
+        assert!(
+            store
+                .get_history(&user, &page(3, None))
+                .await
+                .items
+                .is_empty()
+        );
+        // Page 0 is treated as the first page
+        assert_eq!(
+            store.get_history(&user, &page(0, None)).await.items[0].id,
+            first.items[0].id
+        );
+
+        let meditation = store.get_history(&user, &page(1, Some("meditation"))).await;
+        assert_eq!(meditation.total, 5);
+        assert!(meditation.items.iter().all(|i| i.item_type == "meditation"));
+    }
+
+    #[tokio::test]
+    async fn search_combines_with_a_mood_range() {
+        let (store, user) = store_with_user("UTC").await;
+        journal_at(
+            &store,
+            &user,
+            2,
+            "Called my sister about the move",
+            "2026-03-01 09:00:00",
+        )
+        .await;
+        journal_at(
+            &store,
+            &user,
+            4,
+            "My sister came over for dinner",
+            "2026-03-02 09:00:00",
+        )
+        .await;
+        journal_at(
+            &store,
+            &user,
+            5,
+            "Long walk by the river",
+            "2026-03-03 09:00:00",
+        )
+        .await;
+
+        let page = store.get_journal_entries(&user, &search("sister"), 1).await;
+        assert_eq!(
+            notes(&page),
+            [
+                "My sister came over for dinner",
+                "Called my sister about the move"
+            ]
+        );
+
+        let filter = JournalFilter {
+            mood_min: Some(3),
+            ..search("sister")
+        };
+        let page = store.get_journal_entries(&user, &filter, 1).await;
+        assert_eq!(notes(&page), ["My sister came over for dinner"]);
+        assert_eq!(page.total, 1);
+
+        let filter = JournalFilter {
+            mood_max: Some(1),
+            ..search("sister")
+        };
+        assert_eq!(store.get_journal_entries(&user, &filter, 1).await.total, 0);
+    }
+
+    #[tokio::test]
+    async fn search_combines_with_dates_in_the_users_timezone() {
+        let (store, user) = store_with_user("America/New_York").await;
+        // 23:30 on 9 March in New York, though already the 10th in UTC
+        journal_at(
+            &store,
+            &user,
+            3,
+            "Late call with my sister",
+            "2026-03-10 03:30:00",
+        )
+        .await;
+        journal_at(
+            &store,
+            &user,
+            3,
+            "Lunch with my sister",
+            "2026-03-10 16:00:00",
+        )
+        .await;
+        journal_at(&store, &user, 3, "Lunch alone", "2026-03-10 17:00:00").await;
+
+        let day = |d: u32| NaiveDate::from_ymd_opt(2026, 3, d);
+        let on = |d: u32| JournalFilter {
+            from: day(d),
+            to: day(d),
+            ..search("sister")
+        };
+        assert_eq!(
+            notes(&store.get_journal_entries(&user, &on(9), 1).await),
+            ["Late call with my sister"]
+        );
+        assert_eq!(
+            notes(&store.get_journal_entries(&user, &on(10), 1).await),
+            ["Lunch with my sister"]
+        );
+
+        let filter = JournalFilter {
+            from: day(10),
+            ..search("lunch")
+        };
+        assert_eq!(store.get_journal_entries(&user, &filter, 1).await.total, 2);
+    }
+
+    #[tokio::test]
+    async fn search_matches_word_beginnings_in_any_order() {
+        let (store, user) = store_with_user("UTC").await;
+        journal_at(
+            &store,
+            &user,
+            3,
+            "Dinner with both sisters",
+            "2026-03-01 09:00:00",
+        )
+        .await;
+        journal_at(&store, &user, 3, "Dinner on my own", "2026-03-02 09:00:00").await;
+
+        let page = store
+            .get_journal_entries(&user, &search("SIST din"), 1)
+            .await;
+        assert_eq!(notes(&page), ["Dinner with both sisters"]);
+        let snippet = page.entries[0].snippet.as_deref().unwrap();
+        assert!(snippet.contains("\u{E000}sisters\u{E001}"), "{snippet}");
+
+        // Not a substring search: terms match from the start of a word
+        assert_eq!(
+            store
+                .get_journal_entries(&user, &search("isters"), 1)
+                .await
+                .total,
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn search_ranks_by_how_many_words_match() {
+        let (store, user) = store_with_user("UTC").await;
+        journal_at(&store, &user, 3, "Called my sister", "2026-03-01 09:00:00").await;
+        journal_at(
+            &store,
+            &user,
+            3,
+            "Sister came over, my sister's kids too",
+            "2026-02-01 09:00:00",
+        )
+        .await;
+        journal_at(&store, &user, 3, "Sister time again", "2026-03-02 09:00:00").await;
+
+        let page = store.get_journal_entries(&user, &search("sister"), 1).await;
+        assert_eq!(
+            notes(&page),
+            [
+                "Sister came over, my sister's kids too",
+                // Equal matches stay newest first
+                "Sister time again",
+                "Called my sister",
+            ]
+        );
+
+        // "or" is a word to find like any other, not an operator
+        assert_eq!(
+            store
+                .get_journal_entries(&user, &search("sister OR brother"), 1)
+                .await
+                .total,
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn edited_entries_are_found_by_their_new_words() {
+        let (store, user) = store_with_user("UTC").await;
+        journal_at(
+            &store,
+            &user,
+            3,
+            "Walked to the park",
+            "2026-03-01 09:00:00",
+        )
+        .await;
+        let entry = store
+            .get_journal_entries(&user, &search("park"), 1)
+            .await
+            .entries[0]
+            .id
+            .clone();
+
+        store
+            .update_journal_entry(&user, &entry, 3, "Swam at the lake", &[])
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store
+                .get_journal_entries(&user, &search("park"), 1)
+                .await
+                .total,
+            0
+        );
+        assert_eq!(
+            store
+                .get_journal_entries(&user, &search("lake"), 1)
+                .await
+                .total,
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn tag_filter_only_finds_the_users_tagged_entries() {
+        let (store, user) = store_with_user("UTC").await;
+        let other = add_user(&store, "grace@example.com").await;
+        let work = vec!["work".to_string()];
+        store
+            .log_journal_entry(&user, 3, "busy", None, &work)
+            .await
+            .unwrap();
+        store
+            .log_journal_entry(&user, 4, "quiet", None, &[])
+            .await
+            .unwrap();
+        store
+            .log_journal_entry(&other, 2, "theirs", None, &work)
+            .await
+            .unwrap();
+
+        let filter = JournalFilter {
+            tag: Some("work".to_string()),
+            ..JournalFilter::default()
+        };
+        let page = store.get_journal_entries(&user, &filter, 1).await;
+        assert_eq!(notes(&page), ["busy"]);
+        assert_eq!(page.entries[0].tag_list.as_deref(), Some("work"));
+        assert_eq!(
+            store.get_journal_tags(&user).await,
+            [("work".to_string(), 1)]
+        );
+    }
+
+    #[tokio::test]
+    async fn goal_periods_split_at_local_midnight_and_monday() {
+        let (store, user) = store_with_user("America/Los_Angeles").await;
+        for (goal, target) in [
+            ("daily_minutes", 10),
+            ("weekly_sessions", 3),
+            ("weekly_journal", 1),
+        ] {
+            sqlx::query(
+                "INSERT INTO user_goals (user_id, goal, target, set_at)
+                 VALUES (?, ?, ?, '2026-02-28 20:00:00')",
+            )
+            .bind(&user)
+            .bind(goal)
+            .bind(target)
+            .execute(&store.pool)
+            .await
+            .unwrap();
+        }
+        // 23:30 on Sunday 1 March in Los Angeles, Monday morning in UTC
+        session_at(&store, &user, "breathing", 10, "2026-03-02 07:30:00").await;
+        sqlx::query(
+            "INSERT INTO journal_entries (id, user_id, mood, created_at)
+             VALUES ('e1', ?, 3, '2026-03-02 07:59:59')",
+        )
+        .bind(&user)
+        .execute(&store.pool)
+        .await
+        .unwrap();
+        session_at(&store, &user, "journal", 5, "2026-03-02 07:59:59").await;
+        // 00:30 on Monday 2 March in Los Angeles
+        session_at(&store, &user, "meditation", 5, "2026-03-02 08:30:00").await;
+
+        let tz: Tz = "America/Los_Angeles".parse().unwrap();
+        let today = NaiveDate::from_ymd_opt(2026, 3, 3).unwrap();
+        let mut tx = store.pool.begin().await.unwrap();
+        UserStore::sync_goal_periods(&mut tx, &user, tz, today, today)
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+
+        let row = |goal: &str, start: &str, achieved: i64| {
+            (goal.to_string(), start.to_string(), achieved)
+        };
+        assert_eq!(
+            goal_periods(&store, &user).await,
+            [
+                // Set at noon on the 28th local time; the journal session
+                // counts 5 minutes on Sunday too
+                row("daily_minutes", "2026-02-28", 0),
+                row("daily_minutes", "2026-03-01", 15),
+                row("daily_minutes", "2026-03-02", 5),
+                row("daily_minutes", "2026-03-03", 0),
+                row("weekly_journal", "2026-02-23", 1),
+                row("weekly_journal", "2026-03-02", 0),
+                row("weekly_sessions", "2026-02-23", 1),
+                row("weekly_sessions", "2026-03-02", 1),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn stats_and_streaks_follow_the_local_day() {
+        let (store, user) = store_with_user("Asia/Kolkata").await;
+        let tz: Tz = "Asia/Kolkata".parse().unwrap();
+        let today = today_in(tz);
+        let utc = |day: NaiveDate, time: &str| {
+            let local =
+                NaiveDateTime::parse_from_str(&format!("{day} {time}"), "%Y-%m-%d %H:%M").unwrap();
+            tz.from_local_datetime(&local)
+                .unwrap()
+                .with_timezone(&Utc)
+                .format(TIMESTAMP_FORMAT)
+                .to_string()
+        };
+        // 18:00 and 19:00 on the same UTC day
+        let yesterday = today - Duration::days(1);
+        session_at(&store, &user, "breathing", 5, &utc(yesterday, "23:30")).await;
+        session_at(&store, &user, "meditation", 10, &utc(today, "00:30")).await;
+
+        let stats = store.get_stats(&user).await;
+        assert_eq!(stats.sessions_today, 1);
+        assert_eq!(stats.streak, 2);
+        let history = store.get_streak_history(&user).await;
+        assert_eq!(history.first, yesterday);
+        assert_eq!(history.days.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn dashboard_streak_carries_on_from_its_checkpoint() {
+        let (store, user) = store_with_user("UTC").await;
+        let today = today_in(Tz::UTC);
+        for days_ago in 1..=3 {
+            let day = today - Duration::days(days_ago);
+            session_at(&store, &user, "breathing", 5, &format!("{day} 12:00:00")).await;
         }
+        let stats = store.get_stats(&user).await;
+        assert_eq!((stats.streak, stats.longest_streak), (3, 3));
+
+        let checkpoint = sqlx::query_as::<_, (String, i64)>(
+            "SELECT day, current FROM streak_checkpoints WHERE user_id = ?",
+        )
+        .bind(&user)
+        .fetch_one(&store.pool)
+        .await
+        .unwrap();
+        assert_eq!(checkpoint, ((today - Duration::days(1)).to_string(), 3));
+
+        // Later loads start from the checkpoint rather than the first session
+        sqlx::query("UPDATE streak_checkpoints SET longest = 40")
+            .execute(&store.pool)
+            .await
+            .unwrap();
+        assert_eq!(store.get_stats(&user).await.longest_streak, 40);
+
+        // Deleting a day it covered sends the next load back to the start
+        let oldest = sqlx::query_scalar::<_, String>(
+            "SELECT id FROM mindful_sessions ORDER BY completed_at LIMIT 1",
+        )
+        .fetch_one(&store.pool)
+        .await
+        .unwrap();
+        store.delete_session(&user, &oldest).await.unwrap();
+        let stats = store.get_stats(&user).await;
+        assert_eq!((stats.streak, stats.longest_streak), (2, 2));
+    }
+
+    #[tokio::test]
+    async fn daily_activity_covers_every_day_in_range() {
+        let (store, user) = store_with_user("UTC").await;
+        session_at(&store, &user, "breathing", 5, "2026-03-01 08:00:00").await;
+        session_at(&store, &user, "meditation", 10, "2026-03-01 21:00:00").await;
+        session_at(&store, &user, "journal", 5, "2026-03-03 12:00:00").await;
+        for (id, mood) in [("e1", 2), ("e2", 5)] {
+            sqlx::query(
+                "INSERT INTO journal_entries (id, user_id, mood, created_at)
+                 VALUES (?, ?, ?, '2026-03-03 12:00:00')",
+            )
+            .bind(id)
+            .bind(&user)
+            .bind(mood)
+            .execute(&store.pool)
+            .await
+            .unwrap();
+        }
+        // Outside the range
+        session_at(&store, &user, "breathing", 5, "2026-03-05 00:00:00").await;
+
+        let from = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(2026, 3, 4).unwrap();
+        let days = store.get_daily_activity(&user, from, to).await;
+
+        let summary: Vec<_> = days
+            .iter()
+            .map(|d| {
+                (
+                    d.day.to_string(),
+                    d.minutes,
+                    d.type_minutes,
+                    d.sessions,
+                    d.mood,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("2026-03-01".to_string(), 15, [5, 10, 0], 2, None),
+                ("2026-03-02".to_string(), 0, [0, 0, 0], 0, None),
+                ("2026-03-03".to_string(), 5, [0, 0, 5], 0, Some(3.5)),
+                ("2026-03-04".to_string(), 0, [0, 0, 0], 0, None),
+            ]
+        );
+        assert!(
+            days.iter()
+                .all(|d| d.type_minutes.iter().sum::<i64>() == d.minutes)
+        );
+    }
+
+    #[tokio::test]
+    async fn goal_overview_only_reads() {
+        let (store, user) = store_with_user("Asia/Tokyo").await;
+        sqlx::query(
+            "INSERT INTO user_goals (user_id, goal, target, set_at)
+             VALUES (?, 'weekly_sessions', 3, '2026-01-01 00:00:00')",
+        )
+        .bind(&user)
+        .execute(&store.pool)
+        .await
+        .unwrap();
+
+        let overview = store.get_goal_overview(&user).await;
+        assert!(goal_periods(&store, &user).await.is_empty());
+        assert_eq!(overview.current.len(), 1);
+        assert_eq!(overview.current[0].achieved, 0);
+        assert_eq!(overview.current[0].target, 3);
+        assert_eq!(overview.previous.len(), 1);
+        assert!(!overview.previous[0].met());
+
+        let history = store.get_goal_history(&user, 4).await;
+        assert_eq!(history.len(), 4);
+        assert!(
+            history
+                .windows(2)
+                .all(|w| w[0].period_start > w[1].period_start)
+        );
+        assert!(goal_periods(&store, &user).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn recording_activity_updates_goal_progress() {
+        let (store, user) = store_with_user("Asia/Tokyo").await;
+        store
+            .set_goals(
+                &user,
+                &[
+                    (GoalKind::WeeklyJournal, Some(2)),
+                    (GoalKind::DailyMinutes, Some(20)),
+                ],
+            )
+            .await
+            .unwrap();
+
+        store
+            .log_journal_entry(&user, 4, "Slept well", None, &[])
+            .await
+            .unwrap();
+        let overview = store.get_goal_overview(&user).await;
+        let achieved: Vec<(&str, i64)> = overview
+            .current
+            .iter()
+            .map(|p| (p.goal.as_str(), p.achieved))
+            .collect();
+        assert_eq!(achieved, [("daily_minutes", 5), ("weekly_journal", 1)]);
+
+        let entry = store
+            .get_journal_entries(&user, &JournalFilter::default(), 1)
+            .await
+            .entries[0]
+            .id
+            .clone();
+        store.delete_journal_entry(&user, &entry).await.unwrap();
+        let overview = store.get_goal_overview(&user).await;
+        assert!(overview.current.iter().all(|p| p.achieved == 0));
+    }
+
+    #[tokio::test]
+    async fn deleting_past_activity_updates_its_period() {
+        let (store, user) = store_with_user("UTC").await;
+        sqlx::query(
+            "INSERT INTO user_goals (user_id, goal, target, set_at)
+             VALUES (?, 'weekly_sessions', 1, '2000-01-01 00:00:00')",
+        )
+        .bind(&user)
+        .execute(&store.pool)
+        .await
+        .unwrap();
+        let today = today_in(Tz::UTC);
+        let last_week = today - Duration::days(7);
+        session_at(
+            &store,
+            &user,
+            "breathing",
+            5,
+            &format!("{last_week} 12:00:00"),
+        )
+        .await;
+        let mut tx = store.pool.begin().await.unwrap();
+        UserStore::sync_goal_periods(&mut tx, &user, Tz::UTC, today, today)
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+
+        let previous = GoalKind::WeeklySessions.period_start(last_week).to_string();
+        let progress = |periods: Vec<(String, String, i64)>| {
+            periods
+                .into_iter()
+                .find(|(_, start, _)| *start == previous)
+                .map(|(_, _, achieved)| achieved)
+        };
+        assert_eq!(progress(goal_periods(&store, &user).await), Some(1));
+
+        let id = sqlx::query_scalar::<_, String>("SELECT id FROM mindful_sessions")
+            .fetch_one(&store.pool)
+            .await
+            .unwrap();
+        store.delete_session(&user, &id).await.unwrap();
+        assert_eq!(progress(goal_periods(&store, &user).await), Some(0));
     }
 
-    streak
+    #[tokio::test]
+    async fn achievements_unlock_once() {
+        let (store, user) = store_with_user("UTC").await;
+        assert!(store.evaluate_achievements(&user).await.unwrap().is_empty());
+
+        session_at(&store, &user, "breathing", 5, "2026-03-01 09:00:00").await;
+        let keys = |unlocked: Vec<&Achievement>| -> Vec<&str> {
+            unlocked.into_iter().map(|a| a.key).collect()
+        };
+        assert_eq!(
+            keys(store.evaluate_achievements(&user).await.unwrap()),
+            ["first_session"]
+        );
+        // Re-evaluating with nothing new unlocks nothing
+        assert!(store.evaluate_achievements(&user).await.unwrap().is_empty());
+
+        store
+            .log_journal_entry(&user, 4, "First entry", None, &[])
+            .await
+            .unwrap();
+        assert_eq!(
+            keys(store.evaluate_achievements(&user).await.unwrap()),
+            ["first_journal"]
+        );
+
+        // Each badge is announced once
+        assert_eq!(
+            keys(store.take_new_achievements(&user).await),
+            ["first_session", "first_journal"]
+        );
+        assert!(store.take_new_achievements(&user).await.is_empty());
+
+        // and kept after the activity behind it is gone
+        sqlx::query("DELETE FROM mindful_sessions WHERE user_id = ?")
+            .bind(&user)
+            .execute(&store.pool)
+            .await
+            .unwrap();
+        assert!(store.evaluate_achievements(&user).await.unwrap().is_empty());
+        assert_eq!(store.get_achievements(&user).await.len(), 2);
+    }
 }
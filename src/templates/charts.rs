@@ -0,0 +1,762 @@
+//! Server-side SVG charts, so pages can show statistics without client-side
+//! JavaScript.
+//!
+//! Each chart is a struct of typed data whose `render` returns a
+//! self-contained `<svg role="img">`. The chart's title is both its first
+//! `<title>` and its `aria-label`, and every bar, point or cell carries its own
+//! `<title>` tooltip. Labels and tooltips are plain text and escaped here.
+
+use chrono::{Datelike, Duration, NaiveDate};
+
+use super::html::{Attr, Text, TrustedHtml, html};
+
+/// Chart colours, matching the site's greens.
+pub const CHART_FILL: &str = "#52b788";
+pub const CHART_STROKE: &str = "#2d6a4f";
+pub const CHART_GRID: &str = "#e3efe8";
+pub const CHART_EMPTY: &str = "#dee2e6";
+const AXIS_TEXT: &str = "#6c757d";
+const LABEL_TEXT: &str = "#495057";
+
+/// One value with its axis label and tooltip. `None` means no data.
+#[derive(Clone, Debug)]
+pub struct Datum {
+    pub label: String,
+    pub value: Option<f64>,
+    pub tooltip: String,
+}
+
+impl Datum {
+    pub fn new(label: impl Into<String>, value: Option<f64>, tooltip: impl Into<String>) -> Self {
+        Datum {
+            label: label.into(),
+            value,
+            tooltip: tooltip.into(),
+        }
+    }
+}
+
+/// Opening `<svg>` tag plus the chart's accessible title.
+fn svg_open(width: f64, height: f64, title: &str) -> TrustedHtml {
+    html!(
+        r#"<svg viewBox="0 0 {width:.0} {height:.0}" width="100%" role="img" aria-label="{}"><title>{}</title>"#,
+        Attr(title),
+        Text(title),
+        width,
+        height
+    )
+}
+
+// ── Bar chart ──────────────────────────────────────────────────────────────────
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Orientation {
+    #[default]
+    Vertical,
+    /// One row per datum, its label to the left of the bar.
+    Horizontal,
+}
+
+/// One bar per datum. Missing and zero values get a short grey stub so the
+/// slot still reads as "nothing here".
+pub struct BarChart<'a> {
+    pub title: &'a str,
+    pub data: &'a [Datum],
+    /// Value of a full-length bar; the largest value when `None`.
+    pub max: Option<f64>,
+    pub orientation: Orientation,
+    /// Decimal places of the value printed by each bar, or `None` for none.
+    pub value_decimals: Option<usize>,
+    /// Fill for each bar in turn; [`CHART_FILL`] past the end.
+    pub fills: &'a [&'a str],
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Default for BarChart<'_> {
+    fn default() -> Self {
+        BarChart {
+            title: "",
+            data: &[],
+            max: None,
+            orientation: Orientation::Vertical,
+            value_decimals: None,
+            fills: &[],
+            width: 350.0,
+            height: 180.0,
+        }
+    }
+}
+
+impl BarChart<'_> {
+    pub fn render(&self) -> TrustedHtml {
+        let max = self
+            .max
+            .unwrap_or_else(|| self.data.iter().filter_map(|d| d.value).fold(0.0, f64::max));
+        let bars: TrustedHtml = self
+            .data
+            .iter()
+            .enumerate()
+            .map(|(i, datum)| {
+                let value = datum.value.filter(|v| *v > 0.0 && max > 0.0);
+                let fill = match value {
+                    Some(_) => self.fills.get(i).copied().unwrap_or(CHART_FILL),
+                    None => CHART_EMPTY,
+                };
+                let printed = match (datum.value, self.value_decimals) {
+                    (Some(v), Some(decimals)) => format!("{v:.decimals$}"),
+                    (None, Some(_)) => "–".to_string(),
+                    _ => String::new(),
+                };
+                let fraction = value.map(|v| (v / max).min(1.0));
+                let mark = match self.orientation {
+                    Orientation::Vertical => self.column(i, fraction, fill, &datum.label, &printed),
+                    Orientation::Horizontal => self.row(i, fraction, fill, &datum.label, &printed),
+                };
+                html!("<g><title>{}</title>{mark}</g>", Text(&datum.tooltip), mark)
+            })
+            .collect();
+
+        html!(
+            "{}{bars}</svg>",
+            svg_open(self.width, self.height, self.title),
+            bars
+        )
+    }
+
+    fn column(
+        &self,
+        i: usize,
+        fraction: Option<f64>,
+        fill: &str,
+        label: &str,
+        printed: &str,
+    ) -> TrustedHtml {
+        const BOTTOM: f64 = 24.0;
+        let top = if self.value_decimals.is_some() {
+            20.0
+        } else {
+            6.0
+        };
+        let slot = self.width / self.data.len().max(1) as f64;
+        let plot = self.height - top - BOTTOM;
+        let width = slot * 0.6;
+        let bx = slot * i as f64 + slot * 0.2;
+        let height = fraction.map_or(4.0, |f| (plot * f).max(4.0));
+        let by = top + plot - height;
+        let cx = bx + width / 2.0;
+
+        html!(
+            r#"<rect x="{bx:.1}" y="{by:.1}" width="{width:.1}" height="{height:.1}" rx="5" fill="{fill}"/><text x="{cx:.1}" y="{vy:.1}" text-anchor="middle" font-size="11" fill="{CHART_STROKE}">{printed}</text><text x="{cx:.1}" y="{ly:.1}" text-anchor="middle" font-size="11" fill="{AXIS_TEXT}">{label}</text>"#,
+            vy = by - 5.0,
+            ly = self.height - 6.0,
+            label = Text(label),
+            fill = Attr(fill),
+            printed = Text(printed),
+            bx,
+            by,
+            width,
+            height,
+            cx,
+            CHART_STROKE,
+            AXIS_TEXT
+        )
+    }
+
+    fn row(
+        &self,
+        i: usize,
+        fraction: Option<f64>,
+        fill: &str,
+        label: &str,
+        printed: &str,
+    ) -> TrustedHtml {
+        let label_width = self.width * 0.34;
+        let row = self.height / self.data.len().max(1) as f64;
+        let height = (row * 0.6).min(24.0);
+        let full = self.width - label_width - 40.0;
+        let width = fraction.map_or(4.0, |f| (full * f).max(4.0));
+        let ry = row * i as f64 + (row - height) / 2.0;
+        let ty = ry + height / 2.0 + 4.0;
+
+        html!(
+            r#"<text x="0" y="{ty:.1}" font-size="12" fill="{LABEL_TEXT}">{label}</text><rect x="{label_width:.1}" y="{ry:.1}" width="{width:.1}" height="{height:.1}" rx="5" fill="{fill}"/><text x="{vx:.1}" y="{ty:.1}" font-size="12" fill="{CHART_STROKE}">{printed}</text>"#,
+            vx = label_width + width + 6.0,
+            label = Text(label),
+            fill = Attr(fill),
+            printed = Text(printed),
+            ty,
+            LABEL_TEXT,
+            label_width,
+            ry,
+            width,
+            height,
+            CHART_STROKE
+        )
+    }
+}
+
+// ── Stacked bar chart ──────────────────────────────────────────────────────────
+
+/// One layer of a stacked bar chart, with a value per column.
+pub struct Series<'a> {
+    pub name: &'a str,
+    pub colour: &'a str,
+    pub values: Vec<f64>,
+}
+
+/// Columns built from each series' value stacked bottom-up, with a legend.
+pub struct StackedBarChart<'a> {
+    pub title: &'a str,
+    /// One per column.
+    pub labels: &'a [String],
+    pub series: &'a [Series<'a>],
+    /// Appended to values in tooltips, e.g. "min".
+    pub unit: &'a str,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl StackedBarChart<'_> {
+    pub fn render(&self) -> TrustedHtml {
+        const TOP: f64 = 6.0;
+        const BOTTOM: f64 = 40.0;
+        let value = |series: &Series, i: usize| series.values.get(i).copied().unwrap_or(0.0);
+        let totals: Vec<f64> = (0..self.labels.len())
+            .map(|i| self.series.iter().map(|s| value(s, i)).sum())
+            .collect();
+        let max = totals.iter().copied().fold(0.0, f64::max);
+
+        let slot = self.width / self.labels.len().max(1) as f64;
+        let plot = self.height - TOP - BOTTOM;
+        let width = slot * 0.6;
+
+        let columns: TrustedHtml = self
+            .labels
+            .iter()
+            .zip(&totals)
+            .enumerate()
+            .map(|(i, (label, &total))| {
+                let bx = slot * i as f64 + slot * 0.2;
+                let cx = bx + width / 2.0;
+                let axis = html!(
+                    r#"<text x="{cx:.1}" y="{:.1}" text-anchor="middle" font-size="11" fill="{AXIS_TEXT}">{}</text>"#,
+                    self.height - BOTTOM + 16.0,
+                    Text(label), cx, AXIS_TEXT);
+                if total <= 0.0 {
+                    return html!(
+                        r#"<g><title>{}: none</title><rect x="{bx:.1}" y="{:.1}" width="{width:.1}" height="4" rx="2" fill="{CHART_EMPTY}"/>{axis}</g>"#,
+                        Text(label),
+                        TOP + plot - 4.0, bx, width, CHART_EMPTY, axis);
+                }
+
+                let mut base = TOP + plot;
+                let segments: TrustedHtml = self
+                    .series
+                    .iter()
+                    .filter(|s| value(s, i) > 0.0)
+                    .map(|s| {
+                        let v = value(s, i);
+                        let height = plot * v / max;
+                        base -= height;
+                        html!(
+                            r#"<rect x="{bx:.1}" y="{base:.1}" width="{width:.1}" height="{height:.1}" fill="{}"><title>{}: {} {v} {}</title></rect>"#,
+                            Attr(s.colour),
+                            Text(label),
+                            Text(s.name),
+                            Text(self.unit), bx, base, width, height, v)
+                    })
+                    .collect();
+                html!(
+                    r#"<g><title>{}: {total} {} in total</title>{segments}{axis}</g>"#,
+                    Text(label),
+                    Text(self.unit), total, segments, axis)
+            })
+            .collect();
+
+        let legend: TrustedHtml = self
+            .series
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                let lx = i as f64 * self.width / self.series.len().max(1) as f64;
+                let ly = self.height - 10.0;
+                html!(
+                    r#"<rect x="{lx:.1}" y="{:.1}" width="10" height="10" rx="2" fill="{}"/><text x="{:.1}" y="{ly:.1}" font-size="11" fill="{LABEL_TEXT}">{}</text>"#,
+                    ly - 9.0,
+                    Attr(s.colour),
+                    lx + 14.0,
+                    Text(s.name), lx, ly, LABEL_TEXT)
+            })
+            .collect();
+
+        html!(
+            "{}{columns}{legend}</svg>",
+            svg_open(self.width, self.height, self.title),
+            columns,
+            legend
+        )
+    }
+}
+
+// ── Line chart ─────────────────────────────────────────────────────────────────
+
+/// A gridline on the value axis.
+pub struct Tick {
+    pub value: f64,
+    /// Markup, so ticks can be icons such as mood faces.
+    pub label: TrustedHtml,
+    pub tooltip: String,
+}
+
+/// A line through the data that has values, with a dot and tooltip on each.
+/// Missing values are skipped rather than breaking the line, so sparse data
+/// still shows a trend.
+pub struct LineChart<'a> {
+    pub title: &'a str,
+    pub data: &'a [Datum],
+    /// Value-axis range.
+    pub min: f64,
+    pub max: f64,
+    pub ticks: &'a [Tick],
+    /// Indices of the data whose labels go on the horizontal axis.
+    pub x_labels: &'a [usize],
+    pub width: f64,
+    pub height: f64,
+}
+
+impl LineChart<'_> {
+    pub fn render(&self) -> TrustedHtml {
+        const LEFT: f64 = 40.0;
+        const RIGHT: f64 = 12.0;
+        const TOP: f64 = 12.0;
+        const BOTTOM: f64 = 28.0;
+
+        let n = self.data.len();
+        let step = (self.width - LEFT - RIGHT) / (n.max(2) - 1) as f64;
+        let x = |i: usize| LEFT + step * i as f64;
+        let span = (self.max - self.min).max(f64::EPSILON);
+        let y = |v: f64| TOP + (self.height - TOP - BOTTOM) * (self.max - v) / span;
+
+        let grid: TrustedHtml = self
+            .ticks
+            .iter()
+            .map(|tick| {
+                let gy = y(tick.value);
+                html!(
+                    r#"<line x1="{LEFT}" x2="{x2:.1}" y1="{gy:.1}" y2="{gy:.1}" stroke="{CHART_GRID}"/><text x="{tx}" y="{ty:.1}" text-anchor="end" font-size="14"><title>{tooltip}</title>{label}</text>"#,
+                    x2 = self.width - RIGHT,
+                    tx = LEFT - 8.0,
+                    ty = gy + 5.0,
+                    tooltip = Text(&tick.tooltip),
+                    label = &tick.label, LEFT, gy, CHART_GRID)
+            })
+            .collect();
+
+        let points: Vec<String> = self
+            .data
+            .iter()
+            .enumerate()
+            .filter_map(|(i, d)| Some(format!("{:.1},{:.1}", x(i), y(d.value?))))
+            .collect();
+        let line = html!(
+            r#"<polyline points="{}" fill="none" stroke="{CHART_STROKE}" stroke-width="2.5" stroke-linejoin="round"/>"#,
+            Attr(&points.join(" ")),
+            CHART_STROKE
+        );
+
+        let dots: TrustedHtml = self
+            .data
+            .iter()
+            .enumerate()
+            .filter_map(|(i, d)| {
+                Some(html!(
+                    r#"<circle cx="{:.1}" cy="{:.1}" r="4" fill="{CHART_FILL}" stroke="{CHART_STROKE}"><title>{}</title></circle>"#,
+                    x(i),
+                    y(d.value?),
+                    Text(&d.tooltip), CHART_FILL, CHART_STROKE))
+            })
+            .collect();
+
+        let labels: TrustedHtml = self
+            .x_labels
+            .iter()
+            .filter_map(|&i| {
+                Some(html!(
+                    r#"<text x="{:.1}" y="{:.1}" text-anchor="middle" font-size="11" fill="{AXIS_TEXT}">{}</text>"#,
+                    x(i),
+                    self.height - 8.0,
+                    Text(&self.data.get(i)?.label), AXIS_TEXT))
+            })
+            .collect();
+
+        html!(
+            "{}{grid}{line}{dots}{labels}</svg>",
+            svg_open(self.width, self.height, self.title),
+            grid,
+            line,
+            dots,
+            labels
+        )
+    }
+}
+
+// ── Sparkline ──────────────────────────────────────────────────────────────────
+
+/// A small axis-free line for showing a trend inline, e.g. on a stat card.
+pub struct Sparkline<'a> {
+    pub title: &'a str,
+    pub values: &'a [f64],
+    pub colour: &'a str,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Sparkline<'_> {
+    pub fn render(&self) -> TrustedHtml {
+        const PAD: f64 = 2.0;
+        let max = self.values.iter().copied().fold(0.0, f64::max);
+        let step = (self.width - PAD * 2.0) / (self.values.len().max(2) - 1) as f64;
+        let points: Vec<String> = self
+            .values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                let y = if max > 0.0 {
+                    self.height - PAD - (self.height - PAD * 2.0) * v / max
+                } else {
+                    self.height - PAD
+                };
+                format!("{:.1},{y:.1}", PAD + step * i as f64)
+            })
+            .collect();
+        let area = format!(
+            "{PAD},{h} {} {:.1},{h}",
+            points.join(" "),
+            PAD + step * (self.values.len().max(1) - 1) as f64,
+            h = self.height - PAD
+        );
+
+        html!(
+            r#"<svg viewBox="0 0 {w:.0} {h:.0}" width="{w:.0}" height="{h:.0}" role="img" aria-label="{label}"><title>{text}</title><polygon points="{area}" fill="{colour}" opacity=".2"/><polyline points="{line}" fill="none" stroke="{colour}" stroke-width="1.5" stroke-linejoin="round"/></svg>"#,
+            w = self.width,
+            h = self.height,
+            label = Attr(self.title),
+            text = Text(self.title),
+            colour = Attr(self.colour),
+            line = Attr(&points.join(" ")),
+            area = Attr(&area)
+        )
+    }
+}
+
+// ── Calendar heatmap ───────────────────────────────────────────────────────────
+
+/// GitHub-style calendar: a column per week, Monday at the top, month names
+/// above, and every day shaded by its value with a tooltip.
+pub struct CalendarHeatmap<'a> {
+    pub title: &'a str,
+    /// The day of `data[0]`; each following datum is the next day.
+    pub start: NaiveDate,
+    pub data: &'a [Datum],
+    /// Lowest value for each shade, in ascending order. Days below the first
+    /// (and missing values) take the first shade.
+    pub levels: &'a [(f64, &'a str)],
+    /// Unit shown in the legend, e.g. "min".
+    pub unit: &'a str,
+}
+
+impl CalendarHeatmap<'_> {
+    pub fn render(&self) -> TrustedHtml {
+        const CELL: f64 = 11.0;
+        const GAP: f64 = 2.0;
+        const LEFT: f64 = 28.0;
+        const TOP: f64 = 16.0;
+
+        let empty = self.levels.first().map_or(CHART_EMPTY, |(_, c)| c);
+        let offset = self.start.weekday().num_days_from_monday() as usize;
+        let weeks = (offset + self.data.len()).div_ceil(7);
+        let x = |week: usize| LEFT + week as f64 * (CELL + GAP);
+        let y = |weekday: usize| TOP + weekday as f64 * (CELL + GAP);
+
+        let mut cells = TrustedHtml::default();
+        let mut months = TrustedHtml::default();
+        for (i, datum) in self.data.iter().enumerate() {
+            let date = self.start + Duration::days(i as i64);
+            let (week, weekday) = ((offset + i) / 7, (offset + i) % 7);
+            let colour = datum
+                .value
+                .and_then(|v| self.levels.iter().rev().find(|(min, _)| v >= *min))
+                .map_or(empty, |(_, c)| c);
+            cells.push(html!(
+                r#"<rect x="{:.0}" y="{:.0}" width="{CELL}" height="{CELL}" rx="2" fill="{colour}"><title>{}</title></rect>"#,
+                x(week),
+                y(weekday),
+                Text(&datum.tooltip), CELL, colour = Attr(colour)));
+
+            // Name a month above the first full week that starts in it
+            if weekday == 0 && date.day() <= 7 && (week > 0 || offset == 0) {
+                months.push(html!(
+                    r#"<text x="{:.0}" y="10" font-size="9" fill="{AXIS_TEXT}">{}</text>"#,
+                    x(week),
+                    Text(&date.format("%b").to_string()),
+                    AXIS_TEXT
+                ));
+            }
+        }
+
+        let weekdays: TrustedHtml = [(0, "Mon"), (2, "Wed"), (4, "Fri")]
+            .iter()
+            .map(|&(row, label)| {
+                html!(
+                    r#"<text x="0" y="{:.0}" font-size="9" fill="{AXIS_TEXT}">{label}</text>"#,
+                    y(row) + CELL - 2.0,
+                    AXIS_TEXT,
+                    label
+                )
+            })
+            .collect();
+
+        let legend: Vec<TrustedHtml> = self
+            .levels
+            .iter()
+            .enumerate()
+            .map(|(i, (min, colour))| {
+                let label = if i == 0 {
+                    "None".to_string()
+                } else {
+                    format!("{min}+ {}", self.unit)
+                };
+                html!(
+                    r#"<span class="d-inline-block rounded-1 align-middle" style="width:11px;height:11px;background:{colour}" title="{}"></span>"#,
+                    Attr(&label), colour = Attr(colour))
+            })
+            .collect();
+
+        html!(
+            r#"<div style="overflow-x:auto">
+        <svg viewBox="0 0 {w:.0} {h:.0}" width="100%" style="min-width:560px" role="img" aria-label="{label}"><title>{text}</title>{months}{weekdays}{cells}</svg>
+    </div>
+    <div class="d-flex justify-content-end align-items-center gap-1 mt-2 text-muted" style="font-size:.75rem" aria-hidden="true">
+        Less {legend} More
+    </div>"#,
+            w = x(weeks),
+            h = y(7),
+            label = Attr(self.title),
+            text = Text(self.title),
+            legend = TrustedHtml::join(legend, " "),
+            months,
+            weekdays,
+            cells
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Labels and tooltips with markup in them, to check they're escaped.
+    fn week() -> Vec<Datum> {
+        vec![
+            Datum::new("Mon", Some(10.0), "Mon: 10 min"),
+            Datum::new("<b>Tue</b>", Some(5.0), "Tue & \"Wed\": 5 min"),
+            Datum::new("Thu", None, "Thu: no sessions"),
+        ]
+    }
+
+    #[test]
+    fn vertical_bar_chart() {
+        let data = week();
+        let svg = BarChart {
+            title: "Minutes <per> day",
+            data: &data,
+            value_decimals: Some(0),
+            width: 300.0,
+            height: 120.0,
+            ..Default::default()
+        }
+        .render()
+        .into_string();
+        assert_eq!(
+            svg,
+            r##"<svg viewBox="0 0 300 120" width="100%" role="img" aria-label="Minutes &lt;per&gt; day"><title>Minutes &lt;per&gt; day</title><g><title>Mon: 10 min</title><rect x="20.0" y="20.0" width="60.0" height="76.0" rx="5" fill="#52b788"/><text x="50.0" y="15.0" text-anchor="middle" font-size="11" fill="#2d6a4f">10</text><text x="50.0" y="114.0" text-anchor="middle" font-size="11" fill="#6c757d">Mon</text></g><g><title>Tue &amp; &quot;Wed&quot;: 5 min</title><rect x="120.0" y="58.0" width="60.0" height="38.0" rx="5" fill="#52b788"/><text x="150.0" y="53.0" text-anchor="middle" font-size="11" fill="#2d6a4f">5</text><text x="150.0" y="114.0" text-anchor="middle" font-size="11" fill="#6c757d">&lt;b&gt;Tue&lt;/b&gt;</text></g><g><title>Thu: no sessions</title><rect x="220.0" y="92.0" width="60.0" height="4.0" rx="5" fill="#dee2e6"/><text x="250.0" y="87.0" text-anchor="middle" font-size="11" fill="#2d6a4f">–</text><text x="250.0" y="114.0" text-anchor="middle" font-size="11" fill="#6c757d">Thu</text></g></svg>"##
+        );
+    }
+
+    #[test]
+    fn horizontal_bar_chart() {
+        let data = week();
+        let svg = BarChart {
+            title: "Moods",
+            data: &data[..2],
+            max: Some(10.0),
+            orientation: Orientation::Horizontal,
+            value_decimals: Some(1),
+            fills: &["#111111"],
+            width: 200.0,
+            height: 60.0,
+        }
+        .render()
+        .into_string();
+        assert_eq!(
+            svg,
+            r##"<svg viewBox="0 0 200 60" width="100%" role="img" aria-label="Moods"><title>Moods</title><g><title>Mon: 10 min</title><text x="0" y="19.0" font-size="12" fill="#495057">Mon</text><rect x="68.0" y="6.0" width="92.0" height="18.0" rx="5" fill="#111111"/><text x="166.0" y="19.0" font-size="12" fill="#2d6a4f">10.0</text></g><g><title>Tue &amp; &quot;Wed&quot;: 5 min</title><text x="0" y="49.0" font-size="12" fill="#495057">&lt;b&gt;Tue&lt;/b&gt;</text><rect x="68.0" y="36.0" width="46.0" height="18.0" rx="5" fill="#52b788"/><text x="120.0" y="49.0" font-size="12" fill="#2d6a4f">5.0</text></g></svg>"##
+        );
+    }
+
+    #[test]
+    fn stacked_bar_chart() {
+        let labels = vec!["W1".to_string(), "<W2>".to_string(), "W3".to_string()];
+        let series = [
+            Series {
+                name: "Breathing",
+                colour: "#aaaaaa",
+                values: vec![10.0, 0.0, 0.0],
+            },
+            // Shorter than `labels`: the rest count as 0
+            Series {
+                name: "Meditation & <more>",
+                colour: "#bbbbbb",
+                values: vec![5.0, 15.0],
+            },
+        ];
+        let svg = StackedBarChart {
+            title: "Weekly \"mix\"",
+            labels: &labels,
+            series: &series,
+            unit: "min",
+            width: 300.0,
+            height: 140.0,
+        }
+        .render()
+        .into_string();
+        assert_eq!(
+            svg,
+            r##"<svg viewBox="0 0 300 140" width="100%" role="img" aria-label="Weekly &quot;mix&quot;"><title>Weekly &quot;mix&quot;</title><g><title>W1: 15 min in total</title><rect x="20.0" y="37.3" width="60.0" height="62.7" fill="#aaaaaa"><title>W1: Breathing 10 min</title></rect><rect x="20.0" y="6.0" width="60.0" height="31.3" fill="#bbbbbb"><title>W1: Meditation &amp; &lt;more&gt; 5 min</title></rect><text x="50.0" y="116.0" text-anchor="middle" font-size="11" fill="#6c757d">W1</text></g><g><title>&lt;W2&gt;: 15 min in total</title><rect x="120.0" y="6.0" width="60.0" height="94.0" fill="#bbbbbb"><title>&lt;W2&gt;: Meditation &amp; &lt;more&gt; 15 min</title></rect><text x="150.0" y="116.0" text-anchor="middle" font-size="11" fill="#6c757d">&lt;W2&gt;</text></g><g><title>W3: none</title><rect x="220.0" y="96.0" width="60.0" height="4" rx="2" fill="#dee2e6"/><text x="250.0" y="116.0" text-anchor="middle" font-size="11" fill="#6c757d">W3</text></g><rect x="0.0" y="121.0" width="10" height="10" rx="2" fill="#aaaaaa"/><text x="14.0" y="130.0" font-size="11" fill="#495057">Breathing</text><rect x="150.0" y="121.0" width="10" height="10" rx="2" fill="#bbbbbb"/><text x="164.0" y="130.0" font-size="11" fill="#495057">Meditation &amp; &lt;more&gt;</text></svg>"##
+        );
+    }
+
+    #[test]
+    fn line_chart_skips_missing_values_and_labels() {
+        let data = week();
+        let ticks = [
+            Tick {
+                value: 1.0,
+                label: TrustedHtml::new("&#128542;"),
+                tooltip: "Low <1>".to_string(),
+            },
+            Tick {
+                value: 5.0,
+                label: TrustedHtml::new("&#128522;"),
+                tooltip: "Great".to_string(),
+            },
+        ];
+        let svg = LineChart {
+            title: "Mood",
+            data: &data,
+            min: 1.0,
+            max: 10.0,
+            ticks: &ticks,
+            x_labels: &[0, 1, 7],
+            width: 240.0,
+            height: 120.0,
+        }
+        .render()
+        .into_string();
+        assert_eq!(
+            svg,
+            r##"<svg viewBox="0 0 240 120" width="100%" role="img" aria-label="Mood"><title>Mood</title><line x1="40" x2="228.0" y1="92.0" y2="92.0" stroke="#e3efe8"/><text x="32" y="97.0" text-anchor="end" font-size="14"><title>Low &lt;1&gt;</title>&#128542;</text><line x1="40" x2="228.0" y1="56.4" y2="56.4" stroke="#e3efe8"/><text x="32" y="61.4" text-anchor="end" font-size="14"><title>Great</title>&#128522;</text><polyline points="40.0,12.0 134.0,56.4" fill="none" stroke="#2d6a4f" stroke-width="2.5" stroke-linejoin="round"/><circle cx="40.0" cy="12.0" r="4" fill="#52b788" stroke="#2d6a4f"><title>Mon: 10 min</title></circle><circle cx="134.0" cy="56.4" r="4" fill="#52b788" stroke="#2d6a4f"><title>Tue &amp; &quot;Wed&quot;: 5 min</title></circle><text x="40.0" y="112.0" text-anchor="middle" font-size="11" fill="#6c757d">Mon</text><text x="134.0" y="112.0" text-anchor="middle" font-size="11" fill="#6c757d">&lt;b&gt;Tue&lt;/b&gt;</text></svg>"##
+        );
+    }
+
+    #[test]
+    fn sparkline() {
+        let svg = Sparkline {
+            title: "Trend <up>",
+            values: &[1.0, 3.0, 2.0],
+            colour: "#52b788",
+            width: 60.0,
+            height: 20.0,
+        }
+        .render()
+        .into_string();
+        assert_eq!(
+            svg,
+            r##"<svg viewBox="0 0 60 20" width="60" height="20" role="img" aria-label="Trend &lt;up&gt;"><title>Trend &lt;up&gt;</title><polygon points="2,18 2.0,12.7 30.0,2.0 58.0,7.3 58.0,18" fill="#52b788" opacity=".2"/><polyline points="2.0,12.7 30.0,2.0 58.0,7.3" fill="none" stroke="#52b788" stroke-width="1.5" stroke-linejoin="round"/></svg>"##
+        );
+    }
+
+    #[test]
+    fn calendar_heatmap() {
+        // Starts on a Saturday and runs into April
+        let days: Vec<Datum> = (0..10)
+            .map(|i| {
+                let value = if i == 3 { None } else { Some(i as f64 * 3.0) };
+                Datum::new("", value, format!("Day {i} <x>"))
+            })
+            .collect();
+        let html = CalendarHeatmap {
+            title: "Year & more",
+            start: NaiveDate::from_ymd_opt(2026, 3, 28).unwrap(),
+            data: &days,
+            levels: &[(0.0, "#eeeeee"), (5.0, "#88cc88"), (20.0, "#008800")],
+            unit: "min",
+        }
+        .render()
+        .into_string();
+
+        let svg = &html[html.find("<svg").unwrap()..html.find("</svg>").unwrap() + 6];
+        assert_eq!(
+            svg,
+            r##"<svg viewBox="0 0 67 107" width="100%" style="min-width:560px" role="img" aria-label="Year &amp; more"><title>Year &amp; more</title><text x="54" y="10" font-size="9" fill="#6c757d">Apr</text><text x="0" y="25" font-size="9" fill="#6c757d">Mon</text><text x="0" y="51" font-size="9" fill="#6c757d">Wed</text><text x="0" y="77" font-size="9" fill="#6c757d">Fri</text><rect x="28" y="81" width="11" height="11" rx="2" fill="#eeeeee"><title>Day 0 &lt;x&gt;</title></rect><rect x="28" y="94" width="11" height="11" rx="2" fill="#eeeeee"><title>Day 1 &lt;x&gt;</title></rect><rect x="41" y="16" width="11" height="11" rx="2" fill="#88cc88"><title>Day 2 &lt;x&gt;</title></rect><rect x="41" y="29" width="11" height="11" rx="2" fill="#eeeeee"><title>Day 3 &lt;x&gt;</title></rect><rect x="41" y="42" width="11" height="11" rx="2" fill="#88cc88"><title>Day 4 &lt;x&gt;</title></rect><rect x="41" y="55" width="11" height="11" rx="2" fill="#88cc88"><title>Day 5 &lt;x&gt;</title></rect><rect x="41" y="68" width="11" height="11" rx="2" fill="#88cc88"><title>Day 6 &lt;x&gt;</title></rect><rect x="41" y="81" width="11" height="11" rx="2" fill="#008800"><title>Day 7 &lt;x&gt;</title></rect><rect x="41" y="94" width="11" height="11" rx="2" fill="#008800"><title>Day 8 &lt;x&gt;</title></rect><rect x="54" y="16" width="11" height="11" rx="2" fill="#008800"><title>Day 9 &lt;x&gt;</title></rect></svg>"##
+        );
+        assert!(html.contains(r##"background:#88cc88" title="5+ min""##));
+        assert!(html.contains(r##"background:#eeeeee" title="None""##));
+    }
+
+    #[test]
+    fn no_chart_lets_markup_through() {
+        let data = week();
+        let labels = vec!["<W2>".to_string()];
+        let series = [Series {
+            name: "<i>",
+            colour: CHART_FILL,
+            values: vec![1.0],
+        }];
+        let rendered = [
+            BarChart {
+                title: "<script>",
+                data: &data,
+                ..Default::default()
+            }
+            .render(),
+            StackedBarChart {
+                title: "<script>",
+                labels: &labels,
+                series: &series,
+                unit: "<u>",
+                width: 100.0,
+                height: 100.0,
+            }
+            .render(),
+            LineChart {
+                title: "<script>",
+                data: &data,
+                min: 0.0,
+                max: 10.0,
+                ticks: &[],
+                x_labels: &[1],
+                width: 100.0,
+                height: 100.0,
+            }
+            .render(),
+        ];
+        for svg in rendered {
+            let svg = svg.into_string();
+            for raw in ["<script>", "<b>", "<W2>", "<i>", "<u>", "\"Wed\""] {
+                assert!(!svg.contains(raw), "{raw} unescaped in {svg}");
+            }
+        }
+    }
+}
@@ -0,0 +1,332 @@
+//! Contextual escaping for values interpolated into templates.
+//!
+//! Markup is built with [`html!`], which is `format!` returning
+//! [`TrustedHtml`] but only interpolating [`Markup`] values: [`Text`] for
+//! element content, [`Attr`] for a quoted attribute value, [`Url`] for
+//! `href`/`src`, other `TrustedHtml` fragments, and values whose `Display` can't
+//! produce markup (numbers, our own `&'static str` constants). A `&str` or
+//! `String` from anywhere else doesn't compile until it's wrapped, and every
+//! value must be passed as an argument, since `format!`'s implicit `{name}`
+//! captures would skip the check. Markup that is known to be safe but wasn't
+//! built here must be wrapped in [`TrustedHtml::new`] explicitly, so raw output
+//! is always a visible decision.
+
+use std::fmt::{self, Display, Formatter, Write};
+
+/// URL schemes allowed in links and media sources.
+const SAFE_SCHEMES: &[&str] = &["http", "https", "mailto", "otpauth"];
+
+/// Escaped for use between tags.
+pub struct Text<'a>(pub &'a str);
+
+/// Escaped for use inside a double- or single-quoted attribute value.
+pub struct Attr<'a>(pub &'a str);
+
+/// A link target: relative URLs and [`SAFE_SCHEMES`] pass through (escaped as
+/// an attribute); anything else, like `javascript:`, becomes `#`.
+pub struct Url<'a>(pub &'a str);
+
+/// HTML emitted verbatim. Only construct this from markup we generated
+/// ourselves or have already sanitized.
+#[derive(Clone, Debug, Default)]
+pub struct TrustedHtml(String);
+
+impl TrustedHtml {
+    pub fn new(html: impl Into<String>) -> Self {
+        TrustedHtml(html.into())
+    }
+
+    /// Append another fragment.
+    pub fn push(&mut self, html: TrustedHtml) {
+        self.0.push_str(&html.0);
+    }
+
+    /// Concatenate fragments with `separator` between them.
+    pub fn join(parts: impl IntoIterator<Item = TrustedHtml>, separator: &'static str) -> Self {
+        let mut joined = TrustedHtml::default();
+        for (i, part) in parts.into_iter().enumerate() {
+            if i > 0 {
+                joined.0.push_str(separator);
+            }
+            joined.push(part);
+        }
+        joined
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl FromIterator<TrustedHtml> for TrustedHtml {
+    fn from_iter<I: IntoIterator<Item = TrustedHtml>>(iter: I) -> Self {
+        let mut html = TrustedHtml::default();
+        for part in iter {
+            html.push(part);
+        }
+        html
+    }
+}
+
+/// Values [`html!`] will interpolate.
+pub trait Markup: Display {}
+
+impl Markup for Text<'_> {}
+impl Markup for Attr<'_> {}
+impl Markup for Url<'_> {}
+impl Markup for TrustedHtml {}
+/// Literals and constants in our own source.
+impl Markup for &'static str {}
+impl<T: Markup + ?Sized> Markup for &T {}
+
+macro_rules! markup_numbers {
+    ($($t:ty),*) => { $(impl Markup for $t {})* };
+}
+markup_numbers!(i32, i64, u8, u32, u64, usize, f32, f64);
+
+/// Identity, but only for [`Markup`]; how `html!` checks its arguments.
+pub fn markup<T: Markup + ?Sized>(value: &T) -> &T {
+    value
+}
+
+/// Whether every named placeholder in `template` is one of `names`, i.e.
+/// nothing would be captured implicitly from the surrounding scope.
+pub const fn captures_only(template: &str, names: &[&str]) -> bool {
+    let bytes = template.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'{' {
+            i += 1;
+            continue;
+        }
+        if i + 1 < bytes.len() && bytes[i + 1] == b'{' {
+            i += 2;
+            continue;
+        }
+        let start = i + 1;
+        let mut end = start;
+        while end < bytes.len() && bytes[end] != b':' && bytes[end] != b'}' {
+            end += 1;
+        }
+        if end > start && !bytes[start].is_ascii_digit() && !is_listed(bytes, start, end, names) {
+            return false;
+        }
+        i = end;
+    }
+    true
+}
+
+const fn is_listed(bytes: &[u8], start: usize, end: usize, names: &[&str]) -> bool {
+    let mut n = 0;
+    while n < names.len() {
+        let name = names[n].as_bytes();
+        if name.len() == end - start {
+            let mut k = 0;
+            while k < name.len() && name[k] == bytes[start + k] {
+                k += 1;
+            }
+            if k == name.len() {
+                return true;
+            }
+        }
+        n += 1;
+    }
+    false
+}
+
+/// `format!` for markup, returning [`TrustedHtml`]. Arguments are positional
+/// (`html!("<b>{}</b>", Text(name))`), named (`name = Text(name)`) or a bare
+/// identifier standing for `name = name`; each must be [`Markup`].
+macro_rules! html {
+    ($template:literal $(, $($args:tt)*)?) => {
+        $crate::templates::html::html!(@args $template [] [] $($($args)*)?)
+    };
+    (@args $template:literal [$($pos:expr,)*] [$($name:ident = $value:expr,)*]) => {{
+        const {
+            assert!(
+                $crate::templates::html::captures_only($template, &[$(stringify!($name)),*]),
+                "html! template names a value that isn't one of its arguments",
+            )
+        };
+        $crate::templates::html::TrustedHtml::new(format!(
+            $template,
+            $($crate::templates::html::markup(&$pos),)*
+            $($name = $crate::templates::html::markup(&$value),)*
+        ))
+    }};
+    (@args $template:literal [$($pos:tt)*] [$($named:tt)*] $name:ident = $value:expr $(, $($rest:tt)*)?) => {
+        $crate::templates::html::html!(@args $template [$($pos)*] [$($named)* $name = $value,] $($($rest)*)?)
+    };
+    (@args $template:literal [$($pos:tt)*] [$($named:tt)*] $name:ident $(, $($rest:tt)*)?) => {
+        $crate::templates::html::html!(@args $template [$($pos)*] [$($named)* $name = $name,] $($($rest)*)?)
+    };
+    (@args $template:literal [$($pos:tt)*] [$($named:tt)*] $value:expr $(, $($rest:tt)*)?) => {
+        $crate::templates::html::html!(@args $template [$($pos)* $value,] [$($named)*] $($($rest)*)?)
+    };
+}
+pub(crate) use html;
+
+fn write_escaped(f: &mut Formatter<'_>, s: &str) -> fmt::Result {
+    for c in s.chars() {
+        match c {
+            '&' => f.write_str("&amp;")?,
+            '<' => f.write_str("&lt;")?,
+            '>' => f.write_str("&gt;")?,
+            '"' => f.write_str("&quot;")?,
+            '\'' => f.write_str("&#39;")?,
+            c => f.write_char(c)?,
+        }
+    }
+    Ok(())
+}
+
+fn is_safe_url(url: &str) -> bool {
+    // A scheme is whatever precedes the first ':' if that comes before any
+    // '/', '?' or '#'; otherwise the URL is relative.
+    let scheme_end = url.find(':');
+    let path_start = url.find(['/', '?', '#']);
+    match (scheme_end, path_start) {
+        (Some(colon), Some(slash)) if slash < colon => true,
+        (Some(colon), _) => {
+            let scheme = url[..colon].trim().to_ascii_lowercase();
+            SAFE_SCHEMES.contains(&scheme.as_str())
+        }
+        (None, _) => true,
+    }
+}
+
+impl Display for Text<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_escaped(f, self.0)
+    }
+}
+
+impl Display for Attr<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_escaped(f, self.0)
+    }
+}
+
+impl Display for Url<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // Browsers ignore control characters and whitespace inside schemes
+        let url: String = self.0.trim().chars().filter(|c| !c.is_control()).collect();
+        if is_safe_url(&url) {
+            write_escaped(f, &url)
+        } else {
+            f.write_str("#")
+        }
+    }
+}
+
+impl Display for TrustedHtml {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_escapes_markup() {
+        assert_eq!(
+            Text("Tom & Jerry <script>alert(1)</script>").to_string(),
+            "Tom &amp; Jerry &lt;script&gt;alert(1)&lt;/script&gt;"
+        );
+        assert_eq!(Text("&amp;").to_string(), "&amp;amp;");
+    }
+
+    #[test]
+    fn attr_escapes_both_quotes() {
+        assert_eq!(
+            Attr(r#"" onmouseover="alert(1)"#).to_string(),
+            "&quot; onmouseover=&quot;alert(1)"
+        );
+        assert_eq!(Attr("it's").to_string(), "it&#39;s");
+    }
+
+    #[test]
+    fn url_rejects_script_and_data_schemes() {
+        for url in [
+            "javascript:alert(1)",
+            "data:text/html,<script>alert(1)</script>",
+            "vbscript:msgbox(1)",
+            "JavaScript:alert(1)",
+            "DATA:text/html,hi",
+            "  javascript:alert(1)",
+            "\tjava\nscript:alert(1)",
+            "\u{0}javascript:alert(1)",
+        ] {
+            assert_eq!(Url(url).to_string(), "#", "{url:?}");
+        }
+    }
+
+    #[test]
+    fn url_keeps_safe_and_relative_links() {
+        assert_eq!(
+            Url("https://example.com/?a=1&b=2").to_string(),
+            "https://example.com/?a=1&amp;b=2"
+        );
+        assert_eq!(
+            Url("HTTPS://example.com").to_string(),
+            "HTTPS://example.com"
+        );
+        assert_eq!(
+            Url(" mailto:ada@example.com").to_string(),
+            "mailto:ada@example.com"
+        );
+        assert_eq!(Url("/journal?q=a:b").to_string(), "/journal?q=a:b");
+        assert_eq!(Url("#top").to_string(), "#top");
+        assert_eq!(
+            Url(r#"/x" onclick="y"#).to_string(),
+            "/x&quot; onclick=&quot;y"
+        );
+    }
+
+    #[test]
+    fn schemes_are_matched_case_and_whitespace_insensitively() {
+        assert!(is_safe_url("otpauth://totp/CalmControl"));
+        assert!(is_safe_url("Http://example.com"));
+        assert!(is_safe_url("notes/today"));
+        assert!(is_safe_url("./a:b"));
+        assert!(is_safe_url("?next=javascript:alert(1)"));
+        assert!(!is_safe_url("jAvAsCrIpT:alert(1)"));
+        assert!(!is_safe_url(" javascript :alert(1)"));
+        assert!(!is_safe_url("data:image/svg+xml;base64,PHN2Zz4="));
+        assert!(!is_safe_url("file:///etc/passwd"));
+    }
+
+    #[test]
+    fn html_escapes_through_its_arguments() {
+        let name = "<i>Ada</i>";
+        let inner = html!("<b>{}</b>", Text(name));
+        assert_eq!(
+            html!(
+                r#"<p title="{title}">{inner} {count}</p>"#,
+                title = Attr(name),
+                inner,
+                count = 3
+            )
+            .into_string(),
+            r#"<p title="&lt;i&gt;Ada&lt;/i&gt;"><b>&lt;i&gt;Ada&lt;/i&gt;</b> 3</p>"#
+        );
+    }
+
+    #[test]
+    fn only_listed_names_may_be_captured() {
+        assert!(captures_only("{a} {{b}} {0} {} {a:>5}", &["a"]));
+        assert!(!captures_only("{a} {b}", &["a"]));
+        assert!(!captures_only("{ab}", &["a"]));
+    }
+
+    #[test]
+    fn trusted_html_is_written_verbatim() {
+        assert_eq!(TrustedHtml::new("<b>hi</b>").to_string(), "<b>hi</b>");
+    }
+}
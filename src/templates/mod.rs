@@ -1,7 +1,30 @@
+pub mod charts;
+pub mod html;
+
+use charts::{
+    BarChart, CHART_FILL, CHART_GRID, CHART_STROKE, CalendarHeatmap, Datum, LineChart, Orientation,
+    Series, Sparkline, StackedBarChart, Tick,
+};
+use chrono::{Datelike, Months, NaiveDate};
+use html::{Attr, Text, TrustedHtml, Url, html};
+
+use crate::sanitize;
+
 use crate::models::{
+    achievements::{ACHIEVEMENTS, Achievement, UnlockedAchievement},
+    breathing::{BreathingPattern, MAX_CYCLES, MAX_PHASE_SECS, MIN_CYCLES},
+    goals::{GOAL_KINDS, Goal, GoalKind, GoalOverview, GoalPeriod},
+    history::{HISTORY_TYPES, HistoryFilter, HistoryPage},
+    insights::{INSIGHT_PERIODS, MoodAverage, MoodInsights},
+    journal::{
+        HIGHLIGHT_END, HIGHLIGHT_START, JournalEntry, JournalFilter, JournalPage, JournalPrompt,
+        next_prompt,
+    },
+    meditation::{MAX_MINUTES, MAX_PREP_SECS, MIN_MINUTES, MeditationPreset, MeditationSettings},
     newsletter::NewsletterArticle,
-    session::{DashboardStats, WeeklyMinutes},
-    user::User,
+    session::{DashboardStats, DayActivity},
+    streak::{FREEZE_EARN_DAYS, MAX_FREEZES, StreakDay, StreakHistory},
+    user::{User, timezone_names},
     video::{CATEGORIES, VideoWithUploader, category_label},
 };
 
@@ -92,25 +115,6 @@ const CSS: &str = r#"<style>
         border-radius: 12px;
         padding: 14px 18px;
     }
-    /* ── Bar chart ── */
-    .bar-wrap {
-        display: flex;
-        align-items: flex-end;
-        gap: 8px;
-        height: 90px;
-    }
-    .bar {
-        flex: 1;
-        border-radius: 6px 6px 0 0;
-        background: linear-gradient(to top, #40916c, #74c69d);
-        opacity: .75;
-        transition: opacity .2s;
-    }
-    .bar:hover { opacity: 1; }
-    .bar.active-bar {
-        background: linear-gradient(to top, #2d6a4f, #52b788);
-        opacity: 1;
-    }
     /* ── Breathing circle ── */
     .breath-circle {
         width: 200px;
@@ -317,7 +321,8 @@ const CSS: &str = r#"<style>
 
 // ── Shared base layout ─────────────────────────────────────────────────────────
 
-fn base_layout(title: &str, content: &str, logged_in: bool) -> String {
+fn base_layout(title: &str, content: &TrustedHtml, logged_in: bool) -> String {
+    let title = Text(title);
     let nav_items = if logged_in {
         r#"<li class="nav-item">
               <a class="nav-link" href="/dashboard">
@@ -325,6 +330,18 @@ fn base_layout(title: &str, content: &str, logged_in: bool) -> String {
                   Dashboard
               </a>
            </li>
+           <li class="nav-item">
+              <a class="nav-link" href="/history">
+                  <svg xmlns="http://www.w3.org/2000/svg" width="16" height="16" fill="currentColor" viewBox="0 0 16 16" style="margin-right:4px"><path d="M8 3.5a.5.5 0 0 0-1 0V9a.5.5 0 0 0 .252.434l3.5 2a.5.5 0 0 0 .496-.868L8 8.71V3.5z"/><path d="M8 16A8 8 0 1 0 8 0a8 8 0 0 0 0 16zm7-8A7 7 0 1 1 1 8a7 7 0 0 1 14 0z"/></svg>
+                  History
+              </a>
+           </li>
+           <li class="nav-item">
+              <a class="nav-link" href="/insights">
+                  <svg xmlns="http://www.w3.org/2000/svg" width="16" height="16" fill="currentColor" viewBox="0 0 16 16" style="margin-right:4px"><path fill-rule="evenodd" d="M0 0h1v15h15v1H0V0Zm14.817 3.113a.5.5 0 0 1 .07.704l-4.5 5.5a.5.5 0 0 1-.74.037L7.06 6.767l-3.656 5.027a.5.5 0 0 1-.808-.588l4-5.5a.5.5 0 0 1 .758-.06l2.609 2.61 4.15-5.073a.5.5 0 0 1 .704-.07Z"/></svg>
+                  Insights
+              </a>
+           </li>
            <li class="nav-item">
               <a class="nav-link" href="/videos">
                   <svg xmlns="http://www.w3.org/2000/svg" width="16" height="16" fill="currentColor" viewBox="0 0 16 16" style="margin-right:4px"><path d="M0 1a1 1 0 0 1 1-1h14a1 1 0 0 1 1 1v14a1 1 0 0 1-1 1H1a1 1 0 0 1-1-1V1zm4 0v6h6V1H4zm6 8H4v6h6V9zm-6-1h2v1H4V8zm0-3h2v1H4V5zm6 3h2v1h-2V8zm0-3h2v1h-2V5zM1 1v2h2V1H1zm2 3H1v2h2V4zM1 8v2h2V8H1zm2 3H1v2h2v-2z"/></svg>
@@ -370,7 +387,7 @@ fn base_layout(title: &str, content: &str, logged_in: bool) -> String {
            </li>"#
     };
 
-    format!(
+    html!(
         r##"<!DOCTYPE html>
 <html lang="en">
 <head>
@@ -408,29 +425,68 @@ fn base_layout(title: &str, content: &str, logged_in: bool) -> String {
     integrity="sha384-C6RzsynM9kWDrMNeT87bh95OGNyZPhcTNXj1NW7RuBCsyN/o0jlpcV8Qyq46cDfL"
     crossorigin="anonymous"></script>
 </body>
-</html>"##
+</html>"##,
+        title,
+        CSS,
+        nav_items,
+        content
     )
+    .into_string()
 }
 
 // ── Alert helper ───────────────────────────────────────────────────────────────
 
-fn error_alert(msg: &str) -> String {
-    format!(
+fn error_alert(msg: &str) -> TrustedHtml {
+    let msg = Text(msg);
+    html!(
         r#"<div class="alert alert-danger d-flex align-items-center gap-2 rounded-3 mb-4" role="alert">
             <svg xmlns="http://www.w3.org/2000/svg" width="18" height="18" fill="currentColor" viewBox="0 0 16 16">
                 <path d="M8.982 1.566a1.13 1.13 0 0 0-1.96 0L.165 13.233c-.457.778.091 1.767.98 1.767h13.713c.889 0 1.438-.99.98-1.767L8.982 1.566zM8 5c.535 0 .954.462.9.995l-.35 3.507a.552.552 0 0 1-1.1 0L7.1 5.995A.905.905 0 0 1 8 5zm.002 6a1 1 0 1 1 0 2 1 1 0 0 1 0-2z"/>
             </svg>
             <span>{msg}</span>
-        </div>"#
+        </div>"#,
+        msg
+    )
+}
+
+fn success_alert(msg: &str) -> TrustedHtml {
+    let msg = Text(msg);
+    html!(
+        r#"<div class="alert alert-success d-flex align-items-center gap-2 rounded-3 mb-4" role="alert">
+            <svg xmlns="http://www.w3.org/2000/svg" width="18" height="18" fill="currentColor" viewBox="0 0 16 16">
+                <path d="M16 8A8 8 0 1 1 0 8a8 8 0 0 1 16 0zm-3.97-3.03a.75.75 0 0 0-1.08.022L7.477 9.417 5.384 7.323a.75.75 0 0 0-1.06 1.06L6.97 11.03a.75.75 0 0 0 1.079-.02l3.992-4.99a.75.75 0 0 0-.01-1.05z"/>
+            </svg>
+            <span>{msg}</span>
+        </div>"#,
+        msg
+    )
+}
+
+// ── CSRF ───────────────────────────────────────────────────────────────────────
+
+/// Hidden input carrying the session's CSRF token; every POST form needs one.
+fn csrf_field(token: &str) -> TrustedHtml {
+    let token: String = token
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect();
+    html!(
+        r#"<input type="hidden" name="{}" value="{token}">"#,
+        crate::csrf::FORM_FIELD,
+        token = Attr(&token)
     )
 }
 
 // ── Login page ─────────────────────────────────────────────────────────────────
 
-pub fn login_page(error: Option<&str>) -> String {
-    let alert = error.map(|e| error_alert(e)).unwrap_or_default();
+pub fn login_page(csrf: &str, error: Option<&str>, notice: Option<&str>) -> String {
+    let csrf = csrf_field(csrf);
+    let alert = error
+        .map(error_alert)
+        .or_else(|| notice.map(success_alert))
+        .unwrap_or_default();
 
-    let content = format!(
+    let content = html!(
         r#"<div class="row justify-content-center">
     <div class="col-12 col-sm-10 col-md-7 col-lg-5 col-xl-4">
 
@@ -443,6 +499,7 @@ pub fn login_page(error: Option<&str>) -> String {
         <div class="card p-4 p-md-5">
             {alert}
             <form method="POST" action="/login" novalidate>
+                {csrf}
                 <div class="mb-3">
                     <label class="form-label" for="email">Email address</label>
                     <input
@@ -450,7 +507,10 @@ pub fn login_page(error: Option<&str>) -> String {
                         placeholder="you@example.com" autocomplete="email" required>
                 </div>
                 <div class="mb-4">
-                    <label class="form-label" for="password">Password</label>
+                    <div class="d-flex justify-content-between align-items-baseline">
+                        <label class="form-label" for="password">Password</label>
+                        <a href="/forgot-password" class="text-calm" style="font-size:.85rem">Forgot password?</a>
+                    </div>
                     <input
                         type="password" class="form-control" id="password" name="password"
                         placeholder="&bull;&bull;&bull;&bull;&bull;&bull;&bull;&bull;"
@@ -469,18 +529,271 @@ pub fn login_page(error: Option<&str>) -> String {
         </div>
 
     </div>
-</div>"#
+</div>"#,
+        alert,
+        csrf
     );
 
     base_layout("Login", &content, false)
 }
 
+// ── Forgot password page ───────────────────────────────────────────────────────
+
+pub fn forgot_password_page(csrf: &str, sent: bool, error: Option<&str>) -> String {
+    let csrf = csrf_field(csrf);
+    let body = if sent {
+        TrustedHtml::new(
+            r#"<div class="text-center py-2">
+    <div style="font-size:3rem;line-height:1">&#9993;&#65039;</div>
+    <h4 class="fw-bold text-calm mt-3 mb-2">Check your inbox</h4>
+    <p class="text-muted mb-4">
+        If an account exists for that address, we&apos;ve sent a link to reset your password.
+        It expires soon, so use it shortly.
+    </p>
+    <a href="/login" class="btn btn-calm px-4">Back to Sign In</a>
+</div>"#,
+        )
+    } else {
+        let alert = error.map(error_alert).unwrap_or_default();
+        html!(
+            r#"{alert}
+<form method="POST" action="/forgot-password" novalidate>
+    {csrf}
+    <div class="mb-4">
+        <label class="form-label" for="email">Email address</label>
+        <input
+            type="email" class="form-control" id="email" name="email"
+            placeholder="you@example.com" autocomplete="email" required>
+    </div>
+    <button type="submit" class="btn btn-calm w-100 py-2">
+        Send Reset Link
+    </button>
+</form>
+
+<hr class="my-4">
+<p class="text-center text-muted mb-0" style="font-size:.95rem">
+    Remembered it?
+    <a href="/login" class="text-calm fw-semibold">Sign in</a>
+</p>"#,
+            alert,
+            csrf
+        )
+    };
+
+    let content = html!(
+        r#"<div class="row justify-content-center">
+    <div class="col-12 col-sm-10 col-md-7 col-lg-5 col-xl-4">
+
+        <div class="text-center mb-4">
+            <div style="font-size:3rem;line-height:1">&#128273;</div>
+            <h2 class="fw-bold text-calm mt-2 mb-1">Forgot your password?</h2>
+            <p class="text-muted mb-0">We&apos;ll email you a link to choose a new one</p>
+        </div>
+
+        <div class="card p-4 p-md-5">
+            {body}
+        </div>
+
+    </div>
+</div>"#,
+        body
+    );
+
+    base_layout("Forgot Password", &content, false)
+}
+
+// ── Reset password page ────────────────────────────────────────────────────────
+
+/// An empty `token` means the link was invalid: only the error and a way to
+/// request a new link are shown.
+pub fn reset_password_page(csrf: &str, token: &str, error: Option<&str>) -> String {
+    let csrf = csrf_field(csrf);
+    let alert = error.map(error_alert).unwrap_or_default();
+    // Tokens are hex; drop anything else so a crafted value can't break out
+    // of the attribute below.
+    let token: String = token.chars().filter(char::is_ascii_alphanumeric).collect();
+
+    let body = if token.is_empty() {
+        html!(
+            r#"{alert}
+<a href="/forgot-password" class="btn btn-calm w-100 py-2">Request a New Link</a>"#,
+            alert
+        )
+    } else {
+        html!(
+            r#"{alert}
+<form method="POST" action="/reset-password" novalidate>
+    {csrf}
+    <input type="hidden" name="token" value="{token}">
+    <div class="mb-3">
+        <label class="form-label" for="password">New password</label>
+        <input
+            type="password" class="form-control" id="password" name="password"
+            placeholder="Minimum 8 characters"
+            autocomplete="new-password" required>
+    </div>
+    <div class="mb-4">
+        <label class="form-label" for="confirm_password">Confirm new password</label>
+        <input
+            type="password" class="form-control"
+            id="confirm_password" name="confirm_password"
+            placeholder="Repeat your password"
+            autocomplete="new-password" required>
+    </div>
+    <button type="submit" class="btn btn-calm w-100 py-2">
+        Set New Password
+    </button>
+</form>"#,
+            alert,
+            csrf,
+            token = Attr(&token)
+        )
+    };
+
+    let content = html!(
+        r#"<div class="row justify-content-center">
+    <div class="col-12 col-sm-10 col-md-7 col-lg-5 col-xl-4">
+
+        <div class="text-center mb-4">
+            <div style="font-size:3rem;line-height:1">&#128274;</div>
+            <h2 class="fw-bold text-calm mt-2 mb-1">Choose a new password</h2>
+            <p class="text-muted mb-0">Pick something you haven&apos;t used here before</p>
+        </div>
+
+        <div class="card p-4 p-md-5">
+            {body}
+        </div>
+
+    </div>
+</div>"#,
+        body
+    );
+
+    base_layout("Reset Password", &content, false)
+}
+
+// ── Email verification pages ───────────────────────────────────────────────────
+
+/// Result of following a verification link. `error` is `None` on success;
+/// `warning` flags a problem after the address was confirmed anyway.
+pub fn verify_email_page(error: Option<&str>, warning: Option<&str>, logged_in: bool) -> String {
+    let next = if logged_in {
+        r#"<a href="/dashboard" class="btn btn-calm px-4">Go to Dashboard</a>"#
+    } else {
+        r#"<a href="/login" class="btn btn-calm px-4">Sign In</a>"#
+    };
+
+    let alert = warning.map(error_alert).unwrap_or_default();
+    let body = match error {
+        None => html!(
+            r#"{alert}
+<div style="font-size:3.5rem">&#9989;</div>
+<h3 class="fw-bold text-calm mt-3 mb-2">Email confirmed</h3>
+<p class="text-muted mb-4">Thanks! Your address is verified and every feature is unlocked.</p>
+{next}"#,
+            alert,
+            next
+        ),
+        Some(e) => html!(
+            r#"<div style="font-size:3.5rem">&#129300;</div>
+<h3 class="fw-bold mt-3 mb-2" style="color:#555">Link not valid</h3>
+<p class="text-muted mb-4">{e}<br>You can request a new link from your dashboard.</p>
+{next}"#,
+            e = Text(e),
+            next
+        ),
+    };
+
+    let content = html!(
+        r#"<div class="row justify-content-center">
+    <div class="col-12 col-sm-9 col-md-6 col-lg-5">
+        <div class="card p-5 text-center">
+            {body}
+        </div>
+    </div>
+</div>"#,
+        body
+    );
+
+    base_layout("Verify Email", &content, logged_in)
+}
+
+pub fn verification_sent_page(email: &str) -> String {
+    let email = Text(email);
+    let content = html!(
+        r#"<div class="row justify-content-center">
+    <div class="col-12 col-sm-9 col-md-6 col-lg-5">
+        <div class="card p-5 text-center">
+            <div style="font-size:3.5rem">&#9993;&#65039;</div>
+            <h3 class="fw-bold text-calm mt-3 mb-2">Check your inbox</h3>
+            <p class="text-muted mb-4">
+                We&apos;ve sent a fresh confirmation link to <strong>{email}</strong>.
+            </p>
+            <a href="/dashboard" class="btn btn-calm px-4">Back to Dashboard</a>
+        </div>
+    </div>
+</div>"#,
+        email
+    );
+
+    base_layout("Verification Sent", &content, true)
+}
+
+/// Shown (with 403) when the verification policy blocks an action.
+pub fn verification_required_page(csrf: &str, email: &str) -> String {
+    let csrf = csrf_field(csrf);
+    let email = Text(email);
+    let content = html!(
+        r#"<div class="row justify-content-center">
+    <div class="col-12 col-sm-9 col-md-6 col-lg-5">
+        <div class="card p-5 text-center">
+            <div style="font-size:3.5rem">&#128231;</div>
+            <h3 class="fw-bold text-calm mt-3 mb-2">Please confirm your email</h3>
+            <p class="text-muted mb-4">
+                This feature unlocks once you confirm <strong>{email}</strong>.
+                Check your inbox for the link we sent when you signed up.
+            </p>
+            <form method="POST" action="/verify-email/resend" class="mb-3">
+                {csrf}
+                <button type="submit" class="btn btn-calm px-4">Resend Confirmation Email</button>
+            </form>
+            <a href="/dashboard" class="text-muted" style="font-size:.9rem">&#8592; Back to Dashboard</a>
+        </div>
+    </div>
+</div>"#,
+        email,
+        csrf
+    );
+
+    base_layout("Confirm Your Email", &content, true)
+}
+
+fn verification_banner(csrf: &str, user: &User) -> TrustedHtml {
+    let csrf = csrf_field(csrf);
+    if user.is_verified() {
+        return TrustedHtml::default();
+    }
+    let email = Text(&user.email);
+    html!(
+        r#"<div class="alert alert-warning d-flex flex-wrap align-items-center justify-content-between gap-2 rounded-3 mb-4" role="alert">
+    <span>&#128231;&nbsp; Please confirm <strong>{email}</strong> to unlock every feature.</span>
+    <form method="POST" action="/verify-email/resend" class="m-0">
+        {csrf}
+        <button type="submit" class="btn btn-sm btn-outline-dark rounded-3">Resend link</button>
+    </form>
+</div>"#,
+        email,
+        csrf
+    )
+}
+
 // ── Register page ──────────────────────────────────────────────────────────────
 
-pub fn register_page(error: Option<&str>) -> String {
-    let alert = error.map(|e| error_alert(e)).unwrap_or_default();
+pub fn register_page(csrf: &str, error: Option<&str>) -> String {
+    let csrf = csrf_field(csrf);
+    let alert = error.map(error_alert).unwrap_or_default();
 
-    let content = format!(
+    let content = html!(
         r#"<div class="row justify-content-center">
     <div class="col-12 col-sm-10 col-md-8 col-lg-6 col-xl-5">
 
@@ -493,6 +806,7 @@ pub fn register_page(error: Option<&str>) -> String {
         <div class="card p-4 p-md-5">
             {alert}
             <form method="POST" action="/register" novalidate>
+                {csrf}
                 <div class="mb-3">
                     <label class="form-label" for="name">Full Name</label>
                     <input
@@ -520,6 +834,7 @@ pub fn register_page(error: Option<&str>) -> String {
                         placeholder="Repeat your password"
                         autocomplete="new-password" required>
                 </div>
+                <input type="hidden" id="timezone" name="timezone" value="">
                 <button type="submit" class="btn btn-calm w-100 py-2">
                     Create Account
                 </button>
@@ -533,24 +848,64 @@ pub fn register_page(error: Option<&str>) -> String {
         </div>
 
     </div>
-</div>"#
+</div>
+
+{TIMEZONE_SCRIPT}"#,
+        alert,
+        csrf,
+        TIMEZONE_SCRIPT
     );
 
     base_layout("Register", &content, false)
 }
 
+/// Fills a `#timezone` field with the browser's IANA timezone.
+const TIMEZONE_SCRIPT: &str = r#"<script>
+(function () {
+    const zone = Intl.DateTimeFormat().resolvedOptions().timeZone;
+    const field = document.getElementById('timezone');
+    if (zone && field && !field.value) field.value = zone;
+})();
+</script>"#;
+
 // ── Dashboard page ─────────────────────────────────────────────────────────────
 
-pub fn dashboard_page(user: &User, stats: &DashboardStats, weekly: &WeeklyMinutes) -> String {
-    let first_name = user.name.split_whitespace().next().unwrap_or(&user.name);
-    let bars = weekly_bars(weekly);
+pub fn dashboard_page(
+    csrf: &str,
+    user: &User,
+    stats: &DashboardStats,
+    goals: &GoalOverview,
+    new_badges: &[&Achievement],
+    year: &[DayActivity],
+) -> String {
+    let first_name = Text(user.name.split_whitespace().next().unwrap_or(&user.name));
+    let week = &year[year.len().saturating_sub(7)..];
+    let weekly_chart = weekly_chart(week);
+    let minutes_trend = minutes_sparkline(&year[year.len().saturating_sub(30)..]);
+    let active_plural = if year.iter().filter(|d| d.minutes > 0).count() == 1 {
+        ""
+    } else {
+        "s"
+    };
     let sessions_today = stats.sessions_today;
     let streak = stats.streak;
+    let longest_streak = stats.longest_streak;
+    let freezes = stats.freezes;
+    let freeze_plural = if freezes == 1 { "" } else { "s" };
     let total_minutes = stats.total_minutes;
-    let tip = weekly_tip(stats);
-
-    let content = format!(
-        r#"<!-- Header row -->
+    let goal_card = goal_card(goals);
+    let tip = weekly_tip(stats, goals);
+    let banner = verification_banner(csrf, user);
+    let badge_toast = badge_toast(new_badges);
+    let heatmap = year_heatmap(year);
+    let active_days = year.iter().filter(|d| d.minutes > 0).count();
+    let year_minutes: i64 = year.iter().map(|d| d.minutes).sum();
+
+    let content = html!(
+        r#"{banner}
+{badge_toast}
+
+<!-- Header row -->
 <div class="d-flex flex-wrap align-items-center justify-content-between gap-3 mb-5">
     <div>
         <h1 class="fw-bold text-calm mb-1">Good day, {first_name}! &#128075;</h1>
@@ -574,14 +929,14 @@ pub fn dashboard_page(user: &User, stats: &DashboardStats, weekly: &WeeklyMinute
         </div>
     </div>
     <div class="col-12 col-sm-4">
-        <div class="stat-card h-100" style="background:linear-gradient(135deg,#1a6985,#2196a6)">
+        <a href="/streak" class="stat-card h-100 d-block text-decoration-none text-white" style="background:linear-gradient(135deg,#1a6985,#2196a6)">
             <div class="d-flex justify-content-between align-items-start mb-3">
                 <span class="fw-semibold" style="font-size:1.05rem">Day Streak</span>
                 <span style="font-size:1.5rem">&#128293;</span>
             </div>
             <div class="display-6 fw-bold">{streak}</div>
-            <small style="opacity:.75">Consecutive days</small>
-        </div>
+            <small style="opacity:.75">Best {longest_streak} &middot; &#10052;&#65039; {freezes} freeze{freeze_plural} banked</small>
+        </a>
     </div>
     <div class="col-12 col-sm-4">
         <div class="stat-card h-100" style="background:linear-gradient(135deg,#7b3f8c,#9b59b6)">
@@ -589,12 +944,18 @@ pub fn dashboard_page(user: &User, stats: &DashboardStats, weekly: &WeeklyMinute
                 <span class="fw-semibold" style="font-size:1.05rem">Mindful Minutes</span>
                 <span style="font-size:1.5rem">&#9200;</span>
             </div>
-            <div class="display-6 fw-bold">{total_minutes}</div>
+            <div class="d-flex justify-content-between align-items-end">
+                <div class="display-6 fw-bold">{total_minutes}</div>
+                {minutes_trend}
+            </div>
             <small style="opacity:.75">Total time practised</small>
         </div>
     </div>
 </div>
 
+<!-- Goals -->
+{goal_card}
+
 <!-- Quick-start & chart row -->
 <div class="row g-4">
     <div class="col-12 col-md-6">
@@ -602,10 +963,10 @@ pub fn dashboard_page(user: &User, stats: &DashboardStats, weekly: &WeeklyMinute
             <h5 class="fw-bold text-calm mb-3">&#9889;&nbsp; Quick Start</h5>
             <div class="d-grid gap-2">
                 <a href="/breathe" class="btn btn-outline-success rounded-3 text-start py-3 px-4" style="border-color:#cde8d6">
-                    &#128168;&nbsp; 5-min Breathing Exercise
+                    &#128168;&nbsp; Breathing Exercise
                 </a>
                 <a href="/meditate" class="btn btn-outline-success rounded-3 text-start py-3 px-4" style="border-color:#cde8d6">
-                    &#127774;&nbsp; 10-min Guided Meditation
+                    &#127774;&nbsp; Guided Meditation
                 </a>
                 <a href="/journal" class="btn btn-outline-success rounded-3 text-start py-3 px-4" style="border-color:#cde8d6">
                     &#128221;&nbsp; Daily Mood Journal
@@ -617,54 +978,298 @@ pub fn dashboard_page(user: &User, stats: &DashboardStats, weekly: &WeeklyMinute
     <div class="col-12 col-md-6">
         <div class="card p-4 h-100">
             <h5 class="fw-bold text-calm mb-3">&#128202;&nbsp; Weekly Activity</h5>
-            <div class="bar-wrap">
-                {bars}
-            </div>
-            <div class="d-flex justify-content-between mt-2">
-                <small class="text-muted">Mon</small>
-                <small class="text-muted">Tue</small>
-                <small class="text-muted">Wed</small>
-                <small class="text-muted">Thu</small>
-                <small class="text-muted">Fri</small>
-                <small class="text-muted">Sat</small>
-                <small class="text-muted">Sun</small>
-            </div>
+            {weekly_chart}
             <p class="text-muted mt-3 mb-0" style="font-size:.85rem">
                 {tip}
             </p>
         </div>
     </div>
-</div>"#
+</div>
+
+<!-- Year in practice -->
+<div class="card p-4 mt-4">
+    <div class="d-flex flex-wrap justify-content-between align-items-baseline mb-3">
+        <h5 class="fw-bold text-calm mb-0">&#128467;&#65039;&nbsp; Year in Practice</h5>
+        <small class="text-muted">{year_minutes} min over {active_days} active day{active_plural}</small>
+    </div>
+    {heatmap}
+</div>"#,
+        banner,
+        badge_toast,
+        first_name,
+        sessions_today,
+        streak,
+        longest_streak,
+        freezes,
+        freeze_plural,
+        total_minutes,
+        minutes_trend,
+        goal_card,
+        weekly_chart,
+        tip,
+        year_minutes,
+        active_days,
+        active_plural,
+        heatmap
     );
 
     base_layout("Dashboard", &content, true)
 }
 
-/// Renders seven bar chart columns driven by real weekly data.
-fn weekly_bars(minutes: &WeeklyMinutes) -> String {
-    let max = *minutes.iter().max().unwrap_or(&0);
-    minutes
+/// Announces badges earned since the last visit.
+fn badge_toast(badges: &[&Achievement]) -> TrustedHtml {
+    if badges.is_empty() {
+        return TrustedHtml::default();
+    }
+    let items: TrustedHtml = badges
         .iter()
-        .map(|&m| {
-            let h = if max == 0 {
-                5u64
-            } else {
-                ((m as f64 / max as f64) * 90.0 + 10.0) as u64
-            };
-            let label = if m == 0 {
-                "No activity".to_string()
-            } else {
-                format!("{m} min")
-            };
-            let active = if m > 0 { " active-bar" } else { "" };
-            format!(r#"<div class="bar{active}" style="height:{h}%" title="{label}"></div>"#)
+        .map(|a| {
+            html!(
+                r#"<div class="d-flex align-items-center gap-2 mb-1">
+                    <span style="font-size:1.4rem">{}</span>
+                    <div><div class="fw-semibold">{}</div><small class="text-muted">{}</small></div>
+                </div>"#,
+                a.icon,
+                a.name,
+                a.description
+            )
+        })
+        .collect();
+    let heading = if badges.len() == 1 {
+        "New badge earned!"
+    } else {
+        "New badges earned!"
+    };
+
+    html!(
+        r#"<div class="toast-container position-fixed bottom-0 end-0 p-3">
+    <div class="toast show" role="status" aria-live="polite" aria-atomic="true">
+        <div class="toast-header">
+            <strong class="me-auto text-calm">&#127881;&nbsp; {heading}</strong>
+            <button type="button" class="btn-close" data-bs-dismiss="toast" aria-label="Close"></button>
+        </div>
+        <div class="toast-body">
+            {items}
+            <a href="/profile" class="d-inline-block mt-1" style="font-size:.85rem">See all badges</a>
+        </div>
+    </div>
+</div>"#,
+        heading,
+        items
+    )
+}
+
+/// Heatmap shades from no activity to the most, by minutes practised.
+const HEATMAP_LEVELS: [(f64, &str); 5] = [
+    (0.0, "#ebedf0"),
+    (1.0, "#b7e4c7"),
+    (10.0, "#74c69d"),
+    (20.0, "#40916c"),
+    (40.0, "#1b4332"),
+];
+
+/// Session colours for the weekly chart, in `HISTORY_TYPES` order.
+const SESSION_TYPE_COLOURS: [&str; 3] = [CHART_STROKE, CHART_FILL, "#b7e4c7"];
+
+/// A year of practice as a calendar heatmap, with sessions and mood in each
+/// day's tooltip.
+fn year_heatmap(days: &[DayActivity]) -> TrustedHtml {
+    let Some(first) = days.first() else {
+        return TrustedHtml::default();
+    };
+    let data: Vec<Datum> = days
+        .iter()
+        .map(|day| {
+            let mut tooltip = format!(
+                "{}: {} min, {} session{}",
+                day.day.format("%a %-d %b %Y"),
+                day.minutes,
+                day.sessions,
+                if day.sessions == 1 { "" } else { "s" }
+            );
+            if let Some(mood) = day.mood {
+                tooltip.push_str(&format!(", mood {}", mood_summary(mood)));
+            }
+            Datum::new("", Some(day.minutes as f64), tooltip)
+        })
+        .collect();
+    let last = days.last().map_or(first.day, |d| d.day);
+    let title = format!(
+        "Minutes practised each day from {} to {}",
+        first.day.format("%-d %b %Y"),
+        last.format("%-d %b %Y")
+    );
+
+    CalendarHeatmap {
+        title: &title,
+        start: first.day,
+        data: &data,
+        levels: &HEATMAP_LEVELS,
+        unit: "min",
+    }
+    .render()
+}
+
+/// The last seven days' minutes, stacked by session type.
+fn weekly_chart(week: &[DayActivity]) -> TrustedHtml {
+    let labels: Vec<String> = week
+        .iter()
+        .map(|d| d.day.format("%a").to_string())
+        .collect();
+    let series: Vec<Series> = HISTORY_TYPES
+        .iter()
+        .zip(SESSION_TYPE_COLOURS)
+        .enumerate()
+        .map(|(t, ((_, name), colour))| Series {
+            name,
+            colour,
+            values: week.iter().map(|d| d.type_minutes[t] as f64).collect(),
         })
-        .collect::<Vec<_>>()
-        .join("\n                ")
+        .collect();
+
+    StackedBarChart {
+        title: "Minutes practised on each of the last seven days, by session type",
+        labels: &labels,
+        series: &series,
+        unit: "min",
+        width: 350.0,
+        height: 150.0,
+    }
+    .render()
+}
+
+/// Daily minutes over the last month, for the minutes stat card.
+fn minutes_sparkline(days: &[DayActivity]) -> TrustedHtml {
+    let values: Vec<f64> = days.iter().map(|d| d.minutes as f64).collect();
+    let title = format!(
+        "Minutes practised each day over the last {} days",
+        days.len()
+    );
+    Sparkline {
+        title: &title,
+        values: &values,
+        colour: "#fff",
+        width: 90.0,
+        height: 32.0,
+    }
+    .render()
+}
+
+/// Progress rings for the current period's goals, or an invitation to set some.
+fn goal_card(goals: &GoalOverview) -> TrustedHtml {
+    if !goals.has_goals() {
+        return TrustedHtml::new(
+            r#"<div class="card p-4 mb-5 d-flex flex-row flex-wrap align-items-center justify-content-between gap-3">
+    <div>
+        <h5 class="fw-bold text-calm mb-1">&#127919;&nbsp; Goals</h5>
+        <p class="text-muted mb-0" style="font-size:.9rem">Set a daily or weekly target and track your progress here.</p>
+    </div>
+    <a href="/goals" class="btn btn-calm px-4">Set goals</a>
+</div>"#,
+        );
+    }
+
+    let rings: TrustedHtml = goals.current.iter().map(progress_ring).collect();
+    html!(
+        r#"<div class="card p-4 mb-5">
+    <div class="d-flex justify-content-between align-items-baseline mb-3">
+        <h5 class="fw-bold text-calm mb-0">&#127919;&nbsp; Goals</h5>
+        <a href="/goals" class="text-muted" style="font-size:.88rem">Edit goals &amp; history</a>
+    </div>
+    <div class="row g-4 justify-content-center text-center">
+        {rings}
+    </div>
+</div>"#,
+        rings
+    )
+}
+
+/// A ring filled to the fraction of the goal reached so far.
+fn progress_ring(period: &GoalPeriod) -> TrustedHtml {
+    const R: f64 = 36.0;
+    let Some(kind) = period.kind() else {
+        return TrustedHtml::default();
+    };
+
+    let circumference = 2.0 * std::f64::consts::PI * R;
+    let filled = circumference * period.fraction();
+    let percent = (period.fraction() * 100.0).round();
+    let arc = if filled > 0.0 {
+        let colour = if period.met() {
+            CHART_FILL
+        } else {
+            CHART_STROKE
+        };
+        html!(
+            r#"<circle cx="48" cy="48" r="{R}" fill="none" stroke="{colour}" stroke-width="10" stroke-linecap="round" stroke-dasharray="{filled:.1} {circumference:.1}" transform="rotate(-90 48 48)"/>"#,
+            R,
+            colour,
+            filled,
+            circumference
+        )
+    } else {
+        TrustedHtml::default()
+    };
+    let status = if period.met() {
+        TrustedHtml::new("Goal met &#127881;")
+    } else {
+        html!("{} {} to go", period.target - period.achieved, kind.unit())
+    };
+
+    html!(
+        r#"<div class="col-12 col-sm-4">
+            <svg width="96" height="96" viewBox="0 0 96 96" role="img" aria-label="{label}: {achieved} of {target} {unit} {when}">
+                <circle cx="48" cy="48" r="{R}" fill="none" stroke="{CHART_GRID}" stroke-width="10"/>
+                {arc}
+                <text x="48" y="54" text-anchor="middle" font-size="18" font-weight="700" fill="{CHART_STROKE}">{percent}%</text>
+            </svg>
+            <div class="fw-semibold mt-2">{label}</div>
+            <div class="text-muted" style="font-size:.85rem">{achieved} / {target} {unit} {when}</div>
+            <small class="text-muted">{status}</small>
+        </div>"#,
+        label = kind.label(),
+        achieved = period.achieved,
+        target = period.target,
+        unit = kind.unit(),
+        when = kind.current_period_name(),
+        R,
+        CHART_GRID,
+        arc,
+        CHART_STROKE,
+        percent,
+        status
+    )
 }
 
-fn weekly_tip(stats: &DashboardStats) -> &'static str {
-    if stats.sessions_today == 0 {
+/// Celebrates met goals and nudges after missed ones before falling back to
+/// streak-based encouragement.
+fn weekly_tip(stats: &DashboardStats, goals: &GoalOverview) -> TrustedHtml {
+    if goals.all_current_met() {
+        return TrustedHtml::new("&#127919; Every goal met &mdash; lovely consistency!");
+    }
+    if let Some(missed) = goals.missed_previous()
+        && let Some(kind) = missed.kind()
+    {
+        let when = if kind.is_daily() {
+            "yesterday"
+        } else {
+            "last week"
+        };
+        return html!(
+            "&#127793; You reached {} of {} {} {when}. Every period is a fresh start &mdash; small steps count.",
+            missed.achieved,
+            missed.target,
+            kind.unit(),
+            when
+        );
+    }
+    if goals.all_previous_met() {
+        return TrustedHtml::new(
+            "&#127942; You met all your goals last time round. Keep the momentum going!",
+        );
+    }
+
+    TrustedHtml::new(if stats.sessions_today == 0 {
         "&#128161; Complete your first session today to start tracking progress!"
     } else if stats.streak >= 7 {
         "&#127942; Amazing! You're on a 7+ day streak. Keep it up!"
@@ -672,17 +1277,122 @@ fn weekly_tip(stats: &DashboardStats) -> &'static str {
         "&#128293; You're on a roll! Keep the streak going."
     } else {
         "&#10024; Great work today! Every session counts."
-    }
+    })
 }
 
 // ── Breathing page ─────────────────────────────────────────────────────────────
 
-pub fn breathe_page() -> String {
-    let content = r#"<div class="row justify-content-center">
+pub fn breathe_page(
+    csrf: &str,
+    patterns: &[BreathingPattern],
+    selected: &BreathingPattern,
+    cycles: i64,
+    error: Option<&str>,
+) -> String {
+    let csrf = csrf_field(csrf);
+    let alert = error.map(error_alert).unwrap_or_default();
+    let name = Text(&selected.name);
+    let summary = Text(&selected.summary());
+    let minutes = selected.planned_minutes(cycles);
+    // Sent with Begin to plan the session, and back with every POST so an
+    // error can re-render the same one
+    let settings_fields = html!(
+        r#"<input type="hidden" name="pattern" value="{}">
+                <input type="hidden" name="cycles" value="{cycles}">"#,
+        Attr(&selected.key),
+        cycles
+    );
+
+    let option = |p: &BreathingPattern| {
+        let chosen = if p.key == selected.key {
+            " selected"
+        } else {
+            ""
+        };
+        html!(
+            r#"<option value="{}"{chosen}>{}</option>"#,
+            Attr(&p.key),
+            Text(&p.name),
+            chosen
+        )
+    };
+    let builtin_options: TrustedHtml = patterns.iter().filter(|p| !p.custom).map(option).collect();
+    let custom_options: TrustedHtml = patterns.iter().filter(|p| p.custom).map(option).collect();
+    let custom_group = if custom_options.is_empty() {
+        TrustedHtml::default()
+    } else {
+        html!(
+            r#"<optgroup label="Your patterns">{custom_options}</optgroup>"#,
+            custom_options
+        )
+    };
+
+    let custom_rows: TrustedHtml = patterns
+        .iter()
+        .filter(|p| p.custom)
+        .map(|p| {
+            let id = Attr(p.key.trim_start_matches("custom-"));
+            html!(
+                r#"<li class="d-flex justify-content-between align-items-center py-2 border-bottom">
+                    <span><span class="fw-semibold">{}</span><br><small class="text-muted">{}</small></span>
+                    <form method="POST" action="/breathe/patterns/{id}/delete" class="m-0">
+                        {csrf}
+                        {settings_fields}
+                        <button type="submit" class="btn btn-sm btn-outline-danger rounded-3">Delete</button>
+                    </form>
+                </li>"#,
+                Text(&p.name),
+                Text(&p.summary()), id, csrf, settings_fields)
+        })
+        .collect();
+    let custom_list = if custom_rows.is_empty() {
+        TrustedHtml::new(
+            r#"<p class="text-muted mb-3" style="font-size:.88rem">You haven&apos;t saved any patterns yet.</p>"#,
+        )
+    } else {
+        html!(
+            r#"<ul class="list-unstyled mb-3">{custom_rows}</ul>"#,
+            custom_rows
+        )
+    };
+
+    // Embedded as JSON for the script; `<` is escaped so a pattern name can't
+    // close the script element.
+    let config = TrustedHtml::new(
+        serde_json::json!({ "phases": selected.phases, "cycles": cycles })
+            .to_string()
+            .replace('<', "\\u003c"),
+    );
+
+    let content = html!(
+        r#"<div class="row justify-content-center">
     <div class="col-12 col-md-7 col-lg-5 text-center">
 
-        <h2 class="fw-bold text-calm mb-1">&#128168;&nbsp; Breathing Exercise</h2>
-        <p class="text-muted mb-0">Box breathing &mdash; 4 seconds each phase &bull; 5 cycles</p>
+        {alert}
+
+        <h2 class="fw-bold text-calm mb-1">&#128168;&nbsp; {name}</h2>
+        <p class="text-muted mb-3">{summary} &bull; {cycles} cycles (about {minutes} min)</p>
+
+        <!-- Pattern picker -->
+        <form method="GET" action="/breathe" class="card p-3 mb-2 text-start" id="pattern-picker">
+            <div class="row g-2 align-items-end">
+                <div class="col-12 col-sm-7">
+                    <label class="form-label mb-1" for="pattern" style="font-size:.85rem">Pattern</label>
+                    <select id="pattern" name="pattern" class="form-select">
+                        <optgroup label="Built in">{builtin_options}</optgroup>
+                        {custom_group}
+                    </select>
+                </div>
+                <div class="col-6 col-sm-3">
+                    <label class="form-label mb-1" for="cycles" style="font-size:.85rem">Cycles</label>
+                    <input type="number" id="cycles" name="cycles" class="form-control"
+                           min="{MIN_CYCLES}" max="{MAX_CYCLES}" value="{cycles}">
+                </div>
+                <div class="col-6 col-sm-2">
+                    <button type="submit" class="btn btn-outline-secondary w-100 rounded-3">Set</button>
+                </div>
+            </div>
+        </form>
 
         <!-- Breathing circle -->
         <div id="breath-circle" class="breath-circle">&#127807;</div>
@@ -691,9 +1401,11 @@ pub fn breathe_page() -> String {
         <div id="phase-text" class="fw-bold fs-4 mb-1" style="color:var(--calm-mid)">Get Ready&hellip;</div>
         <div id="countdown" class="display-6 fw-bold text-calm mb-3">&nbsp;</div>
 
+        <button type="button" id="begin-btn" class="btn btn-calm px-5 py-2 mb-4">Begin</button>
+
         <!-- Progress -->
         <div class="d-flex justify-content-between mb-1">
-            <small class="text-muted">Cycle <span id="cycle-count">0</span> of 5</small>
+            <small class="text-muted">Cycle <span id="cycle-count">0</span> of {cycles}</small>
             <small class="text-muted" id="progress-label">0%</small>
         </div>
         <div class="calm-progress mb-4">
@@ -704,14 +1416,53 @@ pub fn breathe_page() -> String {
         <div id="session-done" class="session-done-banner">
             <div style="font-size:2.5rem">&#127881;</div>
             <h5 class="fw-bold text-calm mt-2 mb-1">Session Complete!</h5>
-            <p class="text-muted mb-3">You completed 5 rounds of box breathing. Well done!</p>
+            <p class="text-muted mb-3">You completed {cycles} rounds of {name}. Well done!</p>
             <form method="POST" action="/breathe/complete">
+                {csrf}
+                <input type="hidden" name="start_id" value="">
+                {settings_fields}
                 <button type="submit" class="btn btn-calm px-5 py-2">
                     &#10003;&nbsp; Save &amp; Return to Dashboard
                 </button>
             </form>
         </div>
 
+        <!-- Custom patterns -->
+        <div class="card p-4 mt-4 text-start" id="custom-patterns">
+            <h5 class="fw-bold text-calm mb-1">Your patterns</h5>
+            <p class="text-muted mb-3" style="font-size:.85rem">
+                Phase lengths in seconds. Leave a hold at 0 to skip it.
+            </p>
+            {custom_list}
+            <form method="POST" action="/breathe/patterns">
+                {csrf}
+                {settings_fields}
+                <div class="mb-2">
+                    <label class="form-label mb-1" for="pattern_name" style="font-size:.85rem">Name</label>
+                    <input type="text" id="pattern_name" name="name" class="form-control" maxlength="40" required>
+                </div>
+                <div class="row g-2 mb-3">
+                    <div class="col-6 col-sm-3">
+                        <label class="form-label mb-1" for="inhale" style="font-size:.85rem">Inhale</label>
+                        <input type="number" id="inhale" name="inhale" class="form-control" min="1" max="{MAX_PHASE_SECS}" step="0.5" value="4" required>
+                    </div>
+                    <div class="col-6 col-sm-3">
+                        <label class="form-label mb-1" for="hold_in" style="font-size:.85rem">Hold</label>
+                        <input type="number" id="hold_in" name="hold_in" class="form-control" min="0" max="{MAX_PHASE_SECS}" step="0.5" value="0">
+                    </div>
+                    <div class="col-6 col-sm-3">
+                        <label class="form-label mb-1" for="exhale" style="font-size:.85rem">Exhale</label>
+                        <input type="number" id="exhale" name="exhale" class="form-control" min="1" max="{MAX_PHASE_SECS}" step="0.5" value="6" required>
+                    </div>
+                    <div class="col-6 col-sm-3">
+                        <label class="form-label mb-1" for="hold_out" style="font-size:.85rem">Hold</label>
+                        <input type="number" id="hold_out" name="hold_out" class="form-control" min="0" max="{MAX_PHASE_SECS}" step="0.5" value="0">
+                    </div>
+                </div>
+                <button type="submit" class="btn btn-calm px-4">Save Pattern</button>
+            </form>
+        </div>
+
         <!-- Back link -->
         <div class="mt-4">
             <a href="/dashboard" class="text-muted" style="font-size:.9rem">&#8592; Back to Dashboard</a>
@@ -720,20 +1471,41 @@ pub fn breathe_page() -> String {
     </div>
 </div>
 
-<script>
-(function () {
-    const PHASES = [
-        { name: "Inhale",  seconds: 4, expand: true  },
-        { name: "Hold",    seconds: 4, expand: true  },
-        { name: "Exhale",  seconds: 4, expand: false },
-        { name: "Hold",    seconds: 4, expand: false },
-    ];
-    const TOTAL_CYCLES = 5;
-    const TOTAL_STEPS  = TOTAL_CYCLES * PHASES.length; // 20
+<script type="application/json" id="breath-config">{config}</script>
+{BREATHE_SCRIPT}"#,
+        alert,
+        name,
+        summary,
+        cycles,
+        minutes,
+        builtin_options,
+        custom_group,
+        MIN_CYCLES,
+        MAX_CYCLES,
+        csrf,
+        settings_fields,
+        custom_list,
+        MAX_PHASE_SECS,
+        config,
+        BREATHE_SCRIPT
+    );
+
+    base_layout("Breathing Exercise", &content, true)
+}
 
-    let cycle    = 0;
-    let phaseIdx = 0;
-    let tick     = PHASES[0].seconds; // seconds remaining in current phase
+/// Drives the circle through the phases in `#breath-config`. Kept out of the
+/// `format!` above so the braces don't need escaping.
+const BREATHE_SCRIPT: &str = r#"<script>
+(function () {
+    const config       = JSON.parse(document.getElementById('breath-config').textContent);
+    const PHASES       = config.phases;
+    const TOTAL_CYCLES = config.cycles;
+    const TOTAL_STEPS  = TOTAL_CYCLES * PHASES.length;
+
+    let cycle     = 0;
+    let phaseIdx  = 0;
+    let phaseEnds = 0; // timestamp (ms) when the current phase finishes
+    let timer     = null;
 
     const circle      = document.getElementById('breath-circle');
     const phaseText   = document.getElementById('phase-text');
@@ -742,75 +1514,189 @@ pub fn breathe_page() -> String {
     const progressBar = document.getElementById('progress-bar');
     const progressLbl = document.getElementById('progress-label');
     const doneDiv     = document.getElementById('session-done');
+    const beginBtn    = document.getElementById('begin-btn');
+    const picker      = document.getElementById('pattern-picker');
+    const customCard  = document.getElementById('custom-patterns');
 
-    function applyCircle(expand) {
-        if (expand) {
-            circle.classList.add('expand');
-        } else {
-            circle.classList.remove('expand');
-        }
-    }
-
-    function render() {
+    function startPhase() {
         const phase = PHASES[phaseIdx];
-        phaseText.textContent = phase.name;
-        countdownEl.textContent = tick;
+        phaseText.textContent = phase.label;
         cycleEl.textContent = cycle + 1;
-        applyCircle(phase.expand);
+        circle.style.transitionDuration = phase.seconds + 's';
+        circle.classList.toggle('expand', phase.expand);
+        phaseEnds = Date.now() + phase.seconds * 1000;
 
-        const completedSteps = cycle * PHASES.length + phaseIdx;
-        const pct = Math.round(completedSteps / TOTAL_STEPS * 100);
+        const pct = Math.round((cycle * PHASES.length + phaseIdx) / TOTAL_STEPS * 100);
         progressBar.style.width = pct + '%';
         progressLbl.textContent = pct + '%';
     }
 
-    // Small delay so first CSS transition fires
-    setTimeout(() => {
-        render();
+    function tick() {
+        const left = phaseEnds - Date.now();
+        if (left > 0) {
+            countdownEl.textContent = Math.ceil(left / 1000);
+            return;
+        }
 
-        const timer = setInterval(() => {
-            tick--;
-            if (tick <= 0) {
-                phaseIdx++;
-                if (phaseIdx >= PHASES.length) {
-                    phaseIdx = 0;
-                    cycle++;
-                }
-                if (cycle >= TOTAL_CYCLES) {
-                    clearInterval(timer);
-                    progressBar.style.width = '100%';
-                    progressLbl.textContent = '100%';
-                    phaseText.textContent = 'Well done!';
-                    countdownEl.textContent = '';
-                    doneDiv.style.display = 'block';
-                    return;
-                }
-                tick = PHASES[phaseIdx].seconds;
-            }
-            render();
-        }, 1000);
-    }, 300);
+        phaseIdx++;
+        if (phaseIdx >= PHASES.length) {
+            phaseIdx = 0;
+            cycle++;
+        }
+        if (cycle >= TOTAL_CYCLES) {
+            clearInterval(timer);
+            circle.classList.remove('expand');
+            progressBar.style.width = '100%';
+            progressLbl.textContent = '100%';
+            phaseText.textContent = 'Well done!';
+            countdownEl.textContent = '';
+            doneDiv.style.display = 'block';
+            return;
+        }
+        startPhase();
+        tick();
+    }
+
+    // The server times the session from Begin, using the save form's fields,
+    // and answers with the id the save form sends back
+    beginBtn.addEventListener('click', () => {
+        beginBtn.disabled = true;
+        const saveForm = doneDiv.querySelector('form');
+        fetch('/breathe/begin', {
+            method: 'POST',
+            body: new URLSearchParams(new FormData(saveForm)),
+        })
+            .then(res => {
+                if (!res.ok) throw new Error(res.statusText);
+                return res.text();
+            })
+            .then(startId => {
+                saveForm.elements.start_id.value = startId;
+                beginBtn.style.display = 'none';
+                picker.style.display = 'none';
+                customCard.style.display = 'none';
+
+                // Small delay so first CSS transition fires
+                setTimeout(() => {
+                    startPhase();
+                    tick();
+                    timer = setInterval(tick, 100);
+                }, 300);
+            })
+            .catch(() => {
+                beginBtn.disabled = false;
+                phaseText.textContent = "Couldn't start. Please reload the page.";
+            });
+    });
 }());
 </script>"#;
 
-    base_layout("Breathing Exercise", content, true)
-}
-
 // ── Meditation page ─────────────────────────────────────────────────────────────
 
-pub fn meditate_page() -> String {
-    let content = r#"<div class="row justify-content-center">
+pub fn meditate_page(
+    csrf: &str,
+    presets: &[MeditationPreset],
+    settings: &MeditationSettings,
+    error: Option<&str>,
+) -> String {
+    let csrf = csrf_field(csrf);
+    let alert = error.map(error_alert).unwrap_or_default();
+    let summary = Text(&settings.summary());
+    let minutes = settings.minutes;
+    let interval = settings.interval_min;
+    let prep = settings.prep_secs;
+    // Sent with Begin to plan the session, and back on completion so an
+    // error can re-render the same one
+    let settings_fields = html!(
+        r#"<input type="hidden" name="minutes" value="{minutes}">
+                <input type="hidden" name="interval" value="{interval}">
+                <input type="hidden" name="prep" value="{prep}">"#,
+        minutes,
+        interval,
+        prep
+    );
+
+    let preset_rows: TrustedHtml = presets
+        .iter()
+        .map(|p| {
+            let id = Attr(&p.id);
+            html!(
+                r#"<li class="d-flex justify-content-between align-items-center py-2 border-bottom">
+                    <a href="/meditate?preset={id}" class="text-decoration-none" style="color:#512da8">
+                        <span class="fw-semibold">{}</span><br><small class="text-muted">{}</small>
+                    </a>
+                    <form method="POST" action="/meditate/presets/{id}/delete" class="m-0">
+                        {csrf}
+                        <button type="submit" class="btn btn-sm btn-outline-danger rounded-3">Delete</button>
+                    </form>
+                </li>"#,
+                Text(&p.name),
+                Text(&p.settings().summary()), id, csrf)
+        })
+        .collect();
+    let preset_list = if preset_rows.is_empty() {
+        TrustedHtml::new(
+            r#"<p class="text-muted mb-3" style="font-size:.88rem">You haven&apos;t saved any presets yet.</p>"#,
+        )
+    } else {
+        html!(
+            r#"<ul class="list-unstyled mb-3">{preset_rows}</ul>"#,
+            preset_rows
+        )
+    };
+
+    // Numbers only, but escaped the same way as the breathing config
+    let config = TrustedHtml::new(
+        serde_json::json!(settings)
+            .to_string()
+            .replace('<', "\\u003c"),
+    );
+
+    let content = html!(
+        r#"<div class="row justify-content-center">
     <div class="col-12 col-md-7 col-lg-5 text-center">
 
+        {alert}
+
         <h2 class="fw-bold mb-1" style="color:#512da8">&#127774;&nbsp; Guided Meditation</h2>
         <p class="text-muted mb-0">Find a comfortable position, close your eyes, and breathe naturally</p>
+        <p class="text-muted mb-3" style="font-size:.9rem">{summary}</p>
+
+        <!-- Timer settings -->
+        <form method="GET" action="/meditate" class="card p-3 mb-2 text-start" id="timer-settings">
+            <div class="row g-2 align-items-end">
+                <div class="col-4 col-sm-3">
+                    <label class="form-label mb-1" for="minutes" style="font-size:.85rem">Minutes</label>
+                    <input type="number" id="minutes" name="minutes" class="form-control"
+                           min="{MIN_MINUTES}" max="{MAX_MINUTES}" value="{minutes}">
+                </div>
+                <div class="col-4 col-sm-3">
+                    <label class="form-label mb-1" for="interval" style="font-size:.85rem">Bell every</label>
+                    <input type="number" id="interval" name="interval" class="form-control"
+                           min="0" max="{MAX_MINUTES}" value="{interval}" title="Minutes between bells, 0 for none">
+                </div>
+                <div class="col-4 col-sm-3">
+                    <label class="form-label mb-1" for="prep" style="font-size:.85rem">Prep (s)</label>
+                    <input type="number" id="prep" name="prep" class="form-control"
+                           min="0" max="{MAX_PREP_SECS}" value="{prep}">
+                </div>
+                <div class="col-12 col-sm-3">
+                    <button type="submit" class="btn btn-outline-secondary w-100 rounded-3">Set</button>
+                </div>
+            </div>
+        </form>
 
         <!-- Meditation orb -->
         <div class="meditate-orb">&#129445;</div>
 
         <!-- Timer display -->
-        <div id="timer-display" class="display-4 fw-bold mb-1" style="color:#512da8; font-variant-numeric: tabular-nums;">10:00</div>
-        <p id="timer-label" class="text-muted mb-3">remaining</p>
+        <div id="timer-display" class="display-4 fw-bold mb-1" style="color:#512da8; font-variant-numeric: tabular-nums;">{minutes}:00</div>
+        <p id="timer-label" class="text-muted mb-3">ready when you are</p>
+
+        <button type="button" id="begin-btn" class="btn px-5 py-2 mb-4"
+                style="background:linear-gradient(135deg,#7b3f8c,#9b59b6);color:#fff;border:none;border-radius:10px;font-weight:500;">
+            Begin
+        </button>
 
         <!-- Progress -->
         <div class="calm-progress mb-2" style="background:#ede7f6;">
@@ -824,8 +1710,11 @@ pub fn meditate_page() -> String {
         <div id="session-done" class="session-done-banner" style="border-color:#b39ddb; background:linear-gradient(135deg,#ede7f6,#d1c4e9);">
             <div style="font-size:2.5rem">&#129309;</div>
             <h5 class="fw-bold mt-2 mb-1" style="color:#512da8">Meditation Complete!</h5>
-            <p class="text-muted mb-3">10 mindful minutes. Your mind thanks you.</p>
+            <p class="text-muted mb-3">{minutes} mindful minutes. Your mind thanks you.</p>
             <form method="POST" action="/meditate/complete">
+                {csrf}
+                <input type="hidden" name="start_id" value="">
+                {settings_fields}
                 <button type="submit" class="btn px-5 py-2"
                         style="background:linear-gradient(135deg,#7b3f8c,#9b59b6);color:#fff;border:none;border-radius:10px;font-weight:500;">
                     &#10003;&nbsp; Save &amp; Return to Dashboard
@@ -835,12 +1724,35 @@ pub fn meditate_page() -> String {
 
         <!-- Skip / complete early -->
         <form method="POST" action="/meditate/complete" class="mt-3" id="skip-form" style="display:none;">
+            {csrf}
+            <input type="hidden" name="start_id" value="">
+            {settings_fields}
             <button type="submit" class="btn btn-sm"
                     style="border:1.5px solid #9b59b6;color:#9b59b6;border-radius:8px;font-size:.85rem;">
                 &#9654;&nbsp; Mark Complete Early
             </button>
         </form>
 
+        <!-- Presets -->
+        <div class="card p-4 mt-4 text-start" id="meditation-presets">
+            <h5 class="fw-bold mb-1" style="color:#512da8">Your presets</h5>
+            <p class="text-muted mb-3" style="font-size:.85rem">
+                Pick a preset to load it, or save the current settings.
+            </p>
+            {preset_list}
+            <form method="POST" action="/meditate/presets">
+                {csrf}
+                <input type="hidden" name="minutes" value="{minutes}">
+                <input type="hidden" name="interval" value="{interval}">
+                <input type="hidden" name="prep" value="{prep}">
+                <label class="form-label mb-1" for="preset_name" style="font-size:.85rem">Name</label>
+                <div class="input-group">
+                    <input type="text" id="preset_name" name="name" class="form-control" maxlength="40" required>
+                    <button type="submit" class="btn btn-outline-secondary">Save Preset</button>
+                </div>
+            </form>
+        </div>
+
         <div class="mt-3">
             <a href="/dashboard" class="text-muted" style="font-size:.9rem">&#8592; Back to Dashboard</a>
         </div>
@@ -848,156 +1760,1604 @@ pub fn meditate_page() -> String {
     </div>
 </div>
 
-<script>
+<script type="application/json" id="meditate-config">{config}</script>
+{MEDITATE_SCRIPT}"#,
+        alert,
+        summary,
+        MIN_MINUTES,
+        MAX_MINUTES,
+        minutes,
+        interval,
+        MAX_PREP_SECS,
+        prep,
+        csrf,
+        settings_fields,
+        preset_list,
+        config,
+        MEDITATE_SCRIPT
+    );
+
+    base_layout("Guided Meditation", &content, true)
+}
+
+/// Runs the preparation countdown, then the timer described by
+/// `#meditate-config`, ringing a bell at each interval and at the end.
+const MEDITATE_SCRIPT: &str = r#"<script>
 (function () {
-    const TOTAL = 600; // 10 minutes in seconds
-    let remaining = TOTAL;
-    let started = false;
+    const config   = JSON.parse(document.getElementById('meditate-config').textContent);
+    const TOTAL    = config.minutes * 60;
+    const INTERVAL = config.interval_min * 60;
+    const PREP     = config.prep_secs;
+
+    const affirmations = [
+        "\u201cYou are present. You are calm.\u201d",
+        "\u201cLet thoughts pass like clouds.\u201d",
+        "\u201cBreathe in peace, breathe out tension.\u201d",
+        "\u201cThis moment is enough.\u201d",
+        "\u201cYou are safe. You are still.\u201d",
+        "\u201cEvery breath brings clarity.\u201d",
+        "\u201cYou deserve this time for yourself.\u201d",
+    ];
+    let affirmIdx = 0;
+
+    const timerEl   = document.getElementById('timer-display');
+    const labelEl   = document.getElementById('timer-label');
+    const bar       = document.getElementById('progress-bar');
+    const doneDiv   = document.getElementById('session-done');
+    const skipForm  = document.getElementById('skip-form');
+    const affirmEl  = document.getElementById('affirmation');
+    const beginBtn  = document.getElementById('begin-btn');
+    const settings  = document.getElementById('timer-settings');
+    const presets   = document.getElementById('meditation-presets');
+
+    let audio = null;
+    let startsAt = 0; // timestamp (ms) when the meditation itself begins
+    let bellsRung = 0;
+    let timer = null;
+
+    function fmt(s) {
+        const m = Math.floor(s / 60);
+        const sec = s % 60;
+        return m + ':' + String(sec).padStart(2, '0');
+    }
+
+    // A soft sine tone that fades out, standing in for a singing bowl
+    function bell() {
+        if (!audio) return;
+        const osc = audio.createOscillator();
+        const gain = audio.createGain();
+        osc.type = 'sine';
+        osc.frequency.value = 528;
+        gain.gain.setValueAtTime(0.3, audio.currentTime);
+        gain.gain.exponentialRampToValueAtTime(0.001, audio.currentTime + 4);
+        osc.connect(gain).connect(audio.destination);
+        osc.start();
+        osc.stop(audio.currentTime + 4);
+    }
+
+    function tick() {
+        const now = Date.now();
+        if (now < startsAt) {
+            timerEl.textContent = Math.ceil((startsAt - now) / 1000);
+            return;
+        }
+
+        const elapsed = Math.min(Math.floor((now - startsAt) / 1000), TOTAL);
+        if (labelEl.textContent !== 'remaining') {
+            labelEl.textContent = 'remaining';
+            bell();
+        }
+        timerEl.textContent = fmt(TOTAL - elapsed);
+        bar.style.width = (elapsed / TOTAL * 100) + '%';
+
+        if (elapsed >= 30) skipForm.style.display = 'block';
+
+        if (INTERVAL > 0) {
+            const due = Math.floor(elapsed / INTERVAL);
+            if (due > bellsRung && elapsed < TOTAL) {
+                bellsRung = due;
+                bell();
+            }
+        }
+
+        if (elapsed >= TOTAL) {
+            clearInterval(timer);
+            bell();
+            timerEl.textContent = '0:00';
+            labelEl.textContent = 'complete';
+            skipForm.style.display = 'none';
+            doneDiv.style.display = 'block';
+        }
+    }
+
+    affirmEl.style.transition = 'opacity .5s';
+
+    beginBtn.addEventListener('click', () => {
+        beginBtn.disabled = true;
+
+        // Browsers only allow audio to start from a user gesture
+        const Ctx = window.AudioContext || window.webkitAudioContext;
+        if (Ctx && !audio) audio = new Ctx();
+
+        // The server times the session from Begin, using the save form's
+        // fields, and answers with the id both save forms send back
+        fetch('/meditate/begin', {
+            method: 'POST',
+            body: new URLSearchParams(new FormData(doneDiv.querySelector('form'))),
+        })
+            .then(res => {
+                if (!res.ok) throw new Error(res.statusText);
+                return res.text();
+            })
+            .then(startId => {
+                document.querySelectorAll('input[name="start_id"]').forEach(input => {
+                    input.value = startId;
+                });
+                beginBtn.style.display = 'none';
+                settings.style.display = 'none';
+                presets.style.display = 'none';
+
+                startsAt = Date.now() + PREP * 1000;
+                labelEl.textContent = 'settle in';
+
+                // Rotate affirmations every 30s
+                setInterval(() => {
+                    affirmIdx = (affirmIdx + 1) % affirmations.length;
+                    affirmEl.style.opacity = '0';
+                    setTimeout(() => {
+                        affirmEl.textContent = affirmations[affirmIdx];
+                        affirmEl.style.opacity = '1';
+                    }, 500);
+                }, 30000);
+
+                tick();
+                timer = setInterval(tick, 250);
+            })
+            .catch(() => {
+                beginBtn.disabled = false;
+                labelEl.textContent = "couldn't start, please reload the page";
+            });
+    });
+}());
+</script>"#;
+
+// ── History page ────────────────────────────────────────────────────────────────
+
+/// Query string for `page` of the current filter. Every value has already
+/// been validated, so nothing needs percent-encoding.
+fn history_params(filter: &HistoryFilter, page: i64) -> String {
+    let mut params = Vec::new();
+    if let Some(t) = &filter.item_type {
+        params.push(format!("type={t}"));
+    }
+    if let Some(d) = filter.from {
+        params.push(format!("from={d}"));
+    }
+    if let Some(d) = filter.to {
+        params.push(format!("to={d}"));
+    }
+    params.push(format!("page={page}"));
+    params.join("&")
+}
+
+pub fn history_page(
+    csrf: &str,
+    page: &HistoryPage,
+    filter: &HistoryFilter,
+    patterns: &[BreathingPattern],
+    error: Option<&str>,
+    notice: Option<&str>,
+) -> String {
+    let csrf = csrf_field(csrf);
+    let alert = error.map(error_alert).unwrap_or_default();
+    let notice = notice.map(success_alert).unwrap_or_default();
+    let total = page.total;
+    let page_count = page.page_count();
+    let current = filter.page.min(page_count);
+    let from = filter.from.map(|d| d.to_string()).unwrap_or_default();
+    let to = filter.to.map(|d| d.to_string()).unwrap_or_default();
+
+    let type_options: TrustedHtml = HISTORY_TYPES
+        .iter()
+        .map(|(value, label)| {
+            let chosen = if filter.item_type.as_deref() == Some(*value) {
+                " selected"
+            } else {
+                ""
+            };
+            html!(
+                r#"<option value="{value}"{chosen}>{label}</option>"#,
+                value,
+                chosen,
+                label
+            )
+        })
+        .collect();
+
+    let rows: TrustedHtml = page
+        .items
+        .iter()
+        .map(|item| {
+            let when = Text(item.occurred_at.get(..16).unwrap_or(&item.occurred_at));
+            let (icon, title, detail) = match item.item_type.as_str() {
+                "breathing" => {
+                    let name = item
+                        .pattern
+                        .as_deref()
+                        .and_then(|key| patterns.iter().find(|p| p.key == key))
+                        .map(|p| p.name.as_str())
+                        .unwrap_or("Breathing");
+                    let cycles = item
+                        .cycles
+                        .map(|c| format!("{c} cycles · "))
+                        .unwrap_or_default();
+                    (
+                        "&#128168;",
+                        html!("{}", Text(name)),
+                        html!("{}{} min", Text(&cycles), item.duration_min),
+                    )
+                }
+                "meditation" => (
+                    "&#127774;",
+                    TrustedHtml::new("Meditation"),
+                    html!("{} min", item.duration_min),
+                ),
+                _ => {
+                    let (face, label) = mood_face(item.mood.unwrap_or(3));
+                    let note = item.note.as_deref().unwrap_or_default();
+                    let excerpt: String = note.chars().take(160).collect();
+                    let ellipsis = if note.chars().count() > 160 { "&hellip;" } else { "" };
+                    (
+                        face,
+                        html!(
+                            r#"<a href="/journal/entries/{}" class="text-calm text-decoration-none">Journal &middot; {label}</a>"#,
+                            Attr(&item.id), label),
+                        html!("{}{ellipsis}", Text(&excerpt), ellipsis),
+                    )
+                }
+            };
+
+            let delete = if item.is_journal() {
+                TrustedHtml::default()
+            } else {
+                html!(
+                    r#"<form method="POST" action="/history/sessions/{}/delete?{}" class="m-0">
+                        {csrf}
+                        <button type="submit" class="btn btn-sm btn-outline-danger rounded-3">Delete</button>
+                    </form>"#,
+                    Attr(&item.id),
+                    Attr(&history_params(filter, current)), csrf)
+            };
+
+            html!(
+                r#"<li class="d-flex align-items-start gap-3 py-3 border-bottom">
+                    <span style="font-size:1.6rem; line-height:1">{icon}</span>
+                    <div class="flex-grow-1">
+                        <div class="fw-semibold">{title}</div>
+                        <div class="text-muted" style="font-size:.88rem">{detail}</div>
+                        <small class="text-muted">{when}</small>
+                    </div>
+                    {delete}
+                </li>"#, icon, title, detail, when, delete)
+        })
+        .collect();
+
+    let list = if rows.is_empty() {
+        TrustedHtml::new(
+            r#"<p class="text-muted text-center my-4">Nothing matches these filters yet.</p>"#,
+        )
+    } else {
+        html!(r#"<ul class="list-unstyled mb-0">{rows}</ul>"#, rows)
+    };
+
+    let prev = if current > 1 {
+        html!(
+            r#"<a href="/history?{}" class="btn btn-sm btn-outline-secondary rounded-3">&#8592; Newer</a>"#,
+            Attr(&history_params(filter, current - 1))
+        )
+    } else {
+        TrustedHtml::default()
+    };
+    let next = if current < page_count {
+        html!(
+            r#"<a href="/history?{}" class="btn btn-sm btn-outline-secondary rounded-3">Older &#8594;</a>"#,
+            Attr(&history_params(filter, current + 1))
+        )
+    } else {
+        TrustedHtml::default()
+    };
+
+    let content = html!(
+        r#"<div class="row justify-content-center">
+    <div class="col-12 col-lg-8">
+
+        {alert}
+        {notice}
+
+        <h2 class="fw-bold text-calm mb-1">&#128197;&nbsp; Your History</h2>
+        <p class="text-muted mb-4">Every session and journal entry you&apos;ve logged</p>
+
+        <!-- Filters -->
+        <form method="GET" action="/history" class="card p-3 mb-4">
+            <div class="row g-2 align-items-end">
+                <div class="col-12 col-sm-4">
+                    <label class="form-label mb-1" for="type" style="font-size:.85rem">Type</label>
+                    <select id="type" name="type" class="form-select">
+                        <option value="">Everything</option>
+                        {type_options}
+                    </select>
+                </div>
+                <div class="col-6 col-sm-3">
+                    <label class="form-label mb-1" for="from" style="font-size:.85rem">From</label>
+                    <input type="date" id="from" name="from" class="form-control" value="{from}">
+                </div>
+                <div class="col-6 col-sm-3">
+                    <label class="form-label mb-1" for="to" style="font-size:.85rem">To</label>
+                    <input type="date" id="to" name="to" class="form-control" value="{to}">
+                </div>
+                <div class="col-12 col-sm-2 d-flex gap-2">
+                    <button type="submit" class="btn btn-calm flex-fill rounded-3">Filter</button>
+                </div>
+            </div>
+            <div class="mt-2" style="font-size:.85rem">
+                <a href="/history" class="text-muted">Clear filters</a>
+            </div>
+        </form>
+
+        <div class="card p-4">
+            <p class="text-muted mb-2" style="font-size:.85rem">{total} matching</p>
+            {list}
+            <div class="d-flex justify-content-between align-items-center mt-3">
+                <div>{prev}</div>
+                <small class="text-muted">Page {current} of {page_count}</small>
+                <div>{next}</div>
+            </div>
+        </div>
+
+        <div class="text-center mt-4">
+            <a href="/dashboard" class="text-muted" style="font-size:.9rem">&#8592; Back to Dashboard</a>
+        </div>
+
+    </div>
+</div>"#,
+        alert,
+        notice,
+        type_options,
+        from = Attr(&from),
+        to = Attr(&to),
+        total,
+        list,
+        prev,
+        current,
+        page_count,
+        next
+    );
+
+    base_layout("History", &content, true)
+}
+
+// ── Insights page ───────────────────────────────────────────────────────────────
+
+/// "3.8 (Good)" for an average mood.
+fn mood_summary(mood: f64) -> String {
+    format!("{mood:.1} ({})", mood_face(mood.round() as i64).1)
+}
+
+/// Daily mood as a line joining the days that have entries, each with a dot
+/// and tooltip.
+fn mood_line_chart(insights: &MoodInsights) -> TrustedHtml {
+    let n = insights.daily.len();
+    let format = if n <= 7 { "%a" } else { "%-d %b" };
+    let data: Vec<Datum> = insights
+        .daily
+        .iter()
+        .enumerate()
+        .map(|(i, mood)| {
+            let date = insights.start + chrono::Duration::days(i as i64);
+            let tooltip = match mood {
+                Some(m) => format!("{}: {}", date.format("%a %-d %b"), mood_summary(*m)),
+                None => String::new(),
+            };
+            Datum::new(date.format(format).to_string(), *mood, tooltip)
+        })
+        .collect();
+    let ticks: Vec<Tick> = MOODS
+        .iter()
+        .zip(1..)
+        .map(|((face, label), level)| Tick {
+            value: level as f64,
+            label: TrustedHtml::new(*face),
+            tooltip: label.to_string(),
+        })
+        .collect();
+    // Every day for a week, otherwise five evenly spaced dates
+    let x_labels: Vec<usize> = if n <= 7 {
+        (0..n).collect()
+    } else {
+        (0..5).map(|k| k * (n - 1) / 4).collect()
+    };
+    let title = format!("Average mood per day over the last {} days", insights.days);
+
+    LineChart {
+        title: &title,
+        data: &data,
+        min: 1.0,
+        max: 5.0,
+        ticks: &ticks,
+        x_labels: &x_labels,
+        width: 640.0,
+        height: 220.0,
+    }
+    .render()
+}
+
+/// "across 3 days" for an average.
+fn days_covered(avg: &MoodAverage) -> String {
+    format!(
+        "across {} day{}",
+        avg.days,
+        if avg.days == 1 { "" } else { "s" }
+    )
+}
+
+/// Average mood for each weekday as vertical bars out of 5.
+fn weekday_mood_chart(by_weekday: &[MoodAverage; 7]) -> TrustedHtml {
+    const NAMES: [&str; 7] = [
+        "Monday",
+        "Tuesday",
+        "Wednesday",
+        "Thursday",
+        "Friday",
+        "Saturday",
+        "Sunday",
+    ];
+
+    let data: Vec<Datum> = by_weekday
+        .iter()
+        .zip(NAMES)
+        .map(|(avg, name)| {
+            let tooltip = match avg.mood {
+                Some(m) => format!("{name}s: {} {}", mood_summary(m), days_covered(avg)),
+                None => format!("No entries on {name}s"),
+            };
+            Datum::new(&name[..3], avg.mood, tooltip)
+        })
+        .collect();
+
+    BarChart {
+        title: "Average mood by day of the week",
+        data: &data,
+        max: Some(5.0),
+        value_decimals: Some(1),
+        ..BarChart::default()
+    }
+    .render()
+}
+
+/// Mood on days with and without a breathing or meditation session, as two
+/// horizontal bars out of 5.
+fn practice_mood_chart(with: &MoodAverage, without: &MoodAverage) -> TrustedHtml {
+    let data: Vec<Datum> = [("With practice", with), ("Without practice", without)]
+        .iter()
+        .map(|(label, avg)| {
+            let value = avg
+                .mood
+                .map_or_else(|| "no entries".to_string(), mood_summary);
+            Datum::new(
+                *label,
+                avg.mood,
+                format!("{label}: {value} {}", days_covered(avg)),
+            )
+        })
+        .collect();
+
+    BarChart {
+        title: "Average mood on days with and without a breathing or meditation session",
+        data: &data,
+        max: Some(5.0),
+        orientation: Orientation::Horizontal,
+        value_decimals: Some(1),
+        fills: &[CHART_STROKE, CHART_FILL],
+        height: 88.0,
+        ..BarChart::default()
+    }
+    .render()
+}
+
+/// One sentence comparing mood with and without practice, when both exist.
+fn practice_takeaway(with: &MoodAverage, without: &MoodAverage) -> &'static str {
+    match (with.mood, without.mood) {
+        (Some(w), Some(wo)) if w - wo >= 0.25 => {
+            "&#127793; You tend to feel better on days you practise."
+        }
+        (Some(w), Some(wo)) if wo - w >= 0.25 => {
+            "&#128173; Your mood has been lower on practice days &mdash; perhaps you reach for a session when you need it most."
+        }
+        (Some(_), Some(_)) => "&#9878;&#65039; Your mood is about the same either way so far.",
+        _ => {
+            "&#128161; Journal on days with and without a session to see how practice affects your mood."
+        }
+    }
+}
+
+pub fn insights_page(insights: &MoodInsights) -> String {
+    let days = insights.days;
+
+    let period_links: TrustedHtml = INSIGHT_PERIODS
+        .iter()
+        .map(|&p| {
+            let active = if p == days { " active" } else { "" };
+            html!(
+                r#"<a href="/insights?days={p}" class="filter-pill text-decoration-none me-2{active}">{p} days</a>"#, p, active)
+        })
+        .collect();
+
+    let period_cards: TrustedHtml = insights
+        .periods
+        .iter()
+        .map(|(p, avg)| {
+            let (face, value) = match avg.mood {
+                Some(m) => (mood_face(m.round() as i64).0, html!("{m:.1}", m)),
+                None => ("&#128172;", TrustedHtml::new("&ndash;")),
+            };
+            html!(
+                r#"<div class="col-4">
+                <a href="/insights?days={p}" class="card p-3 h-100 text-center text-decoration-none">
+                    <small class="text-muted">Last {p} days</small>
+                    <div class="fs-3 my-1">{face}</div>
+                    <div class="fw-bold fs-4 text-calm">{value}</div>
+                    <small class="text-muted">{n} day{s} logged</small>
+                </a>
+            </div>"#,
+                n = avg.days,
+                s = if avg.days == 1 { "" } else { "s" }, p, face, value)
+        })
+        .collect();
+
+    let charts = if insights.entries == 0 {
+        html!(
+            r#"<div class="card p-5 text-center">
+            <div style="font-size:2.5rem">&#128200;</div>
+            <p class="text-muted mb-3">No journal entries in the last {days} days yet.</p>
+            <div><a href="/journal" class="btn btn-calm px-4">Write a journal entry</a></div>
+        </div>"#,
+            days
+        )
+    } else {
+        let entries = insights.entries;
+        let line = mood_line_chart(insights);
+        let weekdays = weekday_mood_chart(&insights.by_weekday);
+        let practice = practice_mood_chart(&insights.with_practice, &insights.without_practice);
+        let takeaway = practice_takeaway(&insights.with_practice, &insights.without_practice);
+        html!(
+            r#"<div class="card p-4 mb-4">
+            <div class="d-flex justify-content-between align-items-baseline mb-2">
+                <h5 class="fw-bold text-calm mb-0">&#128200;&nbsp; Mood over {days} days</h5>
+                <small class="text-muted">{entries} entr{plural}</small>
+            </div>
+            {line}
+        </div>
+
+        <div class="row g-4">
+            <div class="col-12 col-md-6">
+                <div class="card p-4 h-100">
+                    <h5 class="fw-bold text-calm mb-3">&#128197;&nbsp; By day of the week</h5>
+                    {weekdays}
+                </div>
+            </div>
+            <div class="col-12 col-md-6">
+                <div class="card p-4 h-100">
+                    <h5 class="fw-bold text-calm mb-3">&#129496;&nbsp; Practice and mood</h5>
+                    {practice}
+                    <p class="text-muted mt-3 mb-0" style="font-size:.85rem">{takeaway}</p>
+                </div>
+            </div>
+        </div>"#,
+            plural = if entries == 1 { "y" } else { "ies" },
+            days,
+            entries,
+            line,
+            weekdays,
+            practice,
+            takeaway
+        )
+    };
+
+    let content = html!(
+        r#"<div class="row justify-content-center">
+    <div class="col-12 col-lg-9">
+
+        <h2 class="fw-bold text-calm mb-1">&#128161;&nbsp; Mood Insights</h2>
+        <p class="text-muted mb-4">How you&apos;ve been feeling, from your journal</p>
+
+        <div class="row g-3 mb-4">
+            {period_cards}
+        </div>
+
+        <div class="mb-3">{period_links}</div>
+
+        {charts}
+
+        <div class="text-center mt-4">
+            <a href="/dashboard" class="text-muted" style="font-size:.9rem">&#8592; Back to Dashboard</a>
+        </div>
+
+    </div>
+</div>"#,
+        period_cards,
+        period_links,
+        charts
+    );
+
+    base_layout("Insights", &content, true)
+}
+
+// ── Goals page ──────────────────────────────────────────────────────────────────
+
+/// "Mon 12 Oct" for a day, "Week of 12 Oct" for a week.
+fn goal_period_label(kind: GoalKind, period: &GoalPeriod) -> String {
+    match period.start_date() {
+        Some(d) if kind.is_daily() => d.format("%a %-d %b").to_string(),
+        Some(d) => d.format("Week of %-d %b").to_string(),
+        None => period.period_start.clone(),
+    }
+}
+
+pub fn goals_page(
+    csrf: &str,
+    goals: &[Goal],
+    history: &[GoalPeriod],
+    notice: Option<&str>,
+    error: Option<&str>,
+) -> String {
+    let csrf = csrf_field(csrf);
+    let alert = error
+        .map(error_alert)
+        .or_else(|| notice.map(success_alert))
+        .unwrap_or_default();
+    let current = |kind: GoalKind| goals.iter().find(|g| g.kind() == Some(kind));
+
+    let fields: TrustedHtml = GOAL_KINDS
+        .iter()
+        .map(|&kind| {
+            let value = current(kind)
+                .map(|g| g.target.to_string())
+                .unwrap_or_default();
+            let per = if kind.is_daily() { "day" } else { "week" };
+            html!(
+                r#"<div class="col-12 col-md-4">
+                    <label class="form-label" for="{key}">{label}</label>
+                    <div class="input-group">
+                        <input type="number" id="{key}" name="{key}" class="form-control"
+                               min="1" max="{max}" value="{value}" placeholder="No goal">
+                        <span class="input-group-text">{unit} / {per}</span>
+                    </div>
+                </div>"#,
+                key = kind.key(),
+                label = kind.label(),
+                max = kind.max_target(),
+                unit = kind.unit(),
+                value = Attr(&value),
+                per
+            )
+        })
+        .collect();
+
+    let tables: TrustedHtml = GOAL_KINDS
+        .iter()
+        .filter_map(|&kind| {
+            let periods: Vec<&GoalPeriod> =
+                history.iter().filter(|p| p.kind() == Some(kind)).collect();
+            if periods.is_empty() {
+                return None;
+            }
+            let active = current(kind).is_some();
+            let rows: TrustedHtml = periods
+                .iter()
+                .enumerate()
+                .map(|(i, p)| {
+                    let badge = if p.met() {
+                        r#"<span class="badge" style="background:#d8f3dc;color:#1b4332;font-weight:500">met</span>"#
+                    } else if i == 0 && active {
+                        r#"<span class="badge" style="background:#e7f1ff;color:#084298;font-weight:500">in progress</span>"#
+                    } else {
+                        r#"<span class="badge" style="background:#f8d7da;color:#842029;font-weight:500">missed</span>"#
+                    };
+                    html!(
+                        r#"<tr>
+                        <td>{period}</td>
+                        <td class="text-end">{achieved} / {target} {unit}</td>
+                        <td class="text-end">{badge}</td>
+                    </tr>"#,
+                        period = Text(&goal_period_label(kind, p)),
+                        achieved = p.achieved,
+                        target = p.target,
+                        unit = kind.unit(), badge)
+                })
+                .collect();
+            let met = periods.iter().filter(|p| p.met()).count();
+            Some(html!(
+                r#"<div class="col-12 col-md-6">
+                <div class="card p-4 h-100">
+                    <div class="d-flex justify-content-between align-items-baseline mb-2">
+                        <h6 class="fw-bold text-calm mb-0">{label}</h6>
+                        <small class="text-muted">{met} of {total} met</small>
+                    </div>
+                    <table class="table table-sm mb-0" style="font-size:.88rem">
+                        <tbody>{rows}</tbody>
+                    </table>
+                </div>
+            </div>"#,
+                label = kind.label(),
+                total = periods.len(), met, rows))
+        })
+        .collect();
+
+    let history_section = if tables.is_empty() {
+        TrustedHtml::new(
+            r#"<p class="text-muted">Once you set a goal, each day or week you work towards it shows up here.</p>"#,
+        )
+    } else {
+        html!(r#"<div class="row g-4">{tables}</div>"#, tables)
+    };
+
+    let content = html!(
+        r#"<div class="row justify-content-center">
+    <div class="col-12 col-lg-9">
+
+        <h2 class="fw-bold text-calm mb-1">&#127919;&nbsp; Goals</h2>
+        <p class="text-muted mb-4">Targets for your practice. Days end at midnight and weeks on Sunday, in your timezone.</p>
+
+        {alert}
+
+        <div class="card p-4 mb-4">
+            <form method="POST" action="/goals">
+                {csrf}
+                <div class="row g-3 mb-3">
+                    {fields}
+                </div>
+                <p class="text-muted mb-3" style="font-size:.85rem">
+                    Leave a field blank for no goal. Changing a target applies from the current day or week.
+                </p>
+                <button type="submit" class="btn btn-calm px-4">Save Goals</button>
+            </form>
+        </div>
+
+        <h5 class="fw-bold text-calm mb-3">&#128197;&nbsp; History</h5>
+        {history_section}
+
+        <div class="text-center mt-4">
+            <a href="/dashboard" class="text-muted" style="font-size:.9rem">&#8592; Back to Dashboard</a>
+        </div>
+
+    </div>
+</div>"#,
+        alert,
+        csrf,
+        fields,
+        history_section
+    );
+
+    base_layout("Goals", &content, true)
+}
+
+// ── Streak page ─────────────────────────────────────────────────────────────────
+
+/// Background, text colour and label for a calendar day.
+fn streak_day_style(day: Option<StreakDay>) -> (&'static str, &'static str, &'static str) {
+    match day {
+        Some(StreakDay::Active) => (CHART_FILL, "#fff", "Active"),
+        Some(StreakDay::Frozen) => ("#cfe8fc", "#0b5394", "Frozen"),
+        Some(StreakDay::Missed) => ("#f1f3f5", "#868e96", "Missed"),
+        Some(StreakDay::Pending) => ("#fff", CHART_STROKE, "Today, no session yet"),
+        None => ("transparent", "#ced4da", "No streak yet"),
+    }
+}
+
+/// A month of days coloured by how they counted towards the streak.
+fn streak_calendar(history: &StreakHistory, month: NaiveDate, today: NaiveDate) -> TrustedHtml {
+    let next_month = month.checked_add_months(Months::new(1)).unwrap_or(month);
+    let blanks = month.weekday().num_days_from_monday();
+
+    let mut cells: TrustedHtml = (0..blanks)
+        .map(|_| TrustedHtml::new("<div></div>"))
+        .collect();
+    for date in month.iter_days().take_while(|d| *d < next_month) {
+        let (background, colour, label) = if date > today {
+            ("transparent", "#ced4da", "Upcoming")
+        } else {
+            streak_day_style(history.day(date))
+        };
+        let icon = match history.day(date) {
+            Some(StreakDay::Frozen) if date <= today => "&#10052;&#65039;",
+            _ => "",
+        };
+        let border = if date == today {
+            format!("2px dashed {CHART_STROKE}")
+        } else {
+            "1px solid #e9ecef".to_string()
+        };
+        cells.push(html!(
+            r#"<div class="rounded-3 d-flex flex-column align-items-center justify-content-center" style="aspect-ratio:1;background:{background};color:{colour};border:{border};font-size:.85rem" title="{title}: {label}" aria-label="{title}: {label}">
+                <span class="fw-semibold">{day}</span><span style="font-size:.7rem;line-height:1">{icon}</span>
+            </div>"#,
+            title = Attr(&date.format("%a %-d %b").to_string()),
+            day = date.day(),
+            border = Attr(&border),
+            background, colour, label, icon));
+    }
+
+    let weekdays: TrustedHtml = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"]
+        .iter()
+        .map(|d| html!(r#"<small class="text-muted text-center">{d}</small>"#, d))
+        .collect();
+
+    html!(
+        r#"<div style="display:grid;grid-template-columns:repeat(7,1fr);gap:.4rem">
+            {weekdays}
+            {cells}
+        </div>"#,
+        weekdays,
+        cells
+    )
+}
+
+pub fn streak_page(history: &StreakHistory, month: NaiveDate, today: NaiveDate) -> String {
+    let calendar = streak_calendar(history, month, today);
+    let title = month.format("%B %Y").to_string();
+    let this_month = today.with_day(1).unwrap_or(today);
+
+    let prev_link = month
+        .checked_sub_months(Months::new(1))
+        .filter(|m| *m >= history.first.with_day(1).unwrap_or(history.first))
+        .map(|m| {
+            html!(
+                r#"<a href="/streak?month={}" class="btn btn-sm btn-outline-secondary" aria-label="Previous month">&#8249;</a>"#,
+                Attr(&m.format("%Y-%m").to_string())
+            )
+        })
+        .unwrap_or_else(|| TrustedHtml::new(r#"<span class="btn btn-sm btn-outline-secondary disabled">&#8249;</span>"#));
+    let next_link = month
+        .checked_add_months(Months::new(1))
+        .filter(|m| *m <= this_month)
+        .map(|m| {
+            html!(
+                r#"<a href="/streak?month={}" class="btn btn-sm btn-outline-secondary" aria-label="Next month">&#8250;</a>"#,
+                Attr(&m.format("%Y-%m").to_string())
+            )
+        })
+        .unwrap_or_else(|| TrustedHtml::new(r#"<span class="btn btn-sm btn-outline-secondary disabled">&#8250;</span>"#));
+
+    let current = history.current;
+    let longest = history.longest;
+    let freezes = history.freezes;
+    let freeze_note = match history.days_to_next_freeze() {
+        Some(days) => format!(
+            "{days} more active day{s} to earn a freeze",
+            s = if days == 1 { "" } else { "s" }
+        ),
+        None => format!("Bank full ({MAX_FREEZES} max)"),
+    };
+    let used = history.frozen_days();
+
+    let legend: TrustedHtml = [
+        Some(StreakDay::Active),
+        Some(StreakDay::Frozen),
+        Some(StreakDay::Missed),
+    ]
+    .into_iter()
+    .map(|day| {
+        let (background, _, label) = streak_day_style(day);
+        html!(
+            r#"<span class="me-3"><span class="d-inline-block rounded-1 align-middle me-1" style="width:14px;height:14px;background:{background};border:1px solid #e9ecef"></span>{label}</span>"#, background, label)
+    })
+    .collect();
+
+    let content = html!(
+        r#"<div class="row justify-content-center">
+    <div class="col-12 col-lg-8">
+
+        <h2 class="fw-bold text-calm mb-1">&#128293;&nbsp; Streak</h2>
+        <p class="text-muted mb-4">
+            Every {FREEZE_EARN_DAYS} active days in a row earns a streak freeze (up to {MAX_FREEZES}).
+            A freeze is used up automatically on a day you miss, so one quiet day doesn&apos;t undo your progress.
+        </p>
+
+        <div class="row g-3 mb-4 text-center">
+            <div class="col-4">
+                <div class="card p-3 h-100">
+                    <small class="text-muted">Current streak</small>
+                    <div class="fw-bold fs-3 text-calm">{current}</div>
+                    <small class="text-muted">active days</small>
+                </div>
+            </div>
+            <div class="col-4">
+                <div class="card p-3 h-100">
+                    <small class="text-muted">Longest streak</small>
+                    <div class="fw-bold fs-3 text-calm">{longest}</div>
+                    <small class="text-muted">active days</small>
+                </div>
+            </div>
+            <div class="col-4">
+                <div class="card p-3 h-100">
+                    <small class="text-muted">Freezes banked</small>
+                    <div class="fw-bold fs-3" style="color:#0b5394">&#10052;&#65039; {freezes}</div>
+                    <small class="text-muted">{freeze_note}</small>
+                </div>
+            </div>
+        </div>
+
+        <div class="card p-4">
+            <div class="d-flex justify-content-between align-items-center mb-3">
+                {prev_link}
+                <h5 class="fw-bold text-calm mb-0">{title}</h5>
+                {next_link}
+            </div>
+            {calendar}
+            <div class="d-flex flex-wrap justify-content-between mt-3 text-muted" style="font-size:.82rem">
+                <div>{legend}</div>
+                <div>{used} freeze{s} used so far</div>
+            </div>
+        </div>
+
+        <div class="text-center mt-4">
+            <a href="/dashboard" class="text-muted" style="font-size:.9rem">&#8592; Back to Dashboard</a>
+        </div>
+
+    </div>
+</div>"#,
+        s = if used == 1 { "" } else { "s" },
+        FREEZE_EARN_DAYS,
+        MAX_FREEZES,
+        current,
+        longest,
+        freezes,
+        freeze_note = Text(&freeze_note),
+        prev_link,
+        title = Text(&title),
+        next_link,
+        calendar,
+        legend,
+        used
+    );
+
+    base_layout("Streak", &content, true)
+}
+
+// ── Journal page ────────────────────────────────────────────────────────────────
+
+/// Emoji and label for each journal mood, indexed by `mood - 1`.
+const MOODS: [(&str, &str); 5] = [
+    ("&#128542;", "Struggling"),
+    ("&#128533;", "Not great"),
+    ("&#128528;", "Okay"),
+    ("&#128512;", "Good"),
+    ("&#128513;", "Great"),
+];
+
+fn mood_face(mood: i64) -> (&'static str, &'static str) {
+    MOODS[(mood.clamp(1, 5) - 1) as usize]
+}
+
+/// The five-face mood radio group, with `selected` checked if given.
+fn mood_picker(heading: &str, selected: Option<i64>) -> TrustedHtml {
+    let options: TrustedHtml = MOODS
+        .iter()
+        .zip(1..)
+        .map(|((face, label), value)| {
+            let checked = if selected == Some(value) { " checked" } else { "" };
+            let required = if value == 1 { " required" } else { "" };
+            html!(
+                r#"<div class="mood-option">
+                        <input type="radio" name="mood" id="m{value}" value="{value}"{checked}{required}>
+                        <label for="m{value}" title="{label}">{face}</label>
+                    </div>"#, value, checked, required, label, face)
+        })
+        .collect();
+
+    html!(
+        r#"<div class="card p-4 mb-4">
+                <h5 class="fw-bold text-calm mb-3">{heading}</h5>
+                <div class="d-flex justify-content-around">
+                    {options}
+                </div>
+                <p id="mood-label" class="text-muted text-center mt-3 mb-0" style="font-size:.9rem; min-height:1.2em;"></p>
+            </div>"#,
+        heading = Text(heading),
+        options
+    )
+}
+
+const MOOD_SCRIPT: &str = r#"<script>
+const labels = ['', 'Struggling \u2014 it\'s okay, you showed up', 'Not great \u2014 acknowledging it is the first step', 'Okay \u2014 steady and present', 'Good \u2014 keep that energy', 'Great \u2014 wonderful!'];
+document.querySelectorAll('input[name="mood"]').forEach(radio => {
+    radio.addEventListener('change', () => {
+        document.getElementById('mood-label').textContent = labels[radio.value] || '';
+    });
+});
+</script>"#;
+
+/// Tag names as links that filter the journal by that tag.
+fn tag_links(tags: &[&str]) -> TrustedHtml {
+    tags.iter()
+        .map(|tag| {
+            let query = serde_urlencoded::to_string([("tag", tag)]).unwrap_or_default();
+            html!(
+                r#"<a href="/journal/entries?{}" class="badge rounded-pill text-bg-light border text-decoration-none me-1">#{}</a>"#,
+                Attr(&query),
+                Text(tag)
+            )
+        })
+        .collect()
+}
+
+/// The comma-separated tag input, with the user's existing tags offered as
+/// one-click suggestions.
+fn tag_field(value: &str, known_tags: &[(String, i64)]) -> TrustedHtml {
+    let value = Attr(value);
+    let suggestions: TrustedHtml = known_tags
+        .iter()
+        .take(12)
+        .map(|(name, _)| {
+            html!(
+                r#"<button type="button" class="btn btn-sm btn-outline-secondary rounded-pill me-1 mb-1 tag-suggestion" data-tag="{}">#{}</button>"#,
+                Attr(name),
+                Text(name)
+            )
+        })
+        .collect();
+
+    html!(
+        r#"<div class="card p-4 mb-4">
+                <label for="tags" class="fw-bold text-calm mb-2 h5">Tags <span class="text-muted fw-normal" style="font-size:.85rem">(optional, separated by commas)</span></label>
+                <input type="text" id="tags" name="tags" class="form-control" value="{value}"
+                       placeholder="work, sleep, family" maxlength="250">
+                <div class="mt-2">{suggestions}</div>
+            </div>"#,
+        value,
+        suggestions
+    )
+}
+
+const TAG_SCRIPT: &str = r#"<script>
+document.querySelectorAll('.tag-suggestion').forEach(btn => {
+    btn.addEventListener('click', () => {
+        const input = document.getElementById('tags');
+        const tags = input.value.split(',').map(t => t.trim()).filter(t => t);
+        if (!tags.includes(btn.dataset.tag)) tags.push(btn.dataset.tag);
+        input.value = tags.join(', ');
+    });
+});
+</script>"#;
+
+pub fn journal_page(
+    csrf: &str,
+    prompt: Option<&JournalPrompt>,
+    known_tags: &[(String, i64)],
+    note: &str,
+    tags: &str,
+    error: Option<&str>,
+) -> String {
+    let csrf = csrf_field(csrf);
+    let alert = error.map(error_alert).unwrap_or_default();
+    let picker = mood_picker("How are you feeling right now?", None);
+    let note = Text(note);
+    let tag_field = tag_field(tags, known_tags);
+
+    let prompt_card = match prompt {
+        Some(p) => html!(
+            r#"<div class="card p-4 mb-4" style="border-left:4px solid var(--calm-mid)">
+                <input type="hidden" name="prompt" value="{key}">
+                <small class="text-uppercase text-muted fw-semibold" style="letter-spacing:.05em">{category} prompt</small>
+                <p class="fs-5 mt-1 mb-3">{text}</p>
+                <div style="font-size:.85rem">
+                    <a href="/journal?prompt={next}" class="text-muted me-3">&#8635; Another prompt</a>
+                    <a href="/journal?prompt=none" class="text-muted">Write freely instead</a>
+                </div>
+            </div>"#,
+            key = Attr(p.key),
+            category = Text(p.category),
+            text = Text(p.text),
+            next = Attr(next_prompt(p).key),
+        ),
+        None => TrustedHtml::new(
+            r#"<input type="hidden" name="prompt" value="">
+            <p class="mb-4" style="font-size:.85rem"><a href="/journal" class="text-muted">&#128161; Show me a prompt</a></p>"#,
+        ),
+    };
+
+    let content = html!(
+        r#"{alert}
+
+<div class="row justify-content-center">
+    <div class="col-12 col-md-8 col-lg-6">
+
+        <h2 class="fw-bold text-calm mb-1">&#128221;&nbsp; Daily Mood Journal</h2>
+        <p class="text-muted mb-4">Take a moment to check in with yourself</p>
+
+        <form method="POST" action="/journal">
+            {csrf}
+
+            <!-- Mood picker -->
+            {picker}
+
+            <!-- Reflection prompt -->
+            {prompt_card}
+
+            <!-- Notes -->
+            <div class="card p-4 mb-4">
+                <h5 class="fw-bold text-calm mb-3">Any thoughts to capture? <span class="text-muted fw-normal" style="font-size:.85rem">(optional)</span></h5>
+                <textarea
+                    name="note"
+                    class="form-control"
+                    rows="5"
+                    placeholder="What's on your mind today? What are you grateful for? What felt hard?"
+                    style="resize:vertical;"
+                >{note}</textarea>
+            </div>
+
+            <!-- Tags -->
+            {tag_field}
+
+            <div class="d-grid">
+                <button type="submit" class="btn btn-calm py-3 fs-5">
+                    &#128221;&nbsp; Save Journal Entry
+                </button>
+            </div>
+
+        </form>
+
+        <div class="text-center mt-4">
+            <a href="/journal/entries" class="text-muted me-3" style="font-size:.9rem">&#128214; Past entries</a>
+            <a href="/dashboard" class="text-muted" style="font-size:.9rem">&#8592; Back to Dashboard</a>
+        </div>
+
+    </div>
+</div>
+
+{MOOD_SCRIPT}
+{TAG_SCRIPT}"#,
+        alert,
+        csrf,
+        picker,
+        prompt_card,
+        note,
+        tag_field,
+        MOOD_SCRIPT,
+        TAG_SCRIPT
+    );
+
+    base_layout("Journal", &content, true)
+}
+
+/// "2026-10-18 09:30", plus "(edited …)" when the entry has been changed.
+fn entry_timestamp(entry: &JournalEntry) -> TrustedHtml {
+    let written = Text(entry.created_at.get(..16).unwrap_or(&entry.created_at));
+    match &entry.updated_at {
+        Some(u) => html!(
+            "{written} &middot; edited {}",
+            Text(u.get(..16).unwrap_or(u)),
+            written
+        ),
+        None => html!("{written}", written),
+    }
+}
+
+/// A search snippet with its matches wrapped in `<mark>`.
+fn highlighted(snippet: &str) -> TrustedHtml {
+    // The markers survive escaping, so only they can become tags
+    TrustedHtml::new(
+        Text(snippet)
+            .to_string()
+            .replace(HIGHLIGHT_START, "<mark>")
+            .replace(HIGHLIGHT_END, "</mark>"),
+    )
+}
+
+fn mood_options(selected: Option<i64>) -> TrustedHtml {
+    MOODS
+        .iter()
+        .zip(1..)
+        .map(|((_, label), value)| {
+            let chosen = if selected == Some(value) {
+                " selected"
+            } else {
+                ""
+            };
+            html!(
+                r#"<option value="{value}"{chosen}>{label}</option>"#,
+                value,
+                chosen,
+                label
+            )
+        })
+        .collect()
+}
+
+/// The prompt an entry answered, shown above its note.
+fn entry_prompt(entry: &JournalEntry) -> TrustedHtml {
+    entry
+        .prompt
+        .as_deref()
+        .map(|p| {
+            html!(
+                r#"<p class="text-muted fst-italic mb-2" style="font-size:.88rem">&#128161; {}</p>"#,
+                Text(p)
+            )
+        })
+        .unwrap_or_default()
+}
+
+pub fn journal_entries_page(
+    results: &JournalPage,
+    filter: &JournalFilter,
+    page: i64,
+    known_tags: &[(String, i64)],
+    error: Option<&str>,
+    notice: Option<&str>,
+) -> String {
+    let total = results.total;
+    let page_count = results.page_count();
+    let alert = error.map(error_alert).unwrap_or_default();
+    let notice = notice.map(success_alert).unwrap_or_default();
+    let q = Attr(filter.q.as_deref().unwrap_or_default());
+    let tag_options: TrustedHtml = known_tags
+        .iter()
+        .map(|(name, uses)| {
+            let chosen = if filter.tag.as_deref() == Some(name.as_str()) {
+                " selected"
+            } else {
+                ""
+            };
+            html!(
+                r#"<option value="{}"{chosen}>{} ({uses})</option>"#,
+                Attr(name),
+                Text(name),
+                chosen,
+                uses
+            )
+        })
+        .collect();
+    let mood_min_options = mood_options(filter.mood_min);
+    let mood_max_options = mood_options(filter.mood_max);
+    let from = filter.from.map(|d| d.to_string()).unwrap_or_default();
+    let to = filter.to.map(|d| d.to_string()).unwrap_or_default();
+    let summary = if filter.is_active() {
+        html!(
+            r#"<p class="text-muted mb-3" style="font-size:.9rem">{total} matching {} &middot; <a href="/journal/entries" class="text-muted">Clear</a></p>"#,
+            if total == 1 { "entry" } else { "entries" },
+            total
+        )
+    } else {
+        TrustedHtml::default()
+    };
+
+    let cards: TrustedHtml = results
+        .entries
+        .iter()
+        .map(|entry| {
+            let (face, label) = mood_face(entry.mood);
+            let id = Attr(&entry.id);
+            let note = if let Some(snippet) = &entry.snippet {
+                html!(
+                    r#"<p class="mb-0" style="white-space:pre-wrap">{}</p>"#,
+                    highlighted(snippet)
+                )
+            } else if entry.note.is_empty() {
+                TrustedHtml::new(r#"<p class="text-muted fst-italic mb-0">No note</p>"#)
+            } else {
+                let excerpt: String = entry.note.chars().take(280).collect();
+                let more = if entry.note.chars().count() > 280 {
+                    "&hellip;"
+                } else {
+                    ""
+                };
+                html!(
+                    r#"<p class="mb-0" style="white-space:pre-wrap">{}{more}</p>"#,
+                    Text(&excerpt), more)
+            };
+            html!(
+                r#"<div class="card p-4 mb-3">
+            <div class="d-flex align-items-center gap-3 mb-2">
+                <span style="font-size:2rem; line-height:1">{face}</span>
+                <div class="flex-grow-1">
+                    <div class="fw-semibold">{label}</div>
+                    <small class="text-muted">{}</small>
+                </div>
+                <a href="/journal/entries/{id}" class="btn btn-sm btn-outline-secondary rounded-3">Open</a>
+            </div>
+            {}
+            {note}
+            <div class="mt-2">{}</div>
+        </div>"#,
+                entry_timestamp(entry),
+                entry_prompt(entry),
+                tag_links(&entry.tags()), face, label, id, note)
+        })
+        .collect();
+
+    let cards = if cards.is_empty() && filter.is_active() {
+        TrustedHtml::new(
+            r#"<div class="card p-5 text-center">
+            <p class="text-muted mb-0">No entries match your search.</p>
+        </div>"#,
+        )
+    } else if cards.is_empty() {
+        TrustedHtml::new(
+            r#"<div class="card p-5 text-center">
+            <p class="text-muted mb-3">You haven&apos;t written any journal entries yet.</p>
+            <a href="/journal" class="btn btn-calm px-4 mx-auto">Write your first entry</a>
+        </div>"#,
+        )
+    } else {
+        cards
+    };
+
+    let prev = if page > 1 {
+        html!(
+            r#"<a href="/journal/entries?{}" class="btn btn-sm btn-outline-secondary rounded-3">&#8592; Previous</a>"#,
+            Attr(&filter.query_string(page - 1))
+        )
+    } else {
+        TrustedHtml::default()
+    };
+    let next = if page < page_count {
+        html!(
+            r#"<a href="/journal/entries?{}" class="btn btn-sm btn-outline-secondary rounded-3">Next &#8594;</a>"#,
+            Attr(&filter.query_string(page + 1))
+        )
+    } else {
+        TrustedHtml::default()
+    };
+
+    let content = html!(
+        r#"<div class="row justify-content-center">
+    <div class="col-12 col-md-8 col-lg-7">
+
+        {alert}
+        {notice}
+
+        <div class="d-flex flex-wrap align-items-center justify-content-between gap-3 mb-4">
+            <div>
+                <h2 class="fw-bold text-calm mb-1">&#128214;&nbsp; Your Journal</h2>
+                <p class="text-muted mb-0">Look back on how you&apos;ve been feeling</p>
+            </div>
+            <a href="/journal" class="btn btn-calm px-4">&#43; New Entry</a>
+        </div>
+
+        <!-- Search -->
+        <form method="GET" action="/journal/entries" class="card p-3 mb-4" role="search">
+            <div class="input-group mb-2">
+                <input type="search" name="q" class="form-control" value="{q}"
+                       placeholder="Search your notes" aria-label="Search your notes" maxlength="200">
+                <button type="submit" class="btn btn-calm px-4">Search</button>
+            </div>
+            <p class="text-muted mb-2" style="font-size:.8rem">
+                Finds entries containing every word you type, or words starting with them,
+                with the entries that mention them most first. Quoted phrases, OR and NOT
+                aren't supported; every word is simply looked for on its own.
+            </p>
+            <div class="row g-2">
+                <div class="col-12 col-sm-4">
+                    <label class="form-label mb-1" for="tag" style="font-size:.8rem">Tag</label>
+                    <select id="tag" name="tag" class="form-select form-select-sm">
+                        <option value="">Any</option>
+                        {tag_options}
+                    </select>
+                </div>
+                <div class="col-6 col-sm-4">
+                    <label class="form-label mb-1" for="mood_min" style="font-size:.8rem">Mood from</label>
+                    <select id="mood_min" name="mood_min" class="form-select form-select-sm">
+                        <option value="">Any</option>
+                        {mood_min_options}
+                    </select>
+                </div>
+                <div class="col-6 col-sm-4">
+                    <label class="form-label mb-1" for="mood_max" style="font-size:.8rem">Mood to</label>
+                    <select id="mood_max" name="mood_max" class="form-select form-select-sm">
+                        <option value="">Any</option>
+                        {mood_max_options}
+                    </select>
+                </div>
+                <div class="col-6">
+                    <label class="form-label mb-1" for="from" style="font-size:.8rem">From</label>
+                    <input type="date" id="from" name="from" class="form-control form-control-sm" value="{from}">
+                </div>
+                <div class="col-6">
+                    <label class="form-label mb-1" for="to" style="font-size:.8rem">To</label>
+                    <input type="date" id="to" name="to" class="form-control form-control-sm" value="{to}">
+                </div>
+            </div>
+        </form>
+
+        {summary}
+        {cards}
+
+        <div class="d-flex justify-content-between align-items-center mt-3">
+            <div>{prev}</div>
+            <small class="text-muted">Page {page} of {page_count}</small>
+            <div>{next}</div>
+        </div>
+
+        <div class="text-center mt-4">
+            <a href="/dashboard" class="text-muted" style="font-size:.9rem">&#8592; Back to Dashboard</a>
+        </div>
 
-    const affirmations = [
-        "\u201cYou are present. You are calm.\u201d",
-        "\u201cLet thoughts pass like clouds.\u201d",
-        "\u201cBreathe in peace, breathe out tension.\u201d",
-        "\u201cThis moment is enough.\u201d",
-        "\u201cYou are safe. You are still.\u201d",
-        "\u201cEvery breath brings clarity.\u201d",
-        "\u201cYou deserve this time for yourself.\u201d",
-    ];
-    let affirmIdx = 0;
+    </div>
+</div>"#,
+        alert,
+        notice,
+        q,
+        tag_options,
+        mood_min_options,
+        mood_max_options,
+        from = Attr(&from),
+        to = Attr(&to),
+        summary,
+        cards,
+        prev,
+        page,
+        page_count,
+        next
+    );
 
-    const timerEl   = document.getElementById('timer-display');
-    const labelEl   = document.getElementById('timer-label');
-    const bar       = document.getElementById('progress-bar');
-    const doneDiv   = document.getElementById('session-done');
-    const skipForm  = document.getElementById('skip-form');
-    const affirmEl  = document.getElementById('affirmation');
+    base_layout("Your Journal", &content, true)
+}
 
-    function fmt(s) {
-        const m = Math.floor(s / 60);
-        const sec = s % 60;
-        return m + ':' + String(sec).padStart(2, '0');
-    }
+pub fn journal_entry_page(csrf: &str, entry: &JournalEntry, notice: Option<&str>) -> String {
+    let csrf = csrf_field(csrf);
+    let notice = notice.map(success_alert).unwrap_or_default();
+    let (face, label) = mood_face(entry.mood);
+    let id = Attr(&entry.id);
+    let when = entry_timestamp(entry);
+    let prompt = entry_prompt(entry);
+    let tags = tag_links(&entry.tags());
+    let note = if entry.note.is_empty() {
+        TrustedHtml::new(r#"<p class="text-muted fst-italic mb-0">No note</p>"#)
+    } else {
+        html!(
+            r#"<p class="mb-0" style="white-space:pre-wrap">{}</p>"#,
+            Text(&entry.note)
+        )
+    };
 
-    // Show skip button after 30 seconds
-    setTimeout(() => { skipForm.style.display = 'block'; }, 30000);
+    let content = html!(
+        r#"<div class="row justify-content-center">
+    <div class="col-12 col-md-8 col-lg-6">
 
-    // Rotate affirmations every 30s
-    setInterval(() => {
-        affirmIdx = (affirmIdx + 1) % affirmations.length;
-        affirmEl.style.opacity = '0';
-        setTimeout(() => {
-            affirmEl.textContent = affirmations[affirmIdx];
-            affirmEl.style.opacity = '1';
-        }, 500);
-    }, 30000);
-    affirmEl.style.transition = 'opacity .5s';
+        {notice}
 
-    const timer = setInterval(() => {
-        remaining--;
-        timerEl.textContent = fmt(remaining);
-        bar.style.width = ((TOTAL - remaining) / TOTAL * 100) + '%';
+        <div class="card p-4 mb-4">
+            <div class="d-flex align-items-center gap-3 mb-3">
+                <span style="font-size:2.5rem; line-height:1">{face}</span>
+                <div>
+                    <h4 class="fw-bold text-calm mb-0">{label}</h4>
+                    <small class="text-muted">{when}</small>
+                </div>
+            </div>
+            {prompt}
+            {note}
+            <div class="mt-3">{tags}</div>
+        </div>
 
-        if (remaining <= 0) {
-            clearInterval(timer);
-            timerEl.textContent = '0:00';
-            labelEl.textContent = 'complete';
-            skipForm.style.display = 'none';
-            doneDiv.style.display = 'block';
-        }
-    }, 1000);
-}());
-</script>"#;
+        <div class="d-flex gap-2">
+            <a href="/journal/entries/{id}/edit" class="btn btn-calm flex-fill rounded-3">&#9998;&nbsp; Edit</a>
+            <form method="POST" action="/journal/entries/{id}/delete" class="flex-fill m-0">
+                {csrf}
+                <button type="submit" class="btn btn-outline-danger w-100 rounded-3">Delete</button>
+            </form>
+        </div>
+        <p class="text-muted mt-2 mb-0" style="font-size:.8rem">
+            Deleting an entry also removes the 5 mindful minutes it added.
+        </p>
 
-    base_layout("Guided Meditation", content, true)
-}
+        <div class="text-center mt-4">
+            <a href="/journal/entries" class="text-muted" style="font-size:.9rem">&#8592; Back to Journal</a>
+        </div>
 
-// ── Journal page ────────────────────────────────────────────────────────────────
+    </div>
+</div>"#,
+        notice,
+        face,
+        label,
+        when,
+        prompt,
+        note,
+        tags,
+        id,
+        csrf
+    );
 
-pub fn journal_page(error: Option<&str>) -> String {
-    let alert = error.map(|e| error_alert(e)).unwrap_or_default();
+    base_layout("Journal Entry", &content, true)
+}
 
-    let content = format!(
+pub fn journal_edit_page(
+    csrf: &str,
+    entry: &JournalEntry,
+    tags: &str,
+    known_tags: &[(String, i64)],
+    error: Option<&str>,
+) -> String {
+    let csrf = csrf_field(csrf);
+    let alert = error.map(error_alert).unwrap_or_default();
+    let picker = mood_picker("How were you feeling?", Some(entry.mood));
+    let id = Attr(&entry.id);
+    let prompt = entry_prompt(entry);
+    let note = Text(&entry.note);
+    let tag_field = tag_field(tags, known_tags);
+
+    let content = html!(
         r#"{alert}
 
 <div class="row justify-content-center">
     <div class="col-12 col-md-8 col-lg-6">
 
-        <h2 class="fw-bold text-calm mb-1">&#128221;&nbsp; Daily Mood Journal</h2>
-        <p class="text-muted mb-4">Take a moment to check in with yourself</p>
+        <h2 class="fw-bold text-calm mb-1">&#9998;&nbsp; Edit Entry</h2>
+        <p class="text-muted mb-4">Written {}</p>
 
-        <form method="POST" action="/journal">
+        <form method="POST" action="/journal/entries/{id}/edit">
+            {csrf}
 
-            <!-- Mood picker -->
-            <div class="card p-4 mb-4">
-                <h5 class="fw-bold text-calm mb-3">How are you feeling right now?</h5>
-                <div class="d-flex justify-content-around">
-                    <div class="mood-option">
-                        <input type="radio" name="mood" id="m1" value="1" required>
-                        <label for="m1" title="Struggling">&#128542;</label>
-                    </div>
-                    <div class="mood-option">
-                        <input type="radio" name="mood" id="m2" value="2">
-                        <label for="m2" title="Not great">&#128533;</label>
-                    </div>
-                    <div class="mood-option">
-                        <input type="radio" name="mood" id="m3" value="3">
-                        <label for="m3" title="Okay">&#128528;</label>
-                    </div>
-                    <div class="mood-option">
-                        <input type="radio" name="mood" id="m4" value="4">
-                        <label for="m4" title="Good">&#128512;</label>
-                    </div>
-                    <div class="mood-option">
-                        <input type="radio" name="mood" id="m5" value="5">
-                        <label for="m5" title="Great">&#128513;</label>
-                    </div>
-                </div>
-                <p id="mood-label" class="text-muted text-center mt-3 mb-0" style="font-size:.9rem; min-height:1.2em;"></p>
-            </div>
+            {picker}
 
-            <!-- Notes -->
             <div class="card p-4 mb-4">
-                <h5 class="fw-bold text-calm mb-3">Any thoughts to capture? <span class="text-muted fw-normal" style="font-size:.85rem">(optional)</span></h5>
-                <textarea
-                    name="note"
-                    class="form-control"
-                    rows="5"
-                    placeholder="What's on your mind today? What are you grateful for? What felt hard?"
-                    style="resize:vertical;"
-                ></textarea>
+                <label for="note" class="fw-bold text-calm mb-3 h5">Note</label>
+                {prompt}
+                <textarea id="note" name="note" class="form-control" rows="6" style="resize:vertical;">{note}</textarea>
             </div>
 
+            {tag_field}
+
             <div class="d-grid">
-                <button type="submit" class="btn btn-calm py-3 fs-5">
-                    &#128221;&nbsp; Save Journal Entry
-                </button>
+                <button type="submit" class="btn btn-calm py-3 fs-5">Save Changes</button>
             </div>
-
         </form>
 
         <div class="text-center mt-4">
-            <a href="/dashboard" class="text-muted" style="font-size:.9rem">&#8592; Back to Dashboard</a>
+            <a href="/journal/entries/{id}" class="text-muted" style="font-size:.9rem">&#8592; Cancel</a>
         </div>
 
     </div>
 </div>
 
-<script>
-const labels = ['', 'Struggling \u2014 it\'s okay, you showed up', 'Not great \u2014 acknowledging it is the first step', 'Okay \u2014 steady and present', 'Good \u2014 keep that energy', 'Great \u2014 wonderful!'];
-document.querySelectorAll('input[name="mood"]').forEach(radio => {{
-    radio.addEventListener('change', () => {{
-        document.getElementById('mood-label').textContent = labels[radio.value] || '';
-    }});
-}});
-</script>"#
+{MOOD_SCRIPT}
+{TAG_SCRIPT}"#,
+        Text(entry.created_at.get(..16).unwrap_or(&entry.created_at)),
+        alert,
+        id,
+        csrf,
+        picker,
+        prompt,
+        note,
+        tag_field,
+        MOOD_SCRIPT,
+        TAG_SCRIPT
     );
 
-    base_layout("Journal", &content, true)
+    base_layout("Edit Journal Entry", &content, true)
 }
 
 // ── Profile page ───────────────────────────────────────────────────────────────
 
-pub fn profile_page(user: &User) -> String {
+pub fn profile_page(
+    csrf: &str,
+    user: &User,
+    badges: &[UnlockedAchievement],
+    notice: Option<&str>,
+    error: Option<&str>,
+) -> String {
+    let two_factor = two_factor_card(csrf, user);
+    let badges = badges_card(badges);
+    let csrf = csrf_field(csrf);
+    let alert = error
+        .map(error_alert)
+        .or_else(|| notice.map(success_alert))
+        .unwrap_or_default();
+    let verified = if user.is_verified() {
+        r#"<span class="badge ms-2" style="background:#d8f3dc;color:#1b4332;font-weight:500">verified</span>"#
+    } else {
+        r#"<span class="badge ms-2" style="background:#fff3cd;color:#664d03;font-weight:500">unverified</span>"#
+    };
     let initials: String = user
         .name
         .split_whitespace()
@@ -1006,16 +3366,36 @@ pub fn profile_page(user: &User) -> String {
         .collect::<String>()
         .to_uppercase();
 
-    let name = &user.name;
-    let email = &user.email;
-    let id = &user.id;
+    let initials = Text(&initials);
+    let name = Text(&user.name);
+    let name_value = Attr(&user.name);
+    let email = Text(&user.email);
+    let id = Text(&user.id);
+    let timezone = Text(&user.timezone);
+    let timezone_options: TrustedHtml = timezone_names()
+        .map(|zone| {
+            let chosen = if zone == user.timezone {
+                " selected"
+            } else {
+                ""
+            };
+            html!(
+                r#"<option value="{zone}"{chosen}>{}</option>"#,
+                Text(&zone.replace('_', " ")),
+                zone,
+                chosen
+            )
+        })
+        .collect();
 
-    let content = format!(
+    let content = html!(
         r#"<div class="row justify-content-center">
     <div class="col-12 col-md-8 col-lg-6">
 
         <h2 class="fw-bold text-calm mb-4">&#128100;&nbsp; My Profile</h2>
 
+        {alert}
+
         <!-- Avatar + name card -->
         <div class="card p-4 mb-4">
             <div class="d-flex align-items-center gap-4 mb-4">
@@ -1038,7 +3418,11 @@ pub fn profile_page(user: &User) -> String {
                 </div>
                 <div class="info-row">
                     <div class="text-muted mb-1" style="font-size:.82rem">&#9993;&nbsp; EMAIL ADDRESS</div>
-                    <div class="fw-semibold">{email}</div>
+                    <div class="fw-semibold">{email}{verified}</div>
+                </div>
+                <div class="info-row">
+                    <div class="text-muted mb-1" style="font-size:.82rem">&#127760;&nbsp; TIMEZONE</div>
+                    <div class="fw-semibold">{timezone}</div>
                 </div>
                 <div class="info-row">
                     <div class="text-muted mb-1" style="font-size:.82rem">&#128273;&nbsp; USER ID</div>
@@ -1047,6 +3431,90 @@ pub fn profile_page(user: &User) -> String {
             </div>
         </div>
 
+        <!-- Badges -->
+        {badges}
+
+        <!-- Edit name -->
+        <div class="card p-4 mb-4">
+            <h5 class="fw-bold text-calm mb-3">Display name</h5>
+            <form method="POST" action="/profile/name" class="d-flex gap-2 flex-wrap">
+                {csrf}
+                <input type="text" name="name" class="form-control flex-fill" style="min-width:200px"
+                       value="{name_value}" maxlength="80" autocomplete="name" required>
+                <button type="submit" class="btn btn-calm px-4">Save</button>
+            </form>
+        </div>
+
+        <!-- Timezone -->
+        <div class="card p-4 mb-4">
+            <h5 class="fw-bold text-calm mb-1">Timezone</h5>
+            <p class="text-muted mb-3" style="font-size:.88rem">
+                Your days, streak and daily stats start at midnight in this timezone.
+            </p>
+            <form method="POST" action="/profile/timezone" class="d-flex gap-2 flex-wrap">
+                {csrf}
+                <select id="profile_timezone" name="timezone" class="form-select flex-fill" style="min-width:200px">
+                    {timezone_options}
+                </select>
+                <button type="submit" class="btn btn-calm px-4">Save</button>
+            </form>
+            <button type="button" id="detect-timezone" class="btn btn-link text-muted p-0 mt-2 align-self-start" style="font-size:.85rem">
+                Use this device&apos;s timezone
+            </button>
+        </div>
+
+        <!-- Change email -->
+        <div class="card p-4 mb-4">
+            <h5 class="fw-bold text-calm mb-1">Email address</h5>
+            <p class="text-muted mb-3" style="font-size:.88rem">
+                We&apos;ll send a confirmation link to the new address. Your email changes once you open it.
+            </p>
+            <form method="POST" action="/profile/email">
+                {csrf}
+                <div class="mb-3">
+                    <label class="form-label" for="new_email">New email address</label>
+                    <input type="email" id="new_email" name="email" class="form-control"
+                           placeholder="you@example.com" autocomplete="email" required>
+                </div>
+                <div class="mb-3">
+                    <label class="form-label" for="email_current_password">Current password</label>
+                    <input type="password" id="email_current_password" name="current_password"
+                           class="form-control" autocomplete="current-password" required>
+                </div>
+                <button type="submit" class="btn btn-calm px-4">Send Confirmation Link</button>
+            </form>
+        </div>
+
+        <!-- Change password -->
+        <div class="card p-4 mb-4">
+            <h5 class="fw-bold text-calm mb-1">Password</h5>
+            <p class="text-muted mb-3" style="font-size:.88rem">
+                Changing your password signs you out on every other device.
+            </p>
+            <form method="POST" action="/profile/password">
+                {csrf}
+                <div class="mb-3">
+                    <label class="form-label" for="current_password">Current password</label>
+                    <input type="password" id="current_password" name="current_password"
+                           class="form-control" autocomplete="current-password" required>
+                </div>
+                <div class="mb-3">
+                    <label class="form-label" for="new_password">New password</label>
+                    <input type="password" id="new_password" name="new_password" class="form-control"
+                           placeholder="Minimum 8 characters" autocomplete="new-password" required>
+                </div>
+                <div class="mb-3">
+                    <label class="form-label" for="confirm_password">Confirm new password</label>
+                    <input type="password" id="confirm_password" name="confirm_password"
+                           class="form-control" autocomplete="new-password" required>
+                </div>
+                <button type="submit" class="btn btn-calm px-4">Change Password</button>
+            </form>
+        </div>
+
+        <!-- Two-factor authentication -->
+        {two_factor}
+
         <!-- Action buttons -->
         <div class="d-flex gap-3">
             <a href="/dashboard" class="btn btn-calm flex-fill py-2">
@@ -1062,16 +3530,275 @@ pub fn profile_page(user: &User) -> String {
         </div>
 
     </div>
-</div>"#
+</div>
+
+<script>
+document.getElementById('detect-timezone').addEventListener('click', () => {{
+    const zone = Intl.DateTimeFormat().resolvedOptions().timeZone;
+    const select = document.getElementById('profile_timezone');
+    if ([...select.options].some(o => o.value === zone)) select.value = zone;
+}});
+</script>"#,
+        alert,
+        initials,
+        name,
+        email,
+        verified,
+        timezone,
+        id,
+        badges,
+        csrf,
+        name_value,
+        timezone_options,
+        two_factor
     );
 
     base_layout("Profile", &content, true)
 }
 
+/// Every achievement, earned ones in colour with their date.
+fn badges_card(unlocked: &[UnlockedAchievement]) -> TrustedHtml {
+    let tiles: TrustedHtml = ACHIEVEMENTS
+        .iter()
+        .map(|a| {
+            let earned = unlocked.iter().find(|u| u.achievement == a.key);
+            let (style, status) = match earned {
+                Some(u) => (
+                    "background:#d8f3dc;border:1px solid #b7e4c7",
+                    html!(
+                        "Unlocked {}",
+                        Text(u.unlocked_at.get(..10).unwrap_or(&u.unlocked_at))
+                    ),
+                ),
+                None => (
+                    "background:#f8f9fa;border:1px dashed #dee2e6;filter:grayscale(1);opacity:.6",
+                    TrustedHtml::new("Locked"),
+                ),
+            };
+            html!(
+                r#"<div class="col-6 col-sm-4">
+                <div class="rounded-3 p-2 h-100 text-center" style="{style}" title="{description}">
+                    <div style="font-size:1.6rem">{icon}</div>
+                    <div class="fw-semibold" style="font-size:.85rem">{name}</div>
+                    <div class="text-muted" style="font-size:.72rem">{status}</div>
+                </div>
+            </div>"#,
+                icon = a.icon,
+                name = a.name,
+                description = a.description,
+                style,
+                status
+            )
+        })
+        .collect();
+
+    html!(
+        r#"<div class="card p-4 mb-4">
+            <div class="d-flex justify-content-between align-items-baseline mb-3">
+                <h5 class="fw-bold text-calm mb-0">&#127941;&nbsp; Badges</h5>
+                <small class="text-muted">{earned} of {total} earned</small>
+            </div>
+            <div class="row g-2">
+                {tiles}
+            </div>
+        </div>"#,
+        earned = unlocked.iter().filter(|u| u.details().is_some()).count(),
+        total = ACHIEVEMENTS.len(),
+        tiles
+    )
+}
+
+fn two_factor_card(csrf: &str, user: &User) -> TrustedHtml {
+    let csrf = csrf_field(csrf);
+    if user.has_two_factor() {
+        html!(
+            r#"<div class="card p-4 mb-4">
+            <h5 class="fw-bold text-calm mb-1">Two-factor authentication
+                <span class="badge ms-2" style="background:#d8f3dc;color:#1b4332;font-weight:500;font-size:.75rem">on</span>
+            </h5>
+            <p class="text-muted mb-3" style="font-size:.88rem">
+                Signing in needs a code from your authenticator app. To turn this off,
+                confirm your password and a current code (or a recovery code).
+            </p>
+            <form method="POST" action="/profile/2fa/disable">
+                {csrf}
+                <div class="mb-3">
+                    <label class="form-label" for="tfa_current_password">Current password</label>
+                    <input type="password" id="tfa_current_password" name="current_password"
+                           class="form-control" autocomplete="current-password" required>
+                </div>
+                <div class="mb-3">
+                    <label class="form-label" for="tfa_code">Authentication or recovery code</label>
+                    <input type="text" id="tfa_code" name="code" class="form-control"
+                           inputmode="numeric" autocomplete="one-time-code" required>
+                </div>
+                <button type="submit" class="btn btn-outline-danger rounded-3 px-4">Turn Off Two-Factor</button>
+            </form>
+        </div>"#,
+            csrf
+        )
+    } else {
+        TrustedHtml::new(
+            r#"<div class="card p-4 mb-4">
+            <h5 class="fw-bold text-calm mb-1">Two-factor authentication</h5>
+            <p class="text-muted mb-3" style="font-size:.88rem">
+                Protect your journal with a one-time code from an authenticator app
+                (Google Authenticator, 1Password, Authy&hellip;) whenever you sign in.
+            </p>
+            <a href="/profile/2fa" class="btn btn-calm px-4 align-self-start">Set Up Two-Factor</a>
+        </div>"#,
+        )
+    }
+}
+
+// ── Two-factor pages ───────────────────────────────────────────────────────────
+
+pub fn two_factor_challenge_page(csrf: &str, error: Option<&str>) -> String {
+    let csrf = csrf_field(csrf);
+    let alert = error.map(error_alert).unwrap_or_default();
+
+    let content = html!(
+        r#"<div class="row justify-content-center">
+    <div class="col-12 col-sm-10 col-md-7 col-lg-5 col-xl-4">
+
+        <div class="text-center mb-4">
+            <div style="font-size:3rem;line-height:1">&#128272;</div>
+            <h2 class="fw-bold text-calm mt-2 mb-1">Two-step sign in</h2>
+            <p class="text-muted mb-0">Enter the 6-digit code from your authenticator app</p>
+        </div>
+
+        <div class="card p-4 p-md-5">
+            {alert}
+            <form method="POST" action="/login/2fa" novalidate>
+                {csrf}
+                <div class="mb-4">
+                    <label class="form-label" for="code">Authentication code</label>
+                    <input
+                        type="text" class="form-control text-center fs-4" id="code" name="code"
+                        inputmode="numeric" autocomplete="one-time-code" placeholder="123456"
+                        autofocus required>
+                    <div class="form-text">Lost your device? Enter one of your recovery codes instead.</div>
+                </div>
+                <button type="submit" class="btn btn-calm w-100 py-2">Verify</button>
+            </form>
+
+            <hr class="my-4">
+            <p class="text-center text-muted mb-0" style="font-size:.95rem">
+                <a href="/logout" class="text-calm fw-semibold">Cancel and start over</a>
+            </p>
+        </div>
+
+    </div>
+</div>"#,
+        alert,
+        csrf
+    );
+
+    base_layout("Two-Step Sign In", &content, false)
+}
+
+pub fn two_factor_setup_page(
+    csrf: &str,
+    secret: &str,
+    uri: &str,
+    qr_svg: &TrustedHtml,
+    error: Option<&str>,
+) -> String {
+    let csrf = csrf_field(csrf);
+    let secret = Text(secret);
+    let uri = Url(uri);
+    let alert = error.map(error_alert).unwrap_or_default();
+
+    let content = html!(
+        r#"<div class="row justify-content-center">
+    <div class="col-12 col-md-8 col-lg-6">
+
+        <h2 class="fw-bold text-calm mb-1">&#128272;&nbsp; Set up two-factor</h2>
+        <p class="text-muted mb-4">Scan the code with your authenticator app, then enter the 6-digit code it shows.</p>
+
+        {alert}
+
+        <div class="card p-4 mb-4 text-center">
+            <div class="mx-auto mb-3" style="width:200px">{qr_svg}</div>
+            <p class="text-muted mb-1" style="font-size:.85rem">Can&apos;t scan? Enter this key manually:</p>
+            <code class="fs-6" style="word-break:break-all">{secret}</code>
+            <p class="mt-3 mb-0" style="font-size:.85rem">
+                <a href="{uri}" class="text-calm">Open in an authenticator app on this device</a>
+            </p>
+        </div>
+
+        <div class="card p-4 mb-4">
+            <form method="POST" action="/profile/2fa/enable">
+                {csrf}
+                <div class="mb-3">
+                    <label class="form-label" for="code">Code from your app</label>
+                    <input type="text" id="code" name="code" class="form-control text-center fs-4"
+                           inputmode="numeric" autocomplete="one-time-code" placeholder="123456" required>
+                </div>
+                <button type="submit" class="btn btn-calm w-100 py-2">Turn On Two-Factor</button>
+            </form>
+        </div>
+
+        <div class="text-center">
+            <a href="/profile" class="text-muted" style="font-size:.9rem">&#8592; Back to Profile</a>
+        </div>
+
+    </div>
+</div>"#,
+        alert,
+        qr_svg,
+        secret,
+        uri,
+        csrf
+    );
+
+    base_layout("Set Up Two-Factor", &content, true)
+}
+
+/// Shown once, right after enabling two-factor; the codes are never
+/// displayed again.
+pub fn recovery_codes_page(codes: &[String]) -> String {
+    let items = TrustedHtml::join(
+        codes.iter().map(|c| {
+            html!(
+                r#"<li class="col-6"><code class="fs-6">{}</code></li>"#,
+                Text(c)
+            )
+        }),
+        "\n                ",
+    );
+
+    let content = html!(
+        r#"<div class="row justify-content-center">
+    <div class="col-12 col-md-8 col-lg-6">
+
+        <h2 class="fw-bold text-calm mb-1">&#9989;&nbsp; Two-factor is on</h2>
+        <p class="text-muted mb-4">
+            Save these recovery codes somewhere safe. Each one works once if you lose
+            access to your authenticator app. They won&apos;t be shown again.
+        </p>
+
+        <div class="card p-4 mb-4">
+            <ul class="row list-unstyled g-2 mb-0">
+                {items}
+            </ul>
+        </div>
+
+        <a href="/profile" class="btn btn-calm w-100 py-2">I&apos;ve Saved My Codes</a>
+
+    </div>
+</div>"#,
+        items
+    );
+
+    base_layout("Recovery Codes", &content, true)
+}
+
 // ── 404 Not Found page ─────────────────────────────────────────────────────────
 
 pub fn not_found_page() -> String {
-    let content = r#"<div class="row justify-content-center text-center">
+    let content = TrustedHtml::new(
+        r#"<div class="row justify-content-center text-center">
     <div class="col-12 col-md-6">
         <div style="font-size:6rem;line-height:1">&#127807;</div>
         <h1 class="fw-bold text-calm mt-3 mb-2" style="font-size:5rem">404</h1>
@@ -1090,28 +3817,56 @@ pub fn not_found_page() -> String {
             </a>
         </div>
     </div>
-</div>"#;
+</div>"#,
+    );
+
+    base_layout("404 Not Found", &content, false)
+}
+
+// ── CSRF failure page ──────────────────────────────────────────────────────────
+
+/// Shown (with 403) when a form arrives without a valid CSRF token, most
+/// often because it was opened before the session expired.
+pub fn csrf_failed_page(logged_in: bool) -> String {
+    let home = if logged_in { "/dashboard" } else { "/login" };
+    let content = html!(
+        r#"<div class="row justify-content-center">
+    <div class="col-12 col-sm-9 col-md-6 col-lg-5">
+        <div class="card p-5 text-center">
+            <div style="font-size:3.5rem">&#8987;</div>
+            <h3 class="fw-bold text-calm mt-3 mb-2">This form has expired</h3>
+            <p class="text-muted mb-4">
+                For your security we couldn&apos;t accept that submission. Please go back,
+                refresh the page and try again.
+            </p>
+            <a href="{home}" class="btn btn-calm px-4">Continue</a>
+        </div>
+    </div>
+</div>"#,
+        home
+    );
 
-    base_layout("404 Not Found", content, false)
+    base_layout("Form Expired", &content, logged_in)
 }
 
 // ── Videos browse page ─────────────────────────────────────────────────────────
 
 pub fn videos_page(videos: &[VideoWithUploader]) -> String {
     let video_cards = if videos.is_empty() {
-        r#"<div class="col-12 text-center py-5">
+        TrustedHtml::new(
+            r#"<div class="col-12 text-center py-5">
     <div style="font-size:4rem">🎬</div>
     <h4 class="fw-bold text-calm mt-3 mb-2">No videos yet</h4>
     <p class="text-muted mb-4">Be the first to share a health video with the community.</p>
     <a href="/videos/new" class="btn btn-calm px-4">&#43; Add First Video</a>
-</div>"#
-            .to_string()
+</div>"#,
+        )
     } else {
-        videos.iter().map(|v| {
-            let cat_slug = &v.category;
-            let cat_label = category_label(cat_slug);
+        TrustedHtml::join(videos.iter().map(|v| {
+            let cat_label = category_label(&v.category);
+            let cat_slug = Attr(&v.category);
             let thumb = if v.thumbnail_url.is_empty() {
-                let icon = match cat_slug.as_str() {
+                let icon = match v.category.as_str() {
                     "breathing"     => "🌬️",
                     "meditation"    => "🧘",
                     "nutrition"     => "🥗",
@@ -1119,22 +3874,23 @@ pub fn videos_page(videos: &[VideoWithUploader]) -> String {
                     "mental-health" => "🧠",
                     _               => "🌿",
                 };
-                format!(r#"<div class="video-thumb">{icon}</div>"#)
+                html!(r#"<div class="video-thumb">{icon}</div>"#, icon)
             } else {
-                format!(r#"<div class="video-thumb"><img src="{}" alt="thumbnail" loading="lazy"></div>"#, v.thumbnail_url)
+                html!(r#"<div class="video-thumb"><img src="{}" alt="thumbnail" loading="lazy"></div>"#, Url(&v.thumbnail_url))
             };
             let desc = if v.description.is_empty() {
-                String::new()
+                TrustedHtml::default()
             } else {
                 let short: String = v.description.chars().take(90).collect();
+                let short = Text(&short);
                 let ellipsis = if v.description.len() > 90 { "…" } else { "" };
-                format!(r#"<p class="text-muted mb-3" style="font-size:.88rem;line-height:1.5">{short}{ellipsis}</p>"#)
+                html!(r#"<p class="text-muted mb-3" style="font-size:.88rem;line-height:1.5">{short}{ellipsis}</p>"#, short, ellipsis)
             };
-            let id = &v.id;
-            let title = &v.title;
-            let uploader = &v.uploader_name;
-            let date = &v.created_at[..10];
-            format!(r#"<div class="col-12 col-sm-6 col-lg-4 video-item" data-category="{cat_slug}">
+            let id = Attr(&v.id);
+            let title = Text(&v.title);
+            let uploader = Text(&v.uploader_name);
+            let date = Text(v.created_at.get(..10).unwrap_or_default());
+            html!(r#"<div class="col-12 col-sm-6 col-lg-4 video-item" data-category="{cat_slug}">
     <div class="video-card">
         <a href="/videos/{id}" style="text-decoration:none;color:inherit;">
             {thumb}
@@ -1149,23 +3905,22 @@ pub fn videos_page(videos: &[VideoWithUploader]) -> String {
             </div>
         </a>
     </div>
-</div>"#)
-        }).collect::<Vec<_>>().join("\n")
+</div>"#, cat_slug, id, thumb, cat_label, title, desc, uploader, date)
+        }), "\n")
     };
 
     let category_pills = {
-        let mut pills = String::from(
+        let mut pills = TrustedHtml::new(
             r#"<button class="filter-pill active me-2 mb-2" onclick="filterVideos('all', this)">All</button>"#,
         );
         for (slug, label) in CATEGORIES {
-            pills.push_str(&format!(
-                r#"<button class="filter-pill me-2 mb-2" onclick="filterVideos('{slug}', this)">{label}</button>"#
-            ));
+            pills.push(html!(
+                r#"<button class="filter-pill me-2 mb-2" onclick="filterVideos('{slug}', this)">{label}</button>"#, slug, label));
         }
         pills
     };
 
-    let content = format!(
+    let content = html!(
         r#"<!-- Header -->
 <div class="d-flex flex-wrap align-items-center justify-content-between gap-3 mb-4">
     <div>
@@ -1193,7 +3948,9 @@ function filterVideos(cat, btn) {{
         el.style.display = (cat === 'all' || el.dataset.category === cat) ? '' : 'none';
     }});
 }}
-</script>"#
+</script>"#,
+        category_pills,
+        video_cards
     );
 
     base_layout("Videos", &content, true)
@@ -1201,16 +3958,18 @@ function filterVideos(cat, btn) {{
 
 // ── New video form ─────────────────────────────────────────────────────────────
 
-pub fn new_video_page(error: Option<&str>) -> String {
-    let alert = error.map(|e| error_alert(e)).unwrap_or_default();
+pub fn new_video_page(csrf: &str, error: Option<&str>) -> String {
+    let csrf = csrf_field(csrf);
+    let alert = error.map(error_alert).unwrap_or_default();
 
-    let category_options = CATEGORIES
-        .iter()
-        .map(|(slug, label)| format!(r#"<option value="{slug}">{label}</option>"#))
-        .collect::<Vec<_>>()
-        .join("\n");
+    let category_options = TrustedHtml::join(
+        CATEGORIES
+            .iter()
+            .map(|(slug, label)| html!(r#"<option value="{slug}">{label}</option>"#, slug, label)),
+        "\n",
+    );
 
-    let content = format!(
+    let content = html!(
         r#"{alert}
 
 <div class="row justify-content-center">
@@ -1220,6 +3979,7 @@ pub fn new_video_page(error: Option<&str>) -> String {
         <p class="text-muted mb-4">Share a wellness video with the community. Paste a direct video URL&nbsp;(.mp4, .webm, etc.).</p>
 
         <form method="POST" action="/videos">
+            {csrf}
 
             <div class="card p-4 mb-4">
                 <!-- Title -->
@@ -1275,7 +4035,10 @@ pub fn new_video_page(error: Option<&str>) -> String {
         </form>
 
     </div>
-</div>"#
+</div>"#,
+        alert,
+        csrf,
+        category_options
     );
 
     base_layout("Add Video", &content, true)
@@ -1284,26 +4047,27 @@ pub fn new_video_page(error: Option<&str>) -> String {
 // ── Video player page ──────────────────────────────────────────────────────────
 
 pub fn video_player_page(video: &VideoWithUploader) -> String {
-    let cat_slug = &video.category;
-    let cat_label = category_label(cat_slug);
-    let title = &video.title;
-    let description = &video.description;
-    let uploader = &video.uploader_name;
-    let date = &video.created_at[..10];
-    let video_url = &video.video_url;
-
-    let desc_block = if description.is_empty() {
-        String::new()
+    let cat_label = category_label(&video.category);
+    let cat_slug = Attr(&video.category);
+    let title = Text(&video.title);
+    let description = Text(&video.description);
+    let uploader = Text(&video.uploader_name);
+    let date = Text(video.created_at.get(..10).unwrap_or_default());
+    let video_url = Url(&video.video_url);
+
+    let desc_block = if video.description.is_empty() {
+        TrustedHtml::default()
     } else {
-        format!(
+        html!(
             r#"<div class="card p-4 mb-4">
     <h5 class="fw-bold text-calm mb-2">About this video</h5>
     <p class="mb-0" style="line-height:1.7">{description}</p>
-</div>"#
+</div>"#,
+            description
         )
     };
 
-    let content = format!(
+    let content = html!(
         r#"<div class="row justify-content-center">
     <div class="col-12 col-lg-9">
 
@@ -1331,35 +4095,45 @@ pub fn video_player_page(video: &VideoWithUploader) -> String {
         {desc_block}
 
     </div>
-</div>"#
+</div>"#,
+        video_url,
+        cat_slug,
+        cat_label,
+        title,
+        uploader,
+        date,
+        desc_block
     );
 
-    base_layout(title, &content, true)
+    base_layout(&video.title, &content, true)
 }
 
 // ── Newsletter archive page ────────────────────────────────────────────────────
 
-pub fn newsletter_page(articles: &[NewsletterArticle]) -> String {
+pub fn newsletter_page(csrf: &str, articles: &[NewsletterArticle]) -> String {
+    let csrf = csrf_field(csrf);
     let article_cards = if articles.is_empty() {
-        r#"<div class="col-12 text-center py-5">
+        TrustedHtml::new(
+            r#"<div class="col-12 text-center py-5">
     <div style="font-size:4rem">📬</div>
     <h4 class="fw-bold text-calm mt-3 mb-2">No issues yet</h4>
     <p class="text-muted">The first newsletter issue will appear here once it's published.</p>
-</div>"#
-            .to_string()
+</div>"#,
+        )
     } else {
-        articles.iter().map(|a| {
-            let id = &a.id;
-            let title = &a.title;
-            let date = &a.published_at[..10];
+        TrustedHtml::join(articles.iter().map(|a| {
+            let id = Attr(&a.id);
+            let title = Text(&a.title);
+            let date = Text(a.published_at.get(..10).unwrap_or_default());
             let summary = if a.summary.is_empty() {
-                String::new()
+                TrustedHtml::default()
             } else {
                 let short: String = a.summary.chars().take(120).collect();
+                let short = Text(&short);
                 let ellipsis = if a.summary.len() > 120 { "…" } else { "" };
-                format!(r#"<p class="text-muted mb-3" style="font-size:.9rem;line-height:1.6">{short}{ellipsis}</p>"#)
+                html!(r#"<p class="text-muted mb-3" style="font-size:.9rem;line-height:1.6">{short}{ellipsis}</p>"#, short, ellipsis)
             };
-            format!(r#"<div class="col-12 col-md-6 col-lg-4">
+            html!(r#"<div class="col-12 col-md-6 col-lg-4">
     <div class="newsletter-card h-100">
         <div class="p-4 d-flex flex-column h-100">
             <small class="text-muted mb-2">📅 {date}</small>
@@ -1372,11 +4146,11 @@ pub fn newsletter_page(articles: &[NewsletterArticle]) -> String {
             </div>
         </div>
     </div>
-</div>"#)
-        }).collect::<Vec<_>>().join("\n")
+</div>"#, date, title, summary, id)
+        }), "\n")
     };
 
-    let content = format!(
+    let content = html!(
         r#"<!-- Hero + subscribe -->
 <div class="newsletter-hero mb-5">
     <div class="row align-items-center">
@@ -1391,6 +4165,7 @@ pub fn newsletter_page(articles: &[NewsletterArticle]) -> String {
             <div class="subscribe-box">
                 <p class="fw-semibold mb-3">Get the weekly digest — free, forever.</p>
                 <form method="POST" action="/newsletter/subscribe" class="d-flex gap-2 flex-wrap">
+                    {csrf}
                     <input type="email" name="email" class="form-control flex-fill"
                            placeholder="your@email.com" required style="min-width:180px;">
                     <button type="submit" class="btn btn-calm px-4">Subscribe</button>
@@ -1412,6 +4187,8 @@ pub fn newsletter_page(articles: &[NewsletterArticle]) -> String {
 </div>"#,
         count = articles.len(),
         plural = if articles.len() == 1 { "" } else { "s" },
+        csrf,
+        article_cards
     );
 
     base_layout("Newsletter", &content, false)
@@ -1419,15 +4196,16 @@ pub fn newsletter_page(articles: &[NewsletterArticle]) -> String {
 
 // ── Newsletter article page ────────────────────────────────────────────────────
 
-pub fn newsletter_article_page(article: &NewsletterArticle) -> String {
-    let title = &article.title;
-    let date = &article.published_at[..10];
-    let content_html = &article.content_html;
+pub fn newsletter_article_page(csrf: &str, article: &NewsletterArticle) -> String {
+    let csrf = csrf_field(csrf);
+    let title = Text(&article.title);
+    let date = Text(article.published_at.get(..10).unwrap_or_default());
+    let content_html = TrustedHtml::new(sanitize::clean(&article.content_html));
 
     let sources_block = if article.source_urls.is_empty() {
-        String::new()
+        TrustedHtml::default()
     } else {
-        let chips: String = article
+        let chips: TrustedHtml = article
             .source_urls
             .split(',')
             .filter(|s| !s.trim().is_empty())
@@ -1439,18 +4217,20 @@ pub fn newsletter_article_page(article: &NewsletterArticle) -> String {
                     .split('/')
                     .next()
                     .unwrap_or(url);
-                format!(r#"<a href="{url}" target="_blank" rel="noopener" class="source-chip">{label}</a>"#)
+                let (url, label) = (Url(url), Text(label));
+                html!(r#"<a href="{url}" target="_blank" rel="noopener" class="source-chip">{label}</a>"#, url, label)
             })
             .collect();
-        format!(
+        html!(
             r#"<div class="card p-4 mt-4">
     <h6 class="fw-bold text-calm mb-2">📎 Sources</h6>
     <div>{chips}</div>
-</div>"#
+</div>"#,
+            chips
         )
     };
 
-    let content = format!(
+    let content = html!(
         r#"<div class="row justify-content-center">
     <div class="col-12 col-lg-8">
 
@@ -1478,6 +4258,7 @@ pub fn newsletter_article_page(article: &NewsletterArticle) -> String {
             <h5 class="fw-bold mb-2">Enjoyed this issue?</h5>
             <p class="mb-3" style="opacity:.9">Get the weekly digest delivered to your inbox.</p>
             <form method="POST" action="/newsletter/subscribe" class="d-flex gap-2 flex-wrap">
+                {csrf}
                 <input type="hidden" name="name" value="">
                 <input type="email" name="email" class="form-control"
                        placeholder="your@email.com" required
@@ -1487,17 +4268,24 @@ pub fn newsletter_article_page(article: &NewsletterArticle) -> String {
         </div>
 
     </div>
-</div>"#
+</div>"#,
+        date,
+        title,
+        content_html,
+        sources_block,
+        csrf
     );
 
-    base_layout(title, &content, false)
+    base_layout(&article.title, &content, false)
 }
 
 // ── Newsletter subscribe page ──────────────────────────────────────────────────
 
-pub fn newsletter_subscribe_page(success: bool, error: Option<&str>) -> String {
+pub fn newsletter_subscribe_page(csrf: &str, success: bool, error: Option<&str>) -> String {
+    let csrf = csrf_field(csrf);
     let body = if success {
-        r#"<div class="text-center py-4">
+        TrustedHtml::new(
+            r#"<div class="text-center py-4">
     <div style="font-size:4rem">🎉</div>
     <h3 class="fw-bold text-calm mt-3 mb-2">You're subscribed!</h3>
     <p class="text-muted mb-4">
@@ -1505,11 +4293,11 @@ pub fn newsletter_subscribe_page(success: bool, error: Option<&str>) -> String {
         You'll receive your first issue next Monday.
     </p>
     <a href="/newsletter" class="btn btn-calm px-5 py-2">Browse Past Issues →</a>
-</div>"#
-            .to_string()
+</div>"#,
+        )
     } else {
-        let alert = error.map(|e| error_alert(e)).unwrap_or_default();
-        format!(
+        let alert = error.map(error_alert).unwrap_or_default();
+        html!(
             r#"{alert}
 <div class="text-center mb-4">
     <div style="font-size:3.5rem">📬</div>
@@ -1517,6 +4305,7 @@ pub fn newsletter_subscribe_page(success: bool, error: Option<&str>) -> String {
     <p class="text-muted">Weekly wellness insights. No spam, ever. Unsubscribe anytime.</p>
 </div>
 <form method="POST" action="/newsletter/subscribe">
+    {csrf}
     <div class="mb-3">
         <label class="form-label" for="name">Name <span class="text-muted fw-normal">(optional)</span></label>
         <input type="text" id="name" name="name" class="form-control"
@@ -1536,11 +4325,13 @@ pub fn newsletter_subscribe_page(success: bool, error: Option<&str>) -> String {
 <p class="text-muted text-center mt-3" style="font-size:.82rem">
     By subscribing you agree to receive weekly emails from CalmControl.
     You can unsubscribe at any time.
-</p>"#
+</p>"#,
+            alert,
+            csrf
         )
     };
 
-    let content = format!(
+    let content = html!(
         r#"<div class="row justify-content-center">
     <div class="col-12 col-sm-9 col-md-6 col-lg-5">
         <div class="card p-4 p-md-5">
@@ -1550,7 +4341,8 @@ pub fn newsletter_subscribe_page(success: bool, error: Option<&str>) -> String {
             <a href="/newsletter" class="text-muted" style="font-size:.9rem">← Back to Newsletter</a>
         </div>
     </div>
-</div>"#
+</div>"#,
+        body
     );
 
     base_layout("Subscribe", &content, false)
@@ -1581,14 +4373,15 @@ pub fn newsletter_unsubscribe_page(success: bool) -> String {
 <a href="/newsletter" class="btn btn-calm px-4">Back to Newsletter</a>"#
     };
 
-    let content = format!(
+    let content = html!(
         r#"<div class="row justify-content-center">
     <div class="col-12 col-sm-9 col-md-6 col-lg-5">
         <div class="card p-5 text-center">
             {body}
         </div>
     </div>
-</div>"#
+</div>"#,
+        body
     );
 
     base_layout("Unsubscribe", &content, false)
@@ -0,0 +1,15 @@
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+/// Generate a random single-use token for an emailed link. Returns the raw
+/// token (sent to the user) and its hash (the only thing we store).
+pub fn generate() -> (String, String) {
+    let raw = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+    let hashed = hash(&raw);
+    (raw, hashed)
+}
+
+/// SHA-256 hex digest of a token, so a leaked database can't be replayed.
+pub fn hash(raw: &str) -> String {
+    format!("{:x}", Sha256::digest(raw.as_bytes()))
+}
@@ -0,0 +1,216 @@
+//! RFC 6238 time-based one-time passwords (HMAC-SHA1, 6 digits, 30 s steps),
+//! the defaults every authenticator app understands.
+
+use base32::Alphabet;
+use hmac::{Hmac, Mac};
+use qrcode::{QrCode, render::svg};
+use rand::{Rng, RngCore};
+use sha1::Sha1;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const STEP_SECONDS: u64 = 30;
+const DIGITS: u32 = 6;
+/// Accept codes one step either side of now to tolerate clock drift.
+const SKEW_STEPS: u64 = 1;
+const ISSUER: &str = "CalmControl";
+
+const SECRET_ALPHABET: Alphabet = Alphabet::Rfc4648 { padding: false };
+
+/// A new random 160-bit secret, base32 encoded for display and storage.
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 20];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    base32::encode(SECRET_ALPHABET, &bytes)
+}
+
+/// `otpauth://` URI understood by authenticator apps.
+pub fn otpauth_uri(secret: &str, account: &str) -> String {
+    format!(
+        "otpauth://totp/{issuer}:{account}?secret={secret}&issuer={issuer}&digits={DIGITS}&period={STEP_SECONDS}",
+        issuer = percent_encode(ISSUER),
+        account = percent_encode(account),
+    )
+}
+
+/// Inline `<svg>` QR code for an otpauth URI.
+pub fn qr_svg(uri: &str) -> Option<String> {
+    let code = QrCode::new(uri.as_bytes()).ok()?;
+    let image = code
+        .render::<svg::Color>()
+        .min_dimensions(200, 200)
+        .quiet_zone(true)
+        .build();
+    // Drop the XML prolog so the markup can be embedded in HTML
+    image.find("<svg").map(|i| image[i..].to_string())
+}
+
+fn current_step() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() / STEP_SECONDS)
+        .unwrap_or(0)
+}
+
+fn code_at(key: &[u8], step: u64) -> u32 {
+    let mut mac = Hmac::<Sha1>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(&step.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+
+    // Dynamic truncation, RFC 4226 §5.3
+    let offset = (digest[19] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+    binary % 10u32.pow(DIGITS)
+}
+
+/// Check `code` against `secret`. Returns the matching time step so callers
+/// can reject replays of a step that was already used.
+pub fn verify(secret: &str, code: &str, last_used_step: Option<u64>) -> Option<u64> {
+    verify_at(secret, code, last_used_step, current_step())
+}
+
+/// [`verify`] as of time step `now`.
+fn verify_at(secret: &str, code: &str, last_used_step: Option<u64>, now: u64) -> Option<u64> {
+    let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+    if code.len() != DIGITS as usize {
+        return None;
+    }
+    let code: u32 = code.parse().ok()?;
+    let key = base32::decode(SECRET_ALPHABET, secret)?;
+
+    (now.saturating_sub(SKEW_STEPS)..=now + SKEW_STEPS)
+        .filter(|step| last_used_step.is_none_or(|last| *step > last))
+        .find(|step| code_at(&key, *step) == code)
+}
+
+/// Ten single-use recovery codes in `xxxxx-xxxxx` form.
+pub fn generate_recovery_codes() -> Vec<String> {
+    const CHARS: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+    let mut rng = rand::thread_rng();
+    (0..10)
+        .map(|_| {
+            let mut code: String = (0..10)
+                .map(|_| CHARS[rng.gen_range(0..CHARS.len())] as char)
+                .collect();
+            code.insert(5, '-');
+            code
+        })
+        .collect()
+}
+
+/// Canonical form of a recovery code as typed by a user, for hashing.
+pub fn normalize_recovery_code(code: &str) -> String {
+    code.trim().to_lowercase().replace([' ', '-'], "")
+}
+
+fn percent_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'@' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The RFC 6238 appendix B SHA-1 seed, "12345678901234567890".
+    const RFC_KEY: &[u8] = b"12345678901234567890";
+
+    fn rfc_secret() -> String {
+        base32::encode(SECRET_ALPHABET, RFC_KEY)
+    }
+
+    fn code_string(step: u64) -> String {
+        format!("{:06}", code_at(RFC_KEY, step))
+    }
+
+    #[test]
+    fn matches_rfc_6238_sha1_vectors() {
+        // (Unix time, 8-digit code from the RFC); we use the last 6 digits
+        let vectors = [
+            (59, "94287082"),
+            (1111111109, "07081804"),
+            (1111111111, "14050471"),
+            (1234567890, "89005924"),
+            (2000000000, "69279037"),
+            (20000000000, "65353130"),
+        ];
+        for (time, expected) in vectors {
+            assert_eq!(
+                code_string(time / STEP_SECONDS),
+                &expected[2..],
+                "T = {time}"
+            );
+        }
+    }
+
+    #[test]
+    fn verify_accepts_the_current_code() {
+        let step = 1234567890 / STEP_SECONDS;
+        assert_eq!(verify_at(&rfc_secret(), "005924", None, step), Some(step));
+        assert_eq!(
+            verify_at(&rfc_secret(), " 005 924 ", None, step),
+            Some(step)
+        );
+    }
+
+    #[test]
+    fn verify_rejects_wrong_and_malformed_codes() {
+        let step = 1234567890 / STEP_SECONDS;
+        let secret = rfc_secret();
+        assert_eq!(verify_at(&secret, "005925", None, step), None);
+        assert_eq!(verify_at(&secret, "05924", None, step), None);
+        assert_eq!(verify_at(&secret, "0059240", None, step), None);
+        assert_eq!(verify_at(&secret, "00592a", None, step), None);
+        assert_eq!(verify_at("not base32!", "005924", None, step), None);
+    }
+
+    #[test]
+    fn verify_allows_one_step_of_clock_skew() {
+        let step = 1234567890 / STEP_SECONDS;
+        let secret = rfc_secret();
+        let code = code_string(step);
+
+        assert_eq!(verify_at(&secret, &code, None, step - 1), Some(step));
+        assert_eq!(verify_at(&secret, &code, None, step + 1), Some(step));
+        assert_eq!(verify_at(&secret, &code, None, step - 2), None);
+        assert_eq!(verify_at(&secret, &code, None, step + 2), None);
+    }
+
+    #[test]
+    fn verify_rejects_replayed_and_older_steps() {
+        let step = 1234567890 / STEP_SECONDS;
+        let secret = rfc_secret();
+        let code = code_string(step);
+
+        assert_eq!(verify_at(&secret, &code, Some(step), step), None);
+        assert_eq!(verify_at(&secret, &code, Some(step + 1), step), None);
+        // A code from a later step than the last one used still works
+        assert_eq!(verify_at(&secret, &code, Some(step - 1), step), Some(step));
+        let previous = code_string(step - 1);
+        assert_eq!(verify_at(&secret, &previous, Some(step - 1), step), None);
+    }
+
+    #[test]
+    fn recovery_codes_normalise_as_typed() {
+        let codes = generate_recovery_codes();
+        assert_eq!(codes.len(), 10);
+        for code in &codes {
+            assert_eq!(code.len(), 11);
+            assert_eq!(
+                normalize_recovery_code(&code.to_uppercase()),
+                code.replace('-', "")
+            );
+        }
+        assert_eq!(normalize_recovery_code(" abcde fghjk "), "abcdefghjk");
+    }
+}